@@ -0,0 +1,37 @@
+//! Showcases displaying `indicatif` progress bars
+use clap::{App, Arg};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use klask::Settings;
+use std::{thread, time::Duration};
+
+fn main() {
+    let app = App::new("Indicatif").arg(Arg::new("files").long("files").default_value("3"));
+    klask::run_app(app, Settings::default(), |matches| {
+        let files: u64 = matches.value_of_t("files").unwrap_or(3);
+        let multi = MultiProgress::with_draw_target(klask::output::indicatif_draw_target());
+        let style = ProgressStyle::with_template("[{elapsed}] {bar:40} {pos}/{len} {msg}").unwrap();
+
+        let spinner = multi.add(ProgressBar::new_spinner());
+        spinner.set_message("Connecting");
+
+        let handles: Vec<_> = (0..files)
+            .map(|i| {
+                let bar = multi.add(ProgressBar::new(100).with_style(style.clone()));
+                bar.set_message(format!("file-{}.txt", i));
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        thread::sleep(Duration::from_millis(20 + 10 * i));
+                        bar.inc(1);
+                    }
+                    bar.finish_with_message("done");
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            spinner.tick();
+            handle.join().unwrap();
+        }
+        spinner.finish_and_clear();
+    });
+}
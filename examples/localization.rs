@@ -1,5 +1,5 @@
 use clap::{Parser, ValueHint};
-use klask::{Localization, Settings};
+use klask::{Localization, Message, PluralRule, Settings};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
@@ -21,6 +21,8 @@ fn main() {
     settings.enable_env = Some("Additional env description!".into());
     settings.enable_stdin = Some("Additional stdin description!".into());
     settings.enable_working_dir = Some("Additional working dir description!".into());
+    settings.enable_menu_bar = true;
+    settings.enable_help_tab = true;
     settings.localization = polish_localization_exaple();
 
     klask::run_derived::<LocalizationExample, _>(settings, |_| {})
@@ -31,19 +33,144 @@ fn polish_localization_exaple() -> Localization {
     loc.optional = "(Opcjonalne)".into();
     loc.select_file = "Wybierz plik...".into();
     loc.select_directory = "Wybierz folder...".into();
+    loc.bookmarks = "Zakładki".into();
     loc.new_value = "Nowa wartość".into();
+    loc.paste_list = "Wklej listę".into();
     loc.reset = "Wyczyść".into();
     loc.reset_to_default = "Przywróć domyślną".into();
-    loc.error_is_required = ("Argument '".into(), "' jest wymagany".into());
+    loc.error_is_required = "Argument '{name}' jest wymagany".into();
+    loc.error_hyphen_values =
+        "Argument '{name}' nie może przyjąć kilku wartości zaczynających się od '-'".into();
+    loc.error_unknown_argument = "Nieznany argument '{name}'".into();
+    loc.error_unknown_subcommand = "Nieznane podpolecenie '{name}'".into();
+    loc.error_invalid_value = "Nieprawidłowa wartość '{value}' argumentu '{name}'".into();
+    loc.error_hook_failed = "Polecenie '{command}' nie powiodło się".into();
+    loc.error_unknown_placeholder = "Nieznany symbol zastępczy '{placeholder}'".into();
     loc.arguments = "Argumenty".into();
     loc.env_variables = "Zmienne środowiskowe".into();
     loc.error_env_var_cant_be_empty = "Zmienna środowiskowa nie może być pusta".into();
     loc.input = "Wejście".into();
+    loc.help = "Pomoc".into();
     loc.text = "Tekst".into();
     loc.file = "Plik".into();
+    loc.syntax_highlighting = "Podświetlanie składni".into();
+    loc.plain_text = "Zwykły tekst".into();
+    loc.paste_clipboard = "Wklej ze schowka".into();
     loc.working_directory = "Katalog roboczy".into();
+    loc.resolves_to = "Wskazuje na '{path}'".into();
+    loc.path_doesnt_exist = "'{path}' nie istnieje".into();
     loc.run = "Uruchom".into();
+    loc.rerun = "Uruchom ponownie".into();
+    loc.pin = "Przypnij do ulubionych".into();
+    loc.unpin = "Odepnij".into();
     loc.kill = "Zakończ".into();
+    loc.stop_gracefully = "Zatrzymaj łagodnie".into();
     loc.running = "Działa".into();
+    loc.validating = "Sprawdzanie".into();
+    loc.feeding_input = "Przekazywanie wejścia ({progress})".into();
+    loc.notification_success = "Zakończono po {duration}".into();
+    loc.notification_failure = "Błąd po {duration}".into();
+    loc.close_confirmation = "Program jest uruchomiony. Zakończyć go i wyjść?".into();
+    loc.kill_and_exit = "Zakończ i wyjdź".into();
+    loc.cancel = "Anuluj".into();
+    loc.reset_all = "Wyczyść wszystko".into();
+    loc.reset_all_confirmation = "Przywrócić wszystko do wartości domyślnych?".into();
+    loc.kill_confirmation = "Program działa od {duration}. Zakończyć go?".into();
+    loc.run_blocked = "Przed uruchomieniem popraw:".into();
+    loc.show_flags = "Pokaż flagi".into();
+    loc.aliases = "Aliasy".into();
+    loc.terminal = "Terminal".into();
+    loc.run_in_terminal = "Uruchom w terminalu".into();
+    loc.send_input = "Wejście, wysyłane Enterem".into();
+    loc.end_input = "Zakończ wejście".into();
+    loc.batch = "Seria".into();
+    loc.parallel_runs = "Równoległe uruchomienia".into();
+    loc.run_batch = "Uruchom serię".into();
+    loc.hooks = "Polecenia pomocnicze".into();
+    loc.command_preview = "Polecenie".into();
+    loc.preferences = "Preferencje".into();
+    loc.theme = "Motyw".into();
+    loc.default = "Domyślny".into();
+    loc.theme_light = "Jasny".into();
+    loc.theme_dark = "Ciemny".into();
+    loc.theme_high_contrast = "Wysoki kontrast".into();
+    loc.ansi_palette = "Kolory wyjścia".into();
+    loc.palette_standard = "Standardowe".into();
+    loc.palette_deuteranopia = "Dla daltonistów".into();
+    loc.ui_scale = "Skala interfejsu".into();
+    loc.language = "Język".into();
+    loc.restart_to_apply = "Zostanie zastosowany po ponownym uruchomieniu".into();
+    loc.output_font_size = "Rozmiar czcionki wyniku".into();
+    loc.notify_on_finish = "Powiadamiaj o zakończeniu".into();
+    loc.reset_preferences = "Przywróć domyślne preferencje".into();
+    loc.wizard_step = "Krok {current} z {total}".into();
+    loc.back = "Wstecz".into();
+    loc.next = "Dalej".into();
+    loc.review = "Podsumowanie".into();
+    loc.program_crashed = "Program uległ awarii".into();
+    loc.backtrace = "Ślad stosu".into();
+    loc.closing_in = Message::plural([
+        "Zamknięcie za {count} sekundę",
+        "Zamknięcie za {count} sekundy",
+        "Zamknięcie za {count} sekund",
+    ]);
+    loc.menu_file = "Plik".into();
+    loc.menu_run = "Uruchamianie".into();
+    loc.menu_view = "Widok".into();
+    loc.menu_help = "Pomoc".into();
+    loc.export_completions = "Eksportuj uzupełnianie powłoki".into();
+    loc.export_man_page = "Eksportuj stronę man".into();
+    loc.quit = "Wyjdź".into();
+    loc.light_theme = "Jasny motyw".into();
+    loc.dark_theme = "Ciemny motyw".into();
+    loc.zoom_in = "Powiększ".into();
+    loc.zoom_out = "Pomniejsz".into();
+    loc.reset_zoom = "Przywróć powiększenie".into();
+    loc.about = "O programie".into();
+    loc.drop_files_into = "Które pole wypełnić upuszczonymi plikami?".into();
+    loc.save_log = "Zapisz wynik do pliku".into();
+    loc.record_session = "Nagrywaj sesję".into();
+    loc.replay_session = "Odtwórz sesję...".into();
+    loc.replay_speed = "Prędkość odtwarzania".into();
+    loc.config_file = "Plik konfiguracyjny".into();
+    loc.run_history = "Uruchomienie {current} z {total}".into();
+    loc.compare_runs = "Porównaj".into();
+    loc.run_records = "Poprzednie uruchomienia".into();
+    loc.statistics = "Statystyki".into();
+    loc.subcommand = "Podkomenda".into();
+    loc.runs = "Uruchomienia".into();
+    loc.success_rate = "Odsetek sukcesów".into();
+    loc.average = "Średnia".into();
+    loc.median = "Mediana".into();
+    loc.percentile_95 = "95. percentyl".into();
+    loc.exit_code = "Kod wyjścia".into();
+    loc.duration = "Czas trwania".into();
+    loc.output = "Wynik".into();
+    loc.load = "Wczytaj".into();
+    loc.save = "Zapisz".into();
+    loc.config_file_unavailable = "Wybrana podkomenda nie przyjmuje pliku konfiguracyjnego".into();
+    loc.unsaved_config_edits =
+        "Ścieżka się zmieniła, ale zmiany w poprzednim pliku nie są zapisane".into();
+    loc.save_to_previous = "Zapisz je w '{path}'".into();
+    loc.discard_edits = "Odrzuć je".into();
+    loc.keep_edits = "Zachowaj je dla nowego pliku".into();
+    loc.plural_rule = PluralRule::Polish;
+    loc.palette_hint = "Przejdź do argumentu lub podkomendy...".into();
+    loc.copy_output = "Kopiuj wynik".into();
+    loc.copy_plain_text = "Zwykły tekst".into();
+    loc.copy_ansi_text = "Tekst z kolorami ANSI".into();
+    loc.copy_html = "HTML z kolorami".into();
+    loc.split_view = "Podziel widok".into();
+    loc.split_view_hover = "Surowy tekst obok postępu, tabel i innych elementów".into();
+    loc.scroll_to_end = "Przewiń na koniec".into();
+    loc.view_as_table = "Pokaż jako tabelę".into();
+    loc.view_as_text = "Pokaż jako tekst".into();
+    loc.show_only_level = "Pokaż tylko wiersze {level}".into();
+    loc.show_all_lines = "Pokaż wszystkie wiersze".into();
+    loc.level_only = "Tylko {level}".into();
+    loc.copy_json_path = "Kopiuj ścieżkę".into();
+    loc.copy_json_value = "Kopiuj wartość".into();
+    loc.error_validation_stopped = "Walidacja nieoczekiwanie się zatrzymała".into();
+    loc.error_hooks_stopped = "Polecenia pomocnicze nieoczekiwanie się zatrzymały".into();
     loc
 }
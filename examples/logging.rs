@@ -0,0 +1,34 @@
+//! Showcases `log` and `tracing` integration
+use clap::Parser;
+use klask::Settings;
+
+#[derive(Debug, Parser)]
+enum Logging {
+    /// Log with the `log` crate
+    Log,
+    /// Log with the `tracing` crate
+    Tracing,
+}
+
+fn main() {
+    klask::run_derived::<Logging, _>(Settings::default(), |logging| match logging {
+        Logging::Log => {
+            klask::output::Logger::init(log::LevelFilter::Trace).unwrap();
+            log::trace!("Trace message");
+            log::debug!("Debug message");
+            log::info!("Info message");
+            log::warn!("Warn message");
+            log::error!("Error message");
+        }
+        Logging::Tracing => {
+            tracing::subscriber::set_global_default(klask::output::tracing_subscriber()).unwrap();
+            tracing::info!("Starting");
+            for file in ["a.txt", "b.txt"] {
+                let _span = tracing::info_span!("download", file).entered();
+                tracing::debug!(bytes = 1024, "Downloaded");
+                tracing::warn!("Checksum missing");
+            }
+            tracing::error!(code = 3, "Finished with errors");
+        }
+    });
+}
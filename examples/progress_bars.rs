@@ -7,6 +7,18 @@ fn main() {
     klask::run_app(App::new("Progress bars"), Settings::default(), |_| {
         const MAX: u64 = 100;
 
+        for i in 0..=MAX {
+            klask::output::progress_bar("Preparing", i as f32 / MAX as f32);
+            thread::sleep(Duration::from_millis(5));
+        }
+        // Transient bars can be removed once they are done
+        klask::output::remove("Preparing");
+        // Marks can be jumped to from the bookmarks menu
+        klask::output::mark("Main work");
+
+        let group = klask::output::progress_group("Group", "Grouped progress");
+        let nested = group.group("Nested", "Nested group");
+
         for i in 0..=MAX {
             // You must pass in a value between [0, 1]
             klask::output::progress_bar("Static description", i as f32 / MAX as f32);
@@ -15,10 +27,20 @@ fn main() {
                 &format!("Dynamic description [{}/{}]", i, MAX),
                 i as f32 / MAX as f32,
             );
+            group.progress_bar("First in group", i as f32 / MAX as f32);
+            nested.progress_bar("Second in group", (i as f32 / MAX as f32).powi(2));
+            nested.progress_bar("Third in group", (i as f32 / MAX as f32).sqrt());
+            klask::output::progress_bytes("Bytes", "Downloading", i * 123_456, MAX * 123_456);
 
+            klask::output::set_result("Steps done", i);
             thread::sleep(Duration::from_millis(20));
         }
 
+        klask::output::progress_finish("Static description");
+        klask::output::progress_fail("Progress", "Failed on purpose");
+        group.finish("First in group");
+        klask::output::set_result("Status", "Finished");
+
         println!("Finished!")
     });
 }
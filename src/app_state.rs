@@ -1,41 +1,177 @@
-use crate::{arg_state::ArgState, settings::Localization};
-use clap::Command;
-use eframe::egui::{widgets::Widget, Grid, Response, Ui};
+use crate::{
+    arg_state::{ArgKind, ArgOptions, ArgState, FormContext},
+    localization::Localization,
+    markdown,
+    palette::PaletteEntry,
+    LayoutItem,
+};
+use clap::{Arg, Command};
+use eframe::egui::{ComboBox, Grid, Response, RichText, Ui};
 use inflector::Inflector;
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 use uuid::Uuid;
 
+/// A path argument that dropped files can fill.
 #[derive(Debug, Clone)]
-pub struct AppState<'s> {
+pub struct DropTarget {
+    /// Subcommand names leading to the argument
+    pub path: Vec<String>,
+    /// Index of the argument in its subcommand
+    pub arg: usize,
+    pub label: String,
+    pub multiple: bool,
+    pub focused: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppState {
     id: Uuid,
     about: Option<String>,
-    args: Vec<ArgState<'s>>,
-    subcommands: BTreeMap<String, AppState<'s>>,
+    args: Vec<ArgState>,
+    subcommands: BTreeMap<String, Subcommand>,
     current: Option<String>,
+    /// Groups of arguments where exactly one has to be passed
+    choices: Vec<Choice>,
+    localization: Arc<Localization>,
+    options: Arc<ArgOptions>,
+}
+
+/// A page of the wizard, see [`crate::Settings::enable_wizard`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WizardStep {
+    /// Subcommand names leading to the command
+    pub path: Vec<String>,
+    pub kind: StepKind,
+    /// Whether it's the first step of the command, which shows its about text
+    pub first: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepKind {
+    /// Arguments with a help heading, or without one
+    Args(Option<String>),
+    /// Choosing the subcommand
+    Subcommand,
+}
+
+/// A required group of arguments that can't be used together, shown as
+/// a drop-down for choosing the argument and the value of the chosen one
+#[derive(Debug, Clone)]
+struct Choice {
+    label: String,
+    /// Indices of the arguments
+    members: Vec<usize>,
+    /// Index in `members`
+    selected: usize,
+}
+
+/// The state of a subcommand is only built when it's first selected,
+/// so applications with many subcommands start quickly
+#[derive(Debug, Clone)]
+struct Subcommand {
+    /// Sentence-cased name, shown every frame
+    label: String,
+    about: Option<String>,
+    state: Lazy,
+}
+
+#[derive(Debug, Clone)]
+enum Lazy {
+    Unbuilt(Box<Command<'static>>),
+    Built(AppState),
 }
 
-impl<'s> AppState<'s> {
-    pub fn new(app: &Command, localization: &'s Localization) -> Self {
-        let args = app
-            .get_arguments()
-            .filter(|a| a.get_id() != "help" && a.get_id() != "version")
-            .map(|a| ArgState::new(a, localization))
+impl Subcommand {
+    fn new(app: &Command<'static>) -> Self {
+        Self {
+            label: app.get_name().to_sentence_case(),
+            about: about(app),
+            state: Lazy::Unbuilt(Box::new(app.clone())),
+        }
+    }
+
+    /// Builds the state the first time it's needed
+    fn state_mut(
+        &mut self,
+        localization: &Arc<Localization>,
+        options: &Arc<ArgOptions>,
+    ) -> &mut AppState {
+        if let Lazy::Unbuilt(app) = &self.state {
+            let state = AppState::new(app, localization.clone(), options.clone());
+            self.state = Lazy::Built(state);
+        }
+        match &mut self.state {
+            Lazy::Built(state) => state,
+            Lazy::Unbuilt(_) => unreachable!(),
+        }
+    }
+
+    /// State of a selected subcommand, which is built when it's selected
+    fn state(&self) -> &AppState {
+        match &self.state {
+            Lazy::Built(state) => state,
+            Lazy::Unbuilt(_) => unreachable!("Selected subcommands are always built"),
+        }
+    }
+}
+
+impl AppState {
+    pub fn new(
+        app: &Command<'static>,
+        localization: Arc<Localization>,
+        options: Arc<ArgOptions>,
+    ) -> Self {
+        let mut args: Vec<ArgState> = shown_args(app, &options)
+            .map(|a| ArgState::new(a, localization.clone(), &options))
             .collect();
+        let choices = choices(app, &options, &mut args);
 
-        let subcommands = app
-            .get_subcommands()
-            .map(|app| (app.get_name().to_string(), AppState::new(app, localization)))
+        let subcommands = shown_subcommands(app, &options)
+            .map(|app| (app.get_name().to_string(), Subcommand::new(app)))
             .collect();
+        let current = shown_subcommands(app, &options)
+            .map(|app| app.get_name().to_string())
+            .next();
 
-        AppState {
+        let mut state = AppState {
             id: Uuid::new_v4(),
-            about: app.get_about().map(String::from),
+            about: about(app),
             args,
             subcommands,
-            current: app
-                .get_subcommands()
-                .map(|app| app.get_name().to_string())
-                .next(),
+            current,
+            choices,
+            localization,
+            options,
+        };
+        state.current_mut();
+        state
+    }
+
+    /// Builds the state again with another language, dropping entered values
+    pub fn relocalize(&mut self, app: &Command<'static>, localization: Arc<Localization>) {
+        *self = AppState::new(app, localization, self.options.clone());
+    }
+
+    /// The selected subcommand, built if it's selected for the first time
+    fn current_mut(&mut self) -> Option<&mut AppState> {
+        let current = self.current.as_ref()?;
+        let subcommand = self.subcommands.get_mut(current)?;
+        Some(subcommand.state_mut(&self.localization, &self.options))
+    }
+
+    /// Resets every argument, in this command and the subcommands
+    /// that were built, without changing which subcommands are selected
+    pub fn reset(&mut self) {
+        for arg in &mut self.args {
+            arg.reset();
+        }
+        for choice in &mut self.choices {
+            choice.selected = 0;
+        }
+        for subcommand in self.subcommands.values_mut() {
+            if let Lazy::Built(state) = &mut subcommand.state {
+                state.reset();
+            }
         }
     }
 
@@ -44,46 +180,501 @@ impl<'s> AppState<'s> {
             arg.update_validation_error(name, message);
         }
 
+        if let Some(current) = self.current_mut() {
+            current.update_validation_error(name, message);
+        }
+    }
+
+    pub fn palette_entries(&self, path: &mut Vec<String>, entries: &mut Vec<PaletteEntry>) {
+        for (index, arg) in self.args.iter().enumerate() {
+            entries.push(PaletteEntry {
+                path: path.clone(),
+                arg: Some(index),
+                label: arg.name.clone(),
+                detail: arg.desc.clone().unwrap_or_default(),
+            });
+        }
+
+        for (name, subcommand) in &self.subcommands {
+            path.push(name.clone());
+            entries.push(PaletteEntry {
+                path: path.clone(),
+                arg: None,
+                label: subcommand.label.clone(),
+                detail: subcommand.about.clone().unwrap_or_default(),
+            });
+            match &subcommand.state {
+                Lazy::Built(state) => state.palette_entries(path, entries),
+                Lazy::Unbuilt(app) => command_palette_entries(app, &self.options, path, entries),
+            }
+            path.pop();
+        }
+    }
+
+    /// Selects subcommands along `path` and focuses the argument at index `arg`.
+    pub fn jump_to(&mut self, path: &[String], arg: Option<usize>) {
+        match path.split_first() {
+            Some((name, rest)) => {
+                if self.subcommands.contains_key(name) {
+                    self.current = Some(name.clone());
+                    self.current_mut().unwrap().jump_to(rest, arg);
+                }
+            }
+            None => {
+                if let Some(index) = arg {
+                    self.select_choice(index);
+                }
+                if let Some(arg) = arg.and_then(|i| self.args.get_mut(i)) {
+                    arg.request_focus = true;
+                }
+            }
+        }
+    }
+
+    /// Names of the selected subcommands.
+    pub fn current_path(&self) -> Vec<String> {
+        match &self.current {
+            Some(current) => {
+                let mut path = vec![current.clone()];
+                path.extend(self.subcommands[current].state().current_path());
+                path
+            }
+            None => vec![],
+        }
+    }
+
+    /// Path arguments of this command and the selected subcommands.
+    pub fn drop_targets(&self, path: &mut Vec<String>, targets: &mut Vec<DropTarget>) {
+        for (index, arg) in self.args.iter().enumerate() {
+            if arg.is_path() && !self.is_unchosen(index) {
+                targets.push(DropTarget {
+                    path: path.clone(),
+                    arg: index,
+                    label: arg.name.clone(),
+                    multiple: arg.is_multiple(),
+                    focused: arg.has_focus,
+                });
+            }
+        }
+
         if let Some(current) = &self.current {
-            self.subcommands
+            path.push(current.clone());
+            self.subcommands[current]
+                .state()
+                .drop_targets(path, targets);
+            path.pop();
+        }
+    }
+
+    /// Fills the path argument at index `arg` in the subcommand at `path`.
+    pub fn fill_paths(&mut self, path: &[String], arg: usize, paths: &[String]) {
+        match path.split_first() {
+            Some((name, rest)) => {
+                if let Some(subcommand) = self.subcommands.get_mut(name) {
+                    subcommand
+                        .state_mut(&self.localization, &self.options)
+                        .fill_paths(rest, arg, paths);
+                }
+            }
+            None => {
+                if let Some(arg) = self.args.get_mut(arg) {
+                    arg.fill_paths(paths);
+                }
+            }
+        }
+    }
+
+    /// Fills the focused path argument with dropped paths, otherwise the first multi-value
+    /// path argument, otherwise the only path argument. If it's ambiguous, returns the
+    /// arguments the user has to choose from.
+    pub fn drop_paths(&mut self, paths: &[String]) -> Result<(), Vec<DropTarget>> {
+        let mut targets = vec![];
+        self.drop_targets(&mut vec![], &mut targets);
+
+        let target = targets
+            .iter()
+            .find(|t| t.focused)
+            .or_else(|| targets.iter().find(|t| t.multiple))
+            .or_else(|| (targets.len() == 1).then(|| &targets[0]));
+
+        match target {
+            Some(target) => {
+                let (path, arg) = (target.path.clone(), target.arg);
+                self.fill_paths(&path, arg, paths);
+                Ok(())
+            }
+            None if targets.is_empty() => Ok(()),
+            None => Err(targets),
+        }
+    }
+
+    /// Selects the subcommands along `path`, outermost first
+    pub fn select(&mut self, path: &[String]) -> Result<(), String> {
+        match path.split_first() {
+            Some((name, rest)) if self.subcommands.contains_key(name) => {
+                self.current = Some(name.clone());
+                self.current_mut().unwrap().select(rest)
+            }
+            Some((name, _)) => Err(self
+                .localization
+                .error_unknown_subcommand
+                .format(&[("name", name)])),
+            None => Ok(()),
+        }
+    }
+
+    /// Value of the single-value argument with clap id `id`,
+    /// in this command or the selected subcommands.
+    pub fn value_mut(&mut self, id: &str) -> Option<&mut String> {
+        let (localization, options) = (&self.localization, &self.options);
+        let subcommand = match &self.current {
+            Some(current) => self
+                .subcommands
+                .get_mut(current)
+                .map(|subcommand| subcommand.state_mut(localization, options)),
+            None => None,
+        };
+
+        let arg = self.args.iter_mut().find(|arg| arg.id == id);
+        match arg.map(|arg| &mut arg.kind) {
+            Some(ArgKind::String { value, .. }) => Some(&mut value.0),
+            _ => subcommand.and_then(|subcommand| subcommand.value_mut(id)),
+        }
+    }
+
+    /// Sets the values of the argument with clap id `id`, in this command or the
+    /// selected subcommands. It's chosen if it's in a group of arguments to choose from.
+    pub fn set_arg(&mut self, id: &str, values: &[String]) -> Result<(), String> {
+        if let Some(index) = self.args.iter().position(|arg| arg.id == id) {
+            self.select_choice(index);
+            return self.args[index].set_values(values);
+        }
+
+        let (localization, options) = (&self.localization, &self.options);
+        match &self.current {
+            Some(current) => self
+                .subcommands
                 .get_mut(current)
                 .unwrap()
-                .update_validation_error(name, message);
+                .state_mut(localization, options)
+                .set_arg(id, values),
+            None => Err(localization.error_unknown_argument.format(&[("name", id)])),
+        }
+    }
+
+    /// Selects the argument at `index` in its choice, if it's in one
+    fn select_choice(&mut self, index: usize) {
+        for choice in &mut self.choices {
+            if let Some(position) = choice.members.iter().position(|&m| m == index) {
+                choice.selected = position;
+            }
+        }
+    }
+
+    /// Whether the argument at `index` is in a choice, but isn't the chosen one
+    fn is_unchosen(&self, index: usize) -> bool {
+        self.choices
+            .iter()
+            .any(|c| c.members.contains(&index) && c.members[c.selected] != index)
+    }
+
+    /// Whether an argument of this command or the selected subcommands has a short or long flag
+    pub fn has_flags(&self) -> bool {
+        self.args.iter().any(|arg| arg.flags.is_some())
+            || self
+                .current
+                .as_ref()
+                .is_some_and(|current| self.subcommands[current].state().has_flags())
+    }
+
+    /// Everything that stops the arguments of this command and the selected subcommands
+    /// from running, like missing required values and errors from the last validation.
+    /// Clap can still find other problems, like conflicting arguments.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for (index, arg) in self.args.iter().enumerate() {
+            if self.is_unchosen(index) {
+                continue;
+            }
+            if let Some(message) = &arg.validation_error {
+                problems.push(message.clone());
+            } else if let Err(err) = arg.get_cmd_args(vec![]) {
+                problems.push(err);
+            }
+        }
+        if let Some(current) = &self.current {
+            problems.extend(self.subcommands[current].state().problems());
+        }
+        problems
+    }
+
+    /// Pairs of argument id and value typed into the text fields of this command and the
+    /// selected subcommands, see [`crate::Settings::value_history`]
+    pub fn entered_values(&self, entered: &mut Vec<(String, String)>) {
+        for (index, arg) in self.args.iter().enumerate() {
+            if self.is_unchosen(index) {
+                continue;
+            }
+            let values = match &arg.kind {
+                ArgKind::String {
+                    value, possible, ..
+                } if possible.is_empty() => std::slice::from_ref(value),
+                ArgKind::MultipleStrings {
+                    values, possible, ..
+                } if possible.is_empty() => values.as_slice(),
+                _ => continue,
+            };
+            entered.extend(
+                values
+                    .iter()
+                    .filter(|(value, _)| !value.is_empty())
+                    .map(|(value, _)| (arg.id.clone(), value.clone())),
+            );
+        }
+        if let Some(current) = &self.current {
+            self.subcommands[current].state().entered_values(entered);
         }
     }
 
     pub fn get_cmd_args(&self, mut args: Vec<String>) -> Result<Vec<String>, String> {
-        for arg in &self.args {
-            args = arg.get_cmd_args(args)?;
+        let mut all = vec![];
+        for (index, arg) in self.args.iter().enumerate() {
+            if !self.is_unchosen(index) {
+                all.push((arg, arg.get_cmd_args(vec![])?));
+            }
+        }
+
+        // Positional values starting with '-' would be parsed as flags, unless they
+        // come after `--`. A subcommand can't follow it, so then it's left to clap.
+        let hyphenated = all.iter().any(|(arg, values)| {
+            arg.call_name.is_none() && values.iter().any(|value| arg.is_hyphenated(value))
+        });
+        if hyphenated && self.current.is_none() {
+            let (positionals, options): (Vec<_>, Vec<_>) = all
+                .into_iter()
+                .partition(|(arg, _)| arg.call_name.is_none());
+            args.extend(options.into_iter().flat_map(|(_, values)| values));
+            args.push("--".into());
+            args.extend(positionals.into_iter().flat_map(|(_, values)| values));
+        } else {
+            args.extend(all.into_iter().flat_map(|(_, values)| values));
         }
 
         if let Some(current) = &self.current {
             args.push(current.clone());
-            self.subcommands[current].get_cmd_args(args)
+            self.subcommands[current].state().get_cmd_args(args)
         } else {
             Ok(args)
         }
     }
 }
 
-impl Widget for &mut AppState<'_> {
-    fn ui(self, ui: &mut Ui) -> Response {
-        ui.vertical(|ui| {
+impl AppState {
+    /// Steps of the wizard for this command and the selected subcommands. Every help heading
+    /// is a step, in the order of the first argument with it, followed by choosing a subcommand.
+    pub fn wizard_steps(&self, path: &mut Vec<String>, steps: &mut Vec<WizardStep>) {
+        let mut headings: Vec<Option<&String>> = vec![];
+        for arg in &self.args {
+            if !headings.contains(&arg.heading.as_ref()) {
+                headings.push(arg.heading.as_ref());
+            }
+        }
+        let kinds = headings
+            .into_iter()
+            .map(|heading| StepKind::Args(heading.cloned()))
+            .chain((!self.subcommands.is_empty()).then_some(StepKind::Subcommand));
+        for (index, kind) in kinds.enumerate() {
+            steps.push(WizardStep {
+                path: path.clone(),
+                kind,
+                first: index == 0,
+            });
+        }
+
+        if let Some(current) = &self.current {
+            path.push(current.clone());
+            self.subcommands[current].state().wizard_steps(path, steps);
+            path.pop();
+        }
+    }
+
+    /// Shows a single step of the wizard
+    pub fn show_wizard_step(
+        &mut self,
+        ui: &mut Ui,
+        step: &WizardStep,
+        path: &[String],
+        form: &FormContext,
+    ) {
+        if let Some((name, rest)) = path.split_first() {
+            if let Some(subcommand) = self.subcommands.get_mut(name) {
+                subcommand
+                    .state_mut(&self.localization, &self.options)
+                    .show_wizard_step(ui, step, rest, form);
+            }
+            return;
+        }
+
+        if step.first {
             if let Some(ref about) = self.about {
-                ui.label(about);
-            }
-
-            // Even empty grid adds an empty line
-            if !self.args.is_empty() {
-                Grid::new(self.id)
-                    .num_columns(2)
-                    .striped(true)
-                    .show(ui, |ui| {
-                        for arg in &mut self.args {
-                            ui.add(arg);
-                            ui.end_row();
+                markdown::show(ui, about);
+            }
+        }
+        match &step.kind {
+            StepKind::Args(heading) => {
+                self.show_args(ui, form, |arg| arg.heading == *heading);
+            }
+            StepKind::Subcommand => {
+                for (name, subcommand) in &self.subcommands {
+                    let selected = self.current.as_ref() == Some(name);
+                    if ui.radio(selected, &subcommand.label).clicked() {
+                        self.current = Some(name.clone());
+                    }
+                    if let Some(about) = &subcommand.about {
+                        ui.indent(("klask_wizard_about", name), |ui| {
+                            markdown::show(ui, about);
+                        });
+                    }
+                }
+                // Builds the state of the selected subcommand for the next steps
+                self.current_mut();
+            }
+        }
+    }
+
+    /// Grid of the arguments `shown` returns true for
+    fn show_args(&mut self, ui: &mut Ui, form: &FormContext, shown: impl Fn(&ArgState) -> bool) {
+        let indices: Vec<usize> = (0..self.args.len())
+            .filter(|&index| shown(&self.args[index]))
+            .collect();
+        self.show_grid(ui, 0, &indices, form);
+    }
+
+    /// Grid of the arguments at `indices`, `grid` tells apart grids of the same command
+    fn show_grid(&mut self, ui: &mut Ui, grid: usize, indices: &[usize], form: &FormContext) {
+        // Choices are shown in place of their first member
+        let rows: Vec<usize> = indices
+            .iter()
+            .copied()
+            .filter(|index| {
+                self.choices
+                    .iter()
+                    .find(|c| c.members.contains(index))
+                    .is_none_or(|c| c.members[0] == *index)
+            })
+            .collect();
+        // Even empty grid adds an empty line
+        if rows.is_empty() {
+            return;
+        }
+        Grid::new((self.id, grid))
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for index in rows {
+                    match self.choices.iter_mut().find(|c| c.members.contains(&index)) {
+                        Some(choice) => show_choice(ui, choice, &mut self.args, form),
+                        None => {
+                            self.args[index].show(ui, form);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Shows the arguments as laid out in [`crate::Settings::layout`]. Arguments next to
+    /// each other share a grid, so their values line up.
+    fn show_layout(
+        &mut self,
+        ui: &mut Ui,
+        items: &[LayoutItem],
+        grid: &mut usize,
+        form: &FormContext,
+    ) {
+        let mut run = vec![];
+        for item in items {
+            if let LayoutItem::Arg(id) = item {
+                run.extend(self.args.iter().position(|arg| arg.id == *id));
+                continue;
+            }
+            *grid += 1;
+            self.show_grid(ui, *grid, &std::mem::take(&mut run), form);
+
+            match item {
+                LayoutItem::Group(title, items) if self.any_placed(items) => {
+                    ui.group(|ui| {
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(title).strong());
+                            self.show_layout(ui, items, grid, form);
+                        });
+                    });
+                }
+                LayoutItem::Columns(columns) if columns.iter().any(|c| self.any_placed(c)) => {
+                    ui.columns(columns.len(), |uis| {
+                        for (ui, column) in uis.iter_mut().zip(columns) {
+                            self.show_layout(ui, column, grid, form);
                         }
                     });
+                }
+                LayoutItem::Separator => {
+                    ui.separator();
+                }
+                _ => {}
+            }
+        }
+        *grid += 1;
+        self.show_grid(ui, *grid, &run, form);
+    }
+
+    /// Whether any of the arguments in `items` belong to this command
+    fn any_placed(&self, items: &[LayoutItem]) -> bool {
+        items.iter().any(|item| match item {
+            LayoutItem::Arg(id) => self.args.iter().any(|arg| arg.id == *id),
+            LayoutItem::Group(_, items) => self.any_placed(items),
+            LayoutItem::Columns(columns) => columns.iter().any(|c| self.any_placed(c)),
+            LayoutItem::Separator => false,
+        })
+    }
+
+    /// Indices of the arguments that aren't in the layout, shown after it
+    fn unplaced(&self, items: &[LayoutItem]) -> Vec<usize> {
+        fn placed<'a>(items: &'a [LayoutItem], ids: &mut Vec<&'a str>) {
+            for item in items {
+                match item {
+                    LayoutItem::Arg(id) => ids.push(id),
+                    LayoutItem::Group(_, items) => placed(items, ids),
+                    LayoutItem::Columns(columns) => {
+                        for column in columns {
+                            placed(column, ids);
+                        }
+                    }
+                    LayoutItem::Separator => {}
+                }
+            }
+        }
+        let mut ids = vec![];
+        placed(items, &mut ids);
+        (0..self.args.len())
+            .filter(|&index| !ids.contains(&self.args[index].id.as_str()))
+            .collect()
+    }
+
+    /// Shows the arguments of the command and the selected subcommand
+    pub fn show(&mut self, ui: &mut Ui, form: &FormContext) -> Response {
+        ui.vertical(|ui| {
+            if let Some(ref about) = self.about {
+                markdown::show(ui, about);
+            }
+
+            let options = self.options.clone();
+            if options.layout.is_empty() {
+                self.show_args(ui, form, |_| true);
+            } else {
+                let mut grid = 0;
+                self.show_layout(ui, &options.layout, &mut grid, form);
+                let unplaced = self.unplaced(&options.layout);
+                self.show_grid(ui, grid + 1, &unplaced, form);
             }
 
             ui.separator();
@@ -91,23 +682,211 @@ impl Widget for &mut AppState<'_> {
             if !self.subcommands.is_empty() {
                 // It probably should be changed to wrapping when there are more than a few
                 ui.columns(self.subcommands.len(), |ui| {
-                    for (i, name) in self.subcommands.keys().enumerate() {
+                    for (i, (name, subcommand)) in self.subcommands.iter().enumerate() {
                         ui[i].selectable_value(
                             &mut self.current,
                             Some(name.clone()),
-                            name.to_sentence_case(),
+                            &subcommand.label,
                         );
                     }
                 });
             }
 
-            if let Some(current) = &self.current {
-                ui.add(self.subcommands.get_mut(current).unwrap());
+            if let Some(current) = self.current_mut() {
+                current.show(ui, form);
             }
         })
         .response
     }
 }
 
+fn show_choice(ui: &mut Ui, choice: &mut Choice, args: &mut [ArgState], form: &FormContext) {
+    let selected = &args[choice.members[choice.selected]];
+    let response = ComboBox::from_id_source(("klask_choice", selected.id.as_str()))
+        .selected_text(&selected.name)
+        .show_ui(ui, |ui| {
+            for (position, &member) in choice.members.iter().enumerate() {
+                ui.selectable_value(&mut choice.selected, position, &args[member].name);
+            }
+        })
+        .response
+        .on_hover_text(&choice.label);
+    if let Some(desc) = &selected.desc {
+        response.on_hover_ui(|ui| markdown::show(ui, desc));
+    }
+
+    let selected = &mut args[choice.members[choice.selected]];
+    match selected.kind {
+        // Choosing a flag is enough to pass it
+        ArgKind::Bool { .. } => {
+            ui.label("");
+        }
+        _ => {
+            selected.show_value(ui, form);
+        }
+    }
+}
+
+/// Finds required groups of arguments that can't be used together, and makes the value
+/// of their arguments required, since one of them is always chosen. Flags in them are set.
+///
+/// Clap 3 has no getters for groups, so they are read from their debug output.
+/// Ids are formatted the same way in the debug output of arguments.
+fn choices(app: &Command, options: &ArgOptions, args: &mut [ArgState]) -> Vec<Choice> {
+    // Id and groups of each argument
+    let ids: Vec<(String, Vec<String>)> = shown_args(app, options)
+        .map(|arg| {
+            let debug = format!("{:?}", arg);
+            let id = debug_field(&debug, "id").unwrap_or_default().to_string();
+            let groups = debug_list(debug_field(&debug, "groups").unwrap_or_default());
+            (id, groups)
+        })
+        .collect();
+
+    let mut choices: Vec<Choice> = vec![];
+    for group in app.get_groups() {
+        let debug = format!("{:?}", group);
+        if debug_field(&debug, "required") != Some("true")
+            || debug_field(&debug, "multiple") != Some("false")
+        {
+            continue;
+        }
+
+        // Arguments can be added to the group, or the group to the arguments
+        let group_id = debug_field(&debug, "id").unwrap_or_default();
+        let mut names = debug_list(debug_field(&debug, "args").unwrap_or_default());
+        for (id, groups) in &ids {
+            if groups.iter().any(|g| g == group_id) && !names.contains(id) {
+                names.push(id.clone());
+            }
+        }
+        let members: Option<Vec<usize>> = names
+            .iter()
+            .map(|name| ids.iter().position(|(id, _)| id == name))
+            .collect();
+        // Members that are groups or hidden arguments can't be shown
+        let mut members = match members {
+            Some(members) if members.len() > 1 => members,
+            _ => continue,
+        };
+        members.sort_unstable();
+        if choices
+            .iter()
+            .any(|c| c.members.iter().any(|m| members.contains(m)))
+        {
+            continue;
+        }
+
+        for &member in &members {
+            let arg = &mut args[member];
+            arg.optional = false;
+            if let ArgKind::Bool { value, .. } = &mut arg.kind {
+                *value = true;
+            }
+            arg.initial = arg.kind.clone();
+        }
+        let name = debug_field(&debug, "name").unwrap_or_default();
+        choices.push(Choice {
+            label: name.trim_matches('"').to_sentence_case(),
+            members,
+            selected: 0,
+        });
+    }
+    choices
+}
+
+/// Items of a list in debug output
+fn debug_list(list: &str) -> Vec<String> {
+    let items = list.strip_prefix('[').and_then(|l| l.strip_suffix(']'));
+    match items {
+        Some("") | None => vec![],
+        Some(items) => items.split(", ").map(String::from).collect(),
+    }
+}
+
+/// Value of a field in derived debug output. Lists and strings keep their brackets and quotes.
+fn debug_field<'a>(debug: &'a str, name: &str) -> Option<&'a str> {
+    let start = debug.find(&format!(" {}: ", name))? + name.len() + 3;
+    let rest = &debug[start..];
+    let end = match rest.chars().next()? {
+        '[' => {
+            let mut depth = 0;
+            let (end, _) = rest.char_indices().find(|&(_, c)| {
+                match c {
+                    '[' => depth += 1,
+                    ']' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })?;
+            end + 1
+        }
+        '"' => rest[1..].find('"')? + 2,
+        _ => rest.find([',', ' ', '}'])?,
+    };
+    Some(&rest[..end])
+}
+
+/// Arguments shown in the GUI, everything except help, version
+/// and [`crate::Settings::hide_args`]
+fn shown_args<'a, 'help>(
+    app: &'a Command<'help>,
+    options: &'a ArgOptions,
+) -> impl Iterator<Item = &'a Arg<'help>> {
+    app.get_arguments().filter(move |a| {
+        a.get_id() != "help"
+            && a.get_id() != "version"
+            && !options.hide_args.iter().any(|id| id == a.get_id())
+    })
+}
+
+/// Subcommands shown in the GUI, everything except [`crate::Settings::hide_subcommands`]
+fn shown_subcommands<'a, 'help>(
+    app: &'a Command<'help>,
+    options: &'a ArgOptions,
+) -> impl Iterator<Item = &'a Command<'help>> {
+    app.get_subcommands().filter(move |s| {
+        !options
+            .hide_subcommands
+            .iter()
+            .any(|name| name == s.get_name())
+    })
+}
+
+fn about(app: &Command) -> Option<String> {
+    app.get_long_about()
+        .or_else(|| app.get_about())
+        .map(String::from)
+}
+
+/// Same as [`AppState::palette_entries`], for subcommands that aren't built yet
+fn command_palette_entries(
+    app: &Command,
+    options: &ArgOptions,
+    path: &mut Vec<String>,
+    entries: &mut Vec<PaletteEntry>,
+) {
+    for (index, arg) in shown_args(app, options).enumerate() {
+        entries.push(PaletteEntry {
+            path: path.clone(),
+            arg: Some(index),
+            label: ArgState::name(arg, options),
+            detail: ArgState::tooltip(arg, options).unwrap_or_default(),
+        });
+    }
+
+    for subcommand in shown_subcommands(app, options) {
+        path.push(subcommand.get_name().to_string());
+        entries.push(PaletteEntry {
+            path: path.clone(),
+            arg: None,
+            label: subcommand.get_name().to_sentence_case(),
+            detail: about(subcommand).unwrap_or_default(),
+        });
+        command_palette_entries(subcommand, options, path, entries);
+        path.pop();
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,10 +1,11 @@
-use super::AppState;
+use super::{AppState, StepKind, WizardStep};
 use crate::{
-    arg_state::{ArgKind, ArgState},
-    settings::Localization,
+    arg_state::{ArgKind, ArgOptions, ArgState, FormContext},
+    localization::Localization,
 };
 use clap::{FromArgMatches, IntoApp, Parser, ValueHint};
-use std::{fmt::Debug, path::PathBuf};
+use eframe::egui::{CentralPanel, Context, Event, Key, RawInput};
+use std::{fmt::Debug, path::PathBuf, sync::Arc};
 use uuid::Uuid;
 
 #[derive(Debug, Parser, PartialEq, Eq)]
@@ -202,6 +203,38 @@ fn positional_bool() {
     test_app(|args| args[0].set(), PositionalBool { verbose: true })
 }
 
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct DefaultTrue {
+    #[clap(long = "no-color", action = clap::ArgAction::SetFalse)]
+    color: bool,
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    verbose: bool,
+}
+
+#[test]
+fn default_true_flag() {
+    test_app(
+        |args| {
+            assert!(matches!(args[0].kind, ArgKind::Bool { value: true, .. }));
+            assert!(matches!(args[1].kind, ArgKind::Bool { value: false, .. }));
+        },
+        DefaultTrue {
+            color: true,
+            verbose: false,
+        },
+    );
+    test_app(
+        |args| {
+            args[0].unset();
+            args[1].set();
+        },
+        DefaultTrue {
+            color: false,
+            verbose: true,
+        },
+    );
+}
+
 #[derive(Debug, Parser, PartialEq, Eq)]
 struct MultipleOccurrences {
     #[clap(short, long, number_of_values(1))]
@@ -218,14 +251,592 @@ fn multiple_occurrences() {
     )
 }
 
+#[derive(Debug, Parser, PartialEq, Eq)]
+enum Palette {
+    First,
+    Second {
+        #[clap(long)]
+        value: String,
+    },
+}
+
+#[test]
+fn palette_jump() {
+    let app = Palette::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+
+    let mut entries = vec![];
+    app_state.palette_entries(&mut vec![], &mut entries);
+    let entry = entries
+        .iter()
+        .find(|e| e.label == "Value")
+        .expect("Argument should be in the palette");
+    assert_eq!(entry.path, vec!["second".to_string()]);
+
+    app_state.jump_to(&entry.path.clone(), entry.arg);
+    assert_eq!(app_state.current.as_deref(), Some("second"));
+    assert!(app_state.subcommands["second"].state().args[0].request_focus);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+enum Lazy {
+    First,
+    Second {
+        #[clap(subcommand)]
+        nested: Palette,
+    },
+}
+
+#[test]
+fn subcommands_built_when_selected() {
+    let app = Lazy::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+    let is_built = |state: &AppState, name: &str| {
+        matches!(state.subcommands[name].state, super::Lazy::Built(_))
+    };
+
+    assert!(is_built(&app_state, "first"));
+    assert!(!is_built(&app_state, "second"));
+
+    // The palette still finds arguments of subcommands that aren't built
+    let mut entries = vec![];
+    app_state.palette_entries(&mut vec![], &mut entries);
+    let entry = entries
+        .iter()
+        .find(|e| e.label == "Value")
+        .expect("Argument should be in the palette");
+    assert_eq!(entry.path, ["second", "second"]);
+
+    app_state.jump_to(&entry.path.clone(), entry.arg);
+    assert!(is_built(&app_state, "second"));
+    assert_eq!(app_state.current_path(), ["second", "second"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Numbers {
+    #[clap(long, value_parser)]
+    count: u8,
+    #[clap(long, value_parser)]
+    offset: Option<i64>,
+    #[clap(long, value_parser, default_value_t = 5)]
+    level: i32,
+    #[clap(long)]
+    text: Option<u32>,
+}
+
+#[test]
+fn numbers() {
+    test_app(
+        |args| {
+            assert!(matches!(args[0].kind, ArgKind::Number { value: Some(v), .. } if v == 0.0));
+            args[0].number(200.0);
+            args[1].number(-3.0);
+            // Without a value parser there's no type to detect
+            assert!(matches!(args[3].kind, ArgKind::String { .. }));
+        },
+        Numbers {
+            count: 200,
+            offset: Some(-3),
+            level: 5,
+            text: None,
+        },
+    );
+
+    let app = clap::Command::new("floats").arg(
+        clap::Arg::new("ratio")
+            .long("ratio")
+            .takes_value(true)
+            .value_parser(clap::value_parser!(f64)),
+    );
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::default());
+    app_state.args[0].number(0.25);
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(args, ["--ratio", "0.25"]);
+}
+
+#[test]
+fn flags_and_aliases() {
+    let app = clap::Command::new("flags")
+        .arg(
+            clap::Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .visible_short_alias('V')
+                .visible_alias("loud")
+                .alias("hidden"),
+        )
+        .arg(clap::Arg::new("only-long").long("only-long"))
+        .arg(clap::Arg::new("file"));
+    let app_state = AppState::new(&app, Arc::default(), Arc::default());
+    assert_eq!(app_state.args[0].flags.as_deref(), Some("-v, --verbose"));
+    assert_eq!(app_state.args[0].aliases, ["-V", "--loud"]);
+    assert_eq!(app_state.args[1].flags.as_deref(), Some("--only-long"));
+    assert!(app_state.args[1].aliases.is_empty());
+    assert_eq!(app_state.args[2].flags, None);
+    assert!(app_state.has_flags());
+
+    let positional = AppState::new(&PositionalBool::command(), Arc::default(), Arc::default());
+    assert!(!positional.has_flags());
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct HyphenValues {
+    #[clap(long)]
+    name: String,
+    #[clap(long)]
+    exclude: Vec<String>,
+    #[clap(long, value_parser)]
+    offset: i64,
+    files: Vec<String>,
+}
+
+#[test]
+fn hyphen_values() {
+    test_app(
+        |args| {
+            args[0].enter("-n");
+            args[1].enter_multiple(["-a", "--b", "c"]);
+            args[2].number(-7.0);
+            args[3].enter_multiple(["-", "--x", "y"]);
+        },
+        HyphenValues {
+            name: "-n".into(),
+            exclude: vec!["-a".into(), "--b".into(), "c".into()],
+            offset: -7,
+            files: vec!["-".into(), "--x".into(), "y".into()],
+        },
+    );
+
+    // Without multiple occurrences the values can't be told apart from flags
+    let app = clap::Command::new("values").arg(
+        clap::Arg::new("values")
+            .long("values")
+            .takes_value(true)
+            .multiple_values(true),
+    );
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::default());
+    app_state.args[0].enter_multiple(["-a", "b"]);
+    assert!(app_state.get_cmd_args(vec![]).is_err());
+    app_state.args[0].enter_multiple(["-a"]);
+    assert_eq!(app_state.get_cmd_args(vec![]).unwrap(), ["--values=-a"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Verbosity {
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+#[test]
+fn occurrence_labels() {
+    let mut options = ArgOptions::default();
+    options
+        .occurrence_labels
+        .insert("verbose".into(), vec!["Normal".into(), "Verbose".into()]);
+    let app = Verbosity::command();
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::new(options));
+    assert!(
+        matches!(&app_state.args[0].kind, ArgKind::Occurences { labels, .. } if labels.len() == 2)
+    );
+
+    app_state.args[0].occurrences(1);
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    let matches = app
+        .try_get_matches_from(["_name".into()].iter().chain(&args))
+        .unwrap();
+    assert_eq!(
+        Verbosity::from_arg_matches(&matches).unwrap(),
+        Verbosity { verbose: 1 }
+    );
+}
+
+#[test]
+fn arg_texts() {
+    let text = crate::ArgText {
+        label: Some("How chatty".into()),
+        hint: Some("Quiet".into()),
+        ..Default::default()
+    };
+    let mut options = ArgOptions::default();
+    options.arg_texts.insert("verbose".into(), text);
+    let app = Verbosity::command();
+    let app_state = AppState::new(&app, Arc::default(), Arc::new(options));
+    assert_eq!(app_state.args[0].name, "How chatty");
+    assert_eq!(app_state.args[0].hint.as_deref(), Some("Quiet"));
+    assert_eq!(app_state.args[0].desc, None);
+
+    let mut entries = vec![];
+    app_state.palette_entries(&mut vec![], &mut entries);
+    assert_eq!(entries[0].label, "How chatty");
+}
+
+#[derive(Debug, Parser)]
+struct Hidden {
+    #[clap(long)]
+    shown: Option<String>,
+    #[clap(long)]
+    internal: bool,
+    #[clap(subcommand)]
+    command: HiddenCommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum HiddenCommand {
+    DevOnly,
+    Build,
+}
+
+#[test]
+fn hidden_args_and_subcommands() {
+    let options = ArgOptions {
+        hide_args: vec!["internal".into()],
+        hide_subcommands: vec!["dev-only".into()],
+        ..Default::default()
+    };
+    let app = Hidden::command();
+    let app_state = AppState::new(&app, Arc::default(), Arc::new(options));
+    let ids: Vec<&str> = app_state.args.iter().map(|arg| arg.id.as_str()).collect();
+    assert_eq!(ids, ["shown"]);
+    assert_eq!(app_state.get_cmd_args(vec![]).unwrap(), ["build"]);
+
+    let mut entries = vec![];
+    app_state.palette_entries(&mut vec![], &mut entries);
+    let labels: Vec<&str> = entries.iter().map(|entry| entry.label.as_str()).collect();
+    assert_eq!(labels, ["Shown", "Build"]);
+}
+
+#[test]
+fn layout() {
+    use crate::LayoutItem::{Arg, Columns, Group, Separator};
+    let layout = vec![
+        Arg("flag-true".into()),
+        Group("Missing".into(), vec![Arg("not-an-arg".into()), Separator]),
+        Columns(vec![
+            vec![Arg("single".into())],
+            vec![Arg("occurrences".into())],
+        ]),
+    ];
+    let app = Simple::command();
+    let app_state = AppState::new(&app, Arc::default(), Arc::default());
+    assert!(app_state.any_placed(&layout));
+    assert!(!app_state.any_placed(&layout[1..2]));
+    let unplaced: Vec<&str> = app_state
+        .unplaced(&layout)
+        .into_iter()
+        .map(|index| app_state.args[index].id.as_str())
+        .collect();
+    assert_eq!(
+        unplaced,
+        ["optional-no-enter", "optional-enter", "flag-false"]
+    );
+}
+
+#[test]
+fn possible_value_help() {
+    let app = clap::Command::new("values").arg(
+        clap::Arg::new("format")
+            .long("format")
+            .takes_value(true)
+            .value_parser([
+                clap::PossibleValue::new("json").help("Machine readable"),
+                clap::PossibleValue::new("text"),
+                clap::PossibleValue::new("debug").hide(true),
+            ]),
+    );
+    let app_state = AppState::new(&app, Arc::default(), Arc::default());
+    match &app_state.args[0].kind {
+        ArgKind::String { possible, .. } => {
+            let possible: Vec<(&str, Option<&str>)> = possible
+                .iter()
+                .map(|p| (p.name.as_str(), p.help.as_deref()))
+                .collect();
+            assert_eq!(
+                possible,
+                [("json", Some("Machine readable")), ("text", None)]
+            );
+        }
+        kind => panic!("Expected a string argument, got {:?}", kind),
+    }
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Defaults {
+    #[clap(long, default_value = "a")]
+    single: String,
+    #[clap(long, default_values = &["b", "c"], multiple_occurrences = true)]
+    multiple: Vec<String>,
+}
+
+#[test]
+fn prefill_defaults() {
+    let options = ArgOptions {
+        prefill_defaults: true,
+        ..Default::default()
+    };
+    let app = Defaults::command();
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::new(options));
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(
+        args,
+        ["--single", "a", "--multiple", "b", "--multiple", "c"]
+    );
+
+    // Clearing a prefilled value falls back to the default
+    app_state.args[0].enter("");
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(args, ["--multiple", "b", "--multiple", "c"]);
+
+    app_state.reset();
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(
+        args,
+        ["--single", "a", "--multiple", "b", "--multiple", "c"]
+    );
+}
+
+#[test]
+fn reset_subcommands() {
+    let app = Palette::command();
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::default());
+    app_state.jump_to(&["second".to_string()], None);
+    app_state.current_mut().unwrap().args[0].enter("a");
+    assert!(app_state.get_cmd_args(vec![]).is_ok());
+
+    app_state.reset();
+    assert_eq!(app_state.current.as_deref(), Some("second"));
+    assert!(app_state.get_cmd_args(vec![]).is_err());
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+#[clap(group = clap::ArgGroup::new("source").required(true))]
+struct Source {
+    #[clap(long, group = "source")]
+    from_file: Option<PathBuf>,
+    #[clap(long, group = "source")]
+    from_url: Option<String>,
+    #[clap(long, group = "source")]
+    from_stdin: bool,
+    #[clap(long)]
+    output: Option<String>,
+}
+
+#[test]
+fn exactly_one_of_group() {
+    let app = Source::command();
+    let mut app_state = AppState::new(&app, Arc::default(), Arc::default());
+    assert_eq!(app_state.choices.len(), 1);
+    assert_eq!(app_state.choices[0].label, "Source");
+    assert_eq!(app_state.choices[0].members, [0, 1, 2]);
+
+    // The chosen argument is required
+    assert!(app_state.get_cmd_args(vec![]).is_err());
+
+    // Only the chosen argument is passed
+    app_state.args[0].enter("a.txt");
+    app_state.jump_to(&[], Some(1));
+    app_state.args[1].enter("https://example.com");
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(args, ["--from-url", "https://example.com"]);
+
+    // Choosing a flag passes it
+    app_state.jump_to(&[], Some(2));
+    let args = app_state.get_cmd_args(vec![]).unwrap();
+    assert_eq!(args, ["--from-stdin"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct DropPaths {
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    config: Option<PathBuf>,
+    #[clap(long, value_hint = ValueHint::FilePath, multiple_occurrences = true)]
+    inputs: Vec<PathBuf>,
+    #[clap(long, value_hint = ValueHint::DirPath)]
+    output: Option<PathBuf>,
+}
+
+#[test]
+fn drop_paths() {
+    let app = DropPaths::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+    let paths = ["a.txt".to_string(), "b.txt".to_string()];
+
+    // Multi-value argument is filled by default
+    app_state.drop_paths(&paths).unwrap();
+    // Focused argument takes precedence
+    app_state.args[2].has_focus = true;
+    app_state.drop_paths(&paths).unwrap();
+
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        DropPaths::from_arg_matches(&matches).unwrap(),
+        DropPaths {
+            config: None,
+            inputs: vec!["a.txt".into(), "b.txt".into()],
+            output: Some("a.txt".into()),
+        }
+    );
+}
+
+#[test]
+fn output_paths() {
+    let app = DropPaths::command();
+    let options = ArgOptions {
+        output_paths: vec!["output".into()],
+        ..Default::default()
+    };
+    let app_state = AppState::new(&app, Arc::default(), Arc::new(options));
+    let output_paths: Vec<bool> = app_state.args.iter().map(|arg| arg.output_path).collect();
+    assert_eq!(output_paths, [false, false, true]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct DropAmbiguous {
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    config: Option<PathBuf>,
+    #[clap(long)]
+    name: Option<String>,
+    #[clap(long, value_hint = ValueHint::DirPath)]
+    output: Option<PathBuf>,
+}
+
+#[test]
+fn drop_paths_ambiguous() {
+    let app = DropAmbiguous::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+
+    let targets = app_state.drop_paths(&["a.txt".into()]).unwrap_err();
+    let labels: Vec<&str> = targets.iter().map(|t| t.label.as_str()).collect();
+    assert_eq!(labels, ["Config", "Output"]);
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+enum ConfigArg {
+    Build {
+        #[clap(long)]
+        config: PathBuf,
+    },
+}
+
+#[test]
+fn value_mut() {
+    let app = ConfigArg::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+
+    assert!(app_state.value_mut("missing").is_none());
+    *app_state.value_mut("config").unwrap() = "klask.toml".into();
+
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        ConfigArg::from_arg_matches(&matches).unwrap(),
+        ConfigArg::Build {
+            config: "klask.toml".into()
+        }
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+enum BatchArgs {
+    Build {
+        #[clap(long)]
+        input: Vec<String>,
+        #[clap(long)]
+        count: u32,
+        #[clap(long)]
+        verbose: bool,
+    },
+}
+
+#[test]
+fn set_arg() {
+    let app = BatchArgs::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+
+    app_state
+        .set_arg("input", &["a".into(), "b".into()])
+        .unwrap();
+    app_state.set_arg("count", &["3".into()]).unwrap();
+    app_state.set_arg("verbose", &["yes".into()]).unwrap();
+    assert!(app_state.set_arg("verbose", &["maybe".into()]).is_err());
+    assert!(app_state.set_arg("missing", &["x".into()]).is_err());
+
+    let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
+    let matches = app.try_get_matches_from(args.iter()).unwrap();
+    assert_eq!(
+        BatchArgs::from_arg_matches(&matches).unwrap(),
+        BatchArgs::Build {
+            input: vec!["a".into(), "b".into()],
+            count: 3,
+            verbose: true,
+        }
+    );
+}
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Wizard {
+    #[clap(long)]
+    name: String,
+    #[clap(long, help_heading = "Output")]
+    output: Option<String>,
+    #[clap(long)]
+    verbose: bool,
+    #[clap(subcommand)]
+    command: WizardCommand,
+}
+
+#[derive(Debug, clap::Subcommand, PartialEq, Eq)]
+enum WizardCommand {
+    Build {
+        #[clap(long, help_heading = "Build")]
+        release: bool,
+    },
+    Clean,
+}
+
+#[test]
+fn wizard_steps() {
+    let app = Wizard::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+
+    let mut steps = vec![];
+    app_state.wizard_steps(&mut vec![], &mut steps);
+    let step = |path: &[&str], kind, first| WizardStep {
+        path: path.iter().map(ToString::to_string).collect(),
+        kind,
+        first,
+    };
+    assert_eq!(
+        steps,
+        [
+            step(&[], StepKind::Args(None), true),
+            step(&[], StepKind::Args(Some("Output".into())), false),
+            step(&[], StepKind::Subcommand, false),
+            step(&["build"], StepKind::Args(Some("Build".into())), true),
+        ]
+    );
+
+    app_state.jump_to(&["clean".into()], None);
+    steps.clear();
+    app_state.wizard_steps(&mut vec![], &mut steps);
+    assert_eq!(steps.len(), 3);
+}
+
 fn test_app<C, F>(setup: F, expected: C)
 where
     C: IntoApp + FromArgMatches + Debug + Eq,
     F: FnOnce(&mut Vec<ArgState>),
 {
     let app = C::into_app();
-    let localization = Localization::default();
-    let mut app_state = AppState::new(&app, &localization);
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
     setup(&mut app_state.args);
     let args = app_state.get_cmd_args(vec!["_name".into()]).unwrap();
     eprintln!("Args: {:?}", &args[1..]);
@@ -234,13 +845,13 @@ where
     assert_eq!(c, expected);
 }
 
-fn enter_consecutive<const N: usize>(args: &mut Vec<ArgState>, vals: [&str; N]) {
+fn enter_consecutive<const N: usize>(args: &mut [ArgState], vals: [&str; N]) {
     for i in 0..N {
         args[i].enter(vals[i]);
     }
 }
 
-impl crate::arg_state::ArgState<'_> {
+impl crate::arg_state::ArgState {
     fn enter(&mut self, val: &str) {
         if let ArgKind::String { value, .. } = &mut self.kind {
             value.0 = val.to_string();
@@ -260,19 +871,168 @@ impl crate::arg_state::ArgState<'_> {
         }
     }
 
+    fn number(&mut self, val: f64) {
+        if let ArgKind::Number { value, .. } = &mut self.kind {
+            *value = Some(val);
+        } else {
+            panic!("Called number on {:?}", self)
+        }
+    }
+
     fn occurrences(&mut self, val: i32) {
-        if let ArgKind::Occurences(i) = &mut self.kind {
-            *i = val;
+        if let ArgKind::Occurences { count, .. } = &mut self.kind {
+            *count = val;
         } else {
             panic!("Called occurrences on {:?}", self)
         }
     }
 
     fn set(&mut self) {
-        if let ArgKind::Bool(b) = &mut self.kind {
-            *b = true;
+        if let ArgKind::Bool { value, .. } = &mut self.kind {
+            *value = true;
         } else {
             panic!("Called set on {:?}", self)
         }
     }
+
+    fn unset(&mut self) {
+        if let ArgKind::Bool { value, .. } = &mut self.kind {
+            *value = false;
+        } else {
+            panic!("Called unset on {:?}", self)
+        }
+    }
+}
+
+#[test]
+fn problems() {
+    let app = OptionalAndDefault::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+    assert_eq!(app_state.problems().len(), 1);
+
+    app_state.args[0].enter("a");
+    assert!(app_state.problems().is_empty());
+
+    app_state.args[1].validation_error = Some("Invalid value".into());
+    assert_eq!(app_state.problems(), ["Invalid value"]);
+}
+
+#[test]
+fn editing_clears_validation_error() {
+    use eframe::egui::{PointerButton, Pos2};
+
+    let app = Simple::command();
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+    app_state.args[0].enter("a");
+    app_state.update_validation_error(&app_state.args[3].name.clone(), "Invalid value");
+    assert_eq!(app_state.problems(), ["Invalid value"]);
+
+    // Click the checkbox of the flag, pressing and releasing in separate frames
+    let ctx = Context::default();
+    let mut frame = |events: Vec<Event>| {
+        let input = RawInput {
+            events,
+            ..RawInput::default()
+        };
+        let mut rect = None;
+        let _ = ctx.run(input, |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                rect = Some(
+                    app_state.args[3]
+                        .show_value(ui, &FormContext::default())
+                        .rect,
+                );
+            });
+        });
+        rect.unwrap()
+    };
+    let pos: Pos2 = frame(vec![]).center();
+    let click = |pressed| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    };
+    frame(vec![Event::PointerMoved(pos), click(true)]);
+    frame(vec![click(false)]);
+
+    assert!(app_state.problems().is_empty());
+}
+
+#[test]
+fn keyboard_multiple_values_and_occurrences() {
+    use clap::{Arg, Command};
+    use eframe::egui::Id;
+
+    let app = Command::new("keyboard")
+        .arg(
+            Arg::new("files")
+                .long("files")
+                .takes_value(true)
+                .multiple_occurrences(true),
+        )
+        .arg(Arg::new("verbose").short('v').multiple_occurrences(true));
+    let localization = Arc::new(Localization::default());
+    let mut app_state = AppState::new(&app, localization, Arc::default());
+    let ctx = Context::default();
+
+    // Removing a row with the keyboard focuses the next one
+    let files = &mut app_state.args[0];
+    files.enter_multiple(["a", "b"]);
+    let second = match &files.kind {
+        ArgKind::MultipleStrings { values, .. } => values[1].1,
+        _ => unreachable!(),
+    };
+    press(&ctx, files, Key::Tab);
+    press(&ctx, files, Key::Space);
+    show(&ctx, files, vec![]);
+    assert!(ctx.memory().has_focus(Id::new(second)));
+
+    // Adding one focuses its text field
+    press(&ctx, files, Key::Tab);
+    press(&ctx, files, Key::Enter);
+    show(&ctx, files, vec![]);
+    match &files.kind {
+        ArgKind::MultipleStrings { values, .. } => {
+            assert_eq!(values.len(), 2);
+            assert!(ctx.memory().has_focus(Id::new(values[1].1)));
+        }
+        _ => unreachable!(),
+    }
+
+    // Arrow keys count while a button of the counter has focus
+    let verbose = &mut app_state.args[1];
+    let ctx = Context::default();
+    press(&ctx, verbose, Key::Tab);
+    press(&ctx, verbose, Key::Space);
+    press(&ctx, verbose, Key::ArrowUp);
+    press(&ctx, verbose, Key::ArrowUp);
+    press(&ctx, verbose, Key::ArrowDown);
+    assert!(matches!(verbose.kind, ArgKind::Occurences { count: 1, .. }));
+}
+
+/// Shows the value of the argument for one frame
+fn show(ctx: &Context, arg: &mut ArgState, events: Vec<Event>) {
+    let input = RawInput {
+        events,
+        ..RawInput::default()
+    };
+    let _ = ctx.run(input, |ctx| {
+        CentralPanel::default().show(ctx, |ui| {
+            arg.show_value(ui, &FormContext::default());
+        });
+    });
+}
+
+/// Shows the argument for a frame with the key pressed, then for one without any input
+fn press(ctx: &Context, arg: &mut ArgState, key: Key) {
+    let event = Event::Key {
+        key,
+        pressed: true,
+        modifiers: Default::default(),
+    };
+    show(ctx, arg, vec![event]);
+    show(ctx, arg, vec![]);
 }
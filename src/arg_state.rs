@@ -1,63 +1,242 @@
-use crate::{settings::Localization, Klask};
-use clap::{Arg, ValueHint};
-use eframe::egui::{widgets::Widget, ComboBox, Response, TextEdit, Ui};
+use crate::{
+    file_dialog::{self, DialogOptions},
+    localization::Localization,
+    markdown,
+    suggestions::{self, Suggestion},
+    value_history::Values,
+    ArgText, FlagNames, Klask, LayoutItem, Settings,
+};
+use clap::{Arg, ArgAction, ValueHint};
+use eframe::egui::{
+    Align, Color32, ComboBox, DragValue, Event, Id, Key, Response, RichText, TextEdit, Ui,
+};
 use inflector::Inflector;
-use rfd::FileDialog;
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use uuid::Uuid;
 
+/// State of the rest of the GUI the arguments are shown with, which can change every frame
+#[derive(Debug, Clone, Copy)]
+pub struct FormContext<'a> {
+    /// The [`FlagNames`] to show, since they can be toggled in the GUI
+    pub flag_names: FlagNames,
+    /// Absolute working directory, if it's set. Relative paths resolve against it,
+    /// see [`crate::Settings::enable_working_dir`].
+    pub working_dir: Option<&'a Path>,
+    /// Values of earlier runs for suggesting them, see [`crate::Settings::value_history`]
+    pub values: Option<&'a Values>,
+}
+
+impl Default for FormContext<'_> {
+    fn default() -> Self {
+        Self {
+            flag_names: FlagNames::Hidden,
+            working_dir: None,
+            values: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
-pub struct ArgState<'s> {
+pub struct ArgState {
+    /// Id of the argument in clap
+    pub id: String,
     pub name: String,
     pub call_name: Option<String>,
+    /// Short and long flag, like `-v, --verbose`. None for positional arguments
+    pub flags: Option<String>,
+    /// Visible short and long aliases
+    pub aliases: Vec<String>,
     pub desc: Option<String>,
+    /// Shown in empty fields instead of the default, see [`crate::Settings::arg_texts`]
+    pub hint: Option<String>,
+    /// Help heading, every heading is a step of the wizard
+    pub heading: Option<String>,
     pub optional: bool,
     pub use_equals: bool,
+    /// Values starting with `-` aren't mistaken for flags by clap
+    pub allow_hyphen: bool,
     pub forbid_empty: bool,
     pub kind: ArgKind,
+    /// Restored by [`ArgState::reset`]
+    pub initial: ArgKind,
     pub validation_error: Option<String>,
-    pub localization: &'s Localization,
+    /// Set to focus the widget on the next frame
+    pub request_focus: bool,
+    /// Whether the widget had focus last frame
+    pub has_focus: bool,
+    /// Values start as the defaults and can be reset to them
+    pub prefill_defaults: bool,
+    pub dialog: DialogOptions,
+    /// The binary creates the path, so it may not exist yet, see [`crate::Settings::output_paths`]
+    pub output_path: bool,
+    pub localization: Arc<Localization>,
 }
 
-#[derive(Debug, Clone)]
+/// A value from clap's possible values that isn't hidden
+#[derive(Debug, Clone, PartialEq)]
+pub struct PossibleValue {
+    pub name: String,
+    /// Shown when hovering over the value
+    pub help: Option<String>,
+}
+
+/// Options for individual arguments from [`crate::Settings`], by argument id
+#[derive(Debug, Default)]
+pub struct ArgOptions {
+    /// See [`crate::Settings::occurrence_labels`]
+    pub occurrence_labels: HashMap<String, Vec<String>>,
+    /// See [`crate::Settings::prefill_defaults`]
+    pub prefill_defaults: bool,
+    /// See [`crate::Settings::dialog_bookmarks`]
+    pub dialog_bookmarks: Vec<PathBuf>,
+    /// See [`crate::Settings::file_filters`]
+    pub file_filters: HashMap<String, Vec<String>>,
+    /// See [`crate::Settings::output_paths`]
+    pub output_paths: Vec<String>,
+    /// See [`crate::Settings::arg_texts`]
+    pub arg_texts: HashMap<String, ArgText>,
+    /// See [`crate::Settings::hide_args`]
+    pub hide_args: Vec<String>,
+    /// See [`crate::Settings::hide_subcommands`]
+    pub hide_subcommands: Vec<String>,
+    /// See [`crate::Settings::layout`]
+    pub layout: Vec<LayoutItem>,
+}
+
+impl ArgOptions {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            occurrence_labels: settings.occurrence_labels.clone(),
+            prefill_defaults: settings.prefill_defaults,
+            dialog_bookmarks: settings.dialog_bookmarks.clone(),
+            file_filters: settings.file_filters.clone(),
+            output_paths: settings.output_paths.clone(),
+            arg_texts: settings.arg_texts.clone(),
+            hide_args: settings.hide_args.clone(),
+            hide_subcommands: settings.hide_subcommands.clone(),
+            layout: settings.layout.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArgKind {
     String {
         value: (String, Uuid),
         default: Option<String>,
-        possible: Vec<String>,
+        possible: Vec<PossibleValue>,
         value_hint: ValueHint,
     },
     MultipleStrings {
         values: Vec<(String, Uuid)>,
         default: Vec<String>,
-        possible: Vec<String>,
+        possible: Vec<PossibleValue>,
         multiple_values: bool,
         multiple_occurrences: bool,
         use_delimiter: bool,
         req_delimiter: bool,
         value_hint: ValueHint,
     },
-    Occurences(i32),
-    Bool(bool),
+    /// Single value with an integer or float value parser
+    Number {
+        /// None if an optional argument isn't set
+        value: Option<f64>,
+        default: Option<f64>,
+        integer: bool,
+        /// Range of the parsed type
+        range: RangeInclusive<f64>,
+    },
+    Occurences {
+        count: i32,
+        /// Names of the counts starting from 0, the count can't go past the last one
+        labels: Vec<String>,
+    },
+    /// Checked when the value is true, which for flags with
+    /// [`ArgAction::SetFalse`] is when they aren't passed
+    Bool {
+        value: bool,
+        /// Value when the flag isn't passed
+        default: bool,
+    },
+}
+
+/// Integer and float types with their ranges, for arguments with their value parser
+fn number_type(arg: &Arg) -> Option<(bool, RangeInclusive<f64>)> {
+    let id = arg.get_value_parser().type_id();
+    macro_rules! check {
+        ($($ty:ty => $integer:expr),*) => {
+            $(
+                if id == (&<$ty>::default()).into() {
+                    return Some(($integer, <$ty>::MIN as f64..=<$ty>::MAX as f64));
+                }
+            )*
+        };
+    }
+    check!(
+        i8 => true, i16 => true, i32 => true, i64 => true, isize => true,
+        u8 => true, u16 => true, u32 => true, u64 => true, usize => true,
+        f32 => false, f64 => false
+    );
+    None
 }
 
-impl<'s> ArgState<'s> {
-    pub fn new(arg: &Arg, localization: &'s Localization) -> Self {
-        let kind = if arg.is_takes_value_set() {
+impl ArgState {
+    pub fn new(arg: &Arg, localization: Arc<Localization>, options: &ArgOptions) -> Self {
+        let action = arg.get_action();
+        let occurrences = || ArgKind::Occurences {
+            count: 0,
+            labels: options
+                .occurrence_labels
+                .get(arg.get_id())
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let mut kind = if let ArgAction::Count = action {
+            occurrences()
+        } else if let ArgAction::SetTrue | ArgAction::SetFalse = action {
+            let default = arg
+                .get_default_values()
+                .first()
+                .and_then(|d| d.to_str()?.parse().ok())
+                .unwrap_or(matches!(action, ArgAction::SetFalse));
+            ArgKind::Bool {
+                value: default,
+                default,
+            }
+        } else if arg.is_takes_value_set() {
             let mut default = arg
                 .get_default_values()
                 .iter()
                 .map(|s| s.to_string_lossy().into_owned());
 
-            let possible = arg
-                .get_possible_values()
-                .unwrap_or_default()
+            // Set directly, or by a value parser like `clap::value_parser!(SomeEnum)`
+            let possible: Vec<clap::PossibleValue> = match arg.get_possible_values() {
+                Some(possible) => possible.to_vec(),
+                None => arg
+                    .get_value_parser()
+                    .possible_values()
+                    .map(Iterator::collect)
+                    .unwrap_or_default(),
+            };
+            let possible: Vec<PossibleValue> = possible
                 .iter()
-                .map(|v| v.get_name().to_string())
+                .filter(|v| !v.is_hide_set())
+                .map(|v| PossibleValue {
+                    name: v.get_name().to_string(),
+                    help: v.get_help().map(ToString::to_string),
+                })
                 .collect();
 
             let multiple_values = arg.is_multiple_values_set();
             let multiple_occurrences = arg.is_multiple_occurrences_set();
 
+            let number = number_type(arg).filter(|_| possible.is_empty());
+
             if multiple_occurrences | multiple_values {
                 ArgKind::MultipleStrings {
                     values: vec![],
@@ -70,6 +249,18 @@ impl<'s> ArgState<'s> {
                     req_delimiter: arg.is_require_value_delimiter_set(),
                     value_hint: arg.get_value_hint(),
                 }
+            } else if let Some((integer, range)) = number {
+                let default = default.next().and_then(|d| d.parse().ok());
+                // Required numbers always have a value, there's no empty state to show
+                let value = arg
+                    .is_required_set()
+                    .then(|| initial_number(default, &range));
+                ArgKind::Number {
+                    value,
+                    default,
+                    integer,
+                    range,
+                }
             } else {
                 ArgKind::String {
                     value: (String::new(), Uuid::new_v4()),
@@ -79,30 +270,200 @@ impl<'s> ArgState<'s> {
                 }
             }
         } else if arg.is_multiple_occurrences_set() {
-            ArgKind::Occurences(0)
+            occurrences()
         } else {
-            ArgKind::Bool(false)
+            ArgKind::Bool {
+                value: false,
+                default: false,
+            }
         };
 
+        if options.prefill_defaults {
+            match &mut kind {
+                ArgKind::String { value, default, .. } => {
+                    value.0 = default.clone().unwrap_or_default();
+                }
+                ArgKind::MultipleStrings {
+                    values, default, ..
+                } => push_values(values, default.clone()),
+                ArgKind::Number { value, default, .. } => *value = value.or(*default),
+                ArgKind::Occurences { .. } | ArgKind::Bool { .. } => {}
+            }
+        }
+
         Self {
-            name: arg.get_id().to_string().to_sentence_case(),
+            id: arg.get_id().to_string(),
+            name: Self::name(arg, options),
             call_name: arg
                 .get_long()
                 .map(|s| format!("--{}", s))
                 .or_else(|| arg.get_short().map(|c| format!("-{}", c))),
-            desc: arg
-                .get_long_help()
-                .map(ToString::to_string)
-                .or_else(|| arg.get_help().map(ToString::to_string)),
+            flags: Self::flags(arg),
+            aliases: Self::aliases(arg),
+            desc: Self::tooltip(arg, options),
+            hint: options
+                .arg_texts
+                .get(arg.get_id())
+                .and_then(|text| text.hint.clone()),
+            heading: arg.get_help_heading().map(ToString::to_string),
             optional: !arg.is_required_set(),
             use_equals: arg.is_require_equals_set(),
+            allow_hyphen: arg.is_allow_hyphen_values_set(),
             forbid_empty: arg.is_forbid_empty_values_set(),
+            initial: kind.clone(),
             kind,
             validation_error: None,
+            request_focus: false,
+            has_focus: false,
+            prefill_defaults: options.prefill_defaults,
+            output_path: options.output_paths.iter().any(|id| id == arg.get_id()),
+            dialog: DialogOptions {
+                bookmarks: options.dialog_bookmarks.clone(),
+                extensions: options
+                    .file_filters
+                    .get(arg.get_id())
+                    .cloned()
+                    .or_else(|| Self::desc(arg).map(|desc| file_dialog::extensions_in(&desc)))
+                    .unwrap_or_default(),
+            },
             localization,
         }
     }
 
+    /// Label of the argument, also used by the palette before the state is built
+    pub fn name(arg: &Arg, options: &ArgOptions) -> String {
+        options
+            .arg_texts
+            .get(arg.get_id())
+            .and_then(|text| text.label.clone())
+            .unwrap_or_else(|| arg.get_id().to_sentence_case())
+    }
+
+    /// Shown when hovering over the label, the help unless it's overridden
+    pub fn tooltip(arg: &Arg, options: &ArgOptions) -> Option<String> {
+        options
+            .arg_texts
+            .get(arg.get_id())
+            .and_then(|text| text.tooltip.clone())
+            .or_else(|| Self::desc(arg))
+    }
+
+    fn flags(arg: &Arg) -> Option<String> {
+        let flags: Vec<String> = arg
+            .get_short()
+            .map(|c| format!("-{}", c))
+            .into_iter()
+            .chain(arg.get_long().map(|s| format!("--{}", s)))
+            .collect();
+        (!flags.is_empty()).then(|| flags.join(", "))
+    }
+
+    fn aliases(arg: &Arg) -> Vec<String> {
+        let shorts = arg.get_visible_short_aliases().unwrap_or_default();
+        let longs = arg.get_visible_aliases().unwrap_or_default();
+        shorts
+            .into_iter()
+            .map(|c| format!("-{}", c))
+            .chain(longs.into_iter().map(|s| format!("--{}", s)))
+            .collect()
+    }
+
+    pub fn desc(arg: &Arg) -> Option<String> {
+        arg.get_long_help()
+            .map(ToString::to_string)
+            .or_else(|| arg.get_help().map(ToString::to_string))
+    }
+
+    /// True for arguments that take file or directory paths.
+    pub fn is_path(&self) -> bool {
+        match &self.kind {
+            ArgKind::String {
+                value_hint,
+                possible,
+                ..
+            }
+            | ArgKind::MultipleStrings {
+                value_hint,
+                possible,
+                ..
+            } => {
+                possible.is_empty()
+                    && matches!(
+                        value_hint,
+                        ValueHint::AnyPath
+                            | ValueHint::FilePath
+                            | ValueHint::DirPath
+                            | ValueHint::ExecutablePath
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_multiple(&self) -> bool {
+        matches!(self.kind, ArgKind::MultipleStrings { .. })
+    }
+
+    /// Sets the value to the first path, or appends all paths for multi-value arguments.
+    pub fn fill_paths(&mut self, paths: &[String]) {
+        match &mut self.kind {
+            ArgKind::String { value, .. } => {
+                if let Some(path) = paths.first() {
+                    value.0 = path.clone();
+                }
+            }
+            ArgKind::MultipleStrings { values, .. } => push_values(values, paths.to_vec()),
+            _ => {}
+        }
+    }
+
+    /// Sets the value from text, like a value in a file
+    pub fn set_values(&mut self, values: &[String]) -> Result<(), String> {
+        let invalid = self
+            .localization
+            .error_invalid_value
+            .format(&[("name", &self.name), ("value", &values.join(", "))]);
+        let single = match values {
+            [] => "",
+            [value] => value.trim(),
+            _ if self.is_multiple() => "",
+            _ => return Err(invalid),
+        };
+
+        match &mut self.kind {
+            ArgKind::String { value, .. } => value.0 = single.to_string(),
+            ArgKind::MultipleStrings { values: v, .. } => {
+                *v = values
+                    .iter()
+                    .map(|value| (value.clone(), Uuid::new_v4()))
+                    .collect()
+            }
+            ArgKind::Number { value, integer, .. } => match single.parse::<f64>() {
+                Ok(n) if !*integer || n.fract() == 0.0 => *value = Some(n),
+                _ if single.is_empty() => *value = None,
+                _ => return Err(invalid),
+            },
+            ArgKind::Bool { value, .. } => {
+                *value = match single.to_lowercase().as_str() {
+                    "true" | "yes" | "1" => true,
+                    "false" | "no" | "0" | "" => false,
+                    _ => return Err(invalid),
+                }
+            }
+            ArgKind::Occurences { count, .. } => match single.parse::<i32>() {
+                Ok(n) if n >= 0 => *count = n,
+                _ if single.is_empty() => *count = 0,
+                _ => return Err(invalid),
+            },
+        }
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.kind = self.initial.clone();
+        self.validation_error = None;
+    }
+
     pub fn update_validation_error(&mut self, name: &str, message: &str) {
         self.validation_error = (self.name == name).then(|| message.to_string());
     }
@@ -110,67 +471,213 @@ impl<'s> ArgState<'s> {
     #[allow(clippy::too_many_arguments)]
     pub fn ui_single_row(
         ui: &mut Ui,
+        arg_id: &str,
         (value, id): &mut (String, Uuid),
         default: &Option<String>,
-        possible: &[String],
+        hint: Option<&str>,
+        possible: &[PossibleValue],
         value_hint: ValueHint,
+        output_path: bool,
         optional: bool,
         validation_error: bool,
-        localization: &'s Localization,
+        request_focus: bool,
+        resettable: bool,
+        history: &[String],
+        working_dir: Option<&Path>,
+        dialog: &DialogOptions,
+        localization: &Localization,
     ) -> Response {
         let is_error = (!optional && value.is_empty()) || validation_error;
         if is_error {
             Klask::set_error_style(ui);
         }
 
-        let inner_response = if possible.is_empty() {
-            ui.horizontal(|ui| {
-                if matches!(
+        let response = if possible.is_empty() {
+            let inner_response = ui.horizontal(|ui| {
+                let files = matches!(
                     value_hint,
                     ValueHint::AnyPath | ValueHint::FilePath | ValueHint::ExecutablePath
-                ) && ui.button(&localization.select_file).clicked()
-                {
-                    if let Some(file) = FileDialog::new().pick_file() {
-                        *value = file.to_string_lossy().into_owned();
+                );
+                let extensions = &dialog.extensions;
+                if files && ui.button(&localization.select_file).clicked() {
+                    if let Some(file) = file_dialog::pick(ui, arg_id, false, None, extensions) {
+                        *value = file;
                     }
                 }
 
-                if matches!(value_hint, ValueHint::AnyPath | ValueHint::DirPath)
-                    && ui.button(&localization.select_directory).clicked()
-                {
-                    if let Some(file) = FileDialog::new().pick_folder() {
-                        *value = file.to_string_lossy().into_owned();
+                let dirs = matches!(value_hint, ValueHint::AnyPath | ValueHint::DirPath);
+                if dirs && ui.button(&localization.select_directory).clicked() {
+                    if let Some(dir) = file_dialog::pick(ui, arg_id, true, None, &[]) {
+                        *value = dir;
                     }
                 }
 
-                ui.add(
-                    TextEdit::singleline(value).hint_text(match (default, optional) {
-                        (Some(default), _) => default.as_str(),
-                        (_, true) => localization.optional.as_str(),
-                        (_, false) => "",
-                    }),
-                );
+                if (files || dirs) && !dialog.bookmarks.is_empty() {
+                    ui.menu_button("🔖", |ui| {
+                        for bookmark in &dialog.bookmarks {
+                            if ui.button(bookmark.display().to_string()).clicked() {
+                                ui.close_menu();
+                                let folder = !files;
+                                if let Some(path) = file_dialog::pick(
+                                    ui,
+                                    arg_id,
+                                    folder,
+                                    Some(bookmark),
+                                    extensions,
+                                ) {
+                                    *value = path;
+                                }
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text(&localization.bookmarks);
+                }
+
+                // The button disappears once clicked, so the focus moves to the text field
+                let mut reset = false;
+                if let Some(default) = default.as_ref().filter(|d| resettable && *d != value) {
+                    if ui
+                        .small_button("↺")
+                        .on_hover_text(&localization.reset_to_default)
+                        .clicked()
+                    {
+                        *value = default.clone();
+                        reset = true;
+                    }
+                }
+
+                // Relative paths are resolved once the working directory is set
+                let resolved = working_dir
+                    .filter(|_| path_kind(value_hint).is_some())
+                    .filter(|_| !value.is_empty() && Path::new(value).is_relative())
+                    .map(|dir| dir.join(&value));
+                let resolves_to = resolved.as_ref().map(|resolved| {
+                    let path = resolved.display().to_string();
+                    let resolves_to = localization.resolves_to.format(&[("path", &path)]);
+                    if resolved.exists() || output_path {
+                        ui.label(RichText::new("↳").weak())
+                            .on_hover_text(&resolves_to);
+                    } else {
+                        ui.colored_label(Color32::GOLD, "⚠").on_hover_text(
+                            localization.path_doesnt_exist.format(&[("path", &path)]),
+                        );
+                    }
+                    resolves_to
+                });
+
+                let mut response = ui.add(TextEdit::singleline(value).id(Id::new(*id)).hint_text(
+                    match (hint, default, optional) {
+                        (Some(hint), ..) => hint,
+                        (_, Some(default), _) => default.as_str(),
+                        (.., true) => localization.optional.as_str(),
+                        (.., false) => "",
+                    },
+                ));
+                if let Some(resolves_to) = resolves_to {
+                    response = response.on_hover_text(resolves_to);
+                }
+
+                if request_focus || reset {
+                    focus(&response);
+                }
+
+                // Previous values, then files that complete what's typed
+                let suggested = if response.has_focus() {
+                    let candidates = history.iter().map(|h| Suggestion::new(h.as_str(), None));
+                    let mut suggested = suggestions::matching(value, candidates);
+                    if let Some(only_dirs) = path_kind(value_hint) {
+                        let extensions = &dialog.extensions;
+                        suggested.extend(path_completions(
+                            ui,
+                            &response,
+                            value,
+                            working_dir,
+                            only_dirs,
+                            extensions,
+                        ));
+                    }
+                    suggested
+                } else {
+                    vec![]
+                };
+                if let Some(chosen) = suggestions::show(ui, &response, &suggested) {
+                    *value = chosen;
+                }
 
-                Some(())
-            })
+                response
+            });
+
+            // Include the text field, so the response has focus when it does
+            inner_response.response.union(inner_response.inner)
         } else {
-            ComboBox::from_id_source(id)
-                .selected_text(&*value)
+            let inner_response = ComboBox::from_id_source(id)
+                .selected_text(localization.possible_value_name(arg_id, value))
                 .show_ui(ui, |ui| {
                     if optional {
                         ui.selectable_value(value, String::new(), "None");
                     }
                     for p in possible {
-                        ui.selectable_value(value, p.clone(), p);
+                        let name = localization.possible_value_name(arg_id, &p.name);
+                        let response = ui.selectable_value(value, p.name.clone(), name);
+                        if let Some(help) = &p.help {
+                            response.on_hover_text(help);
+                        }
                     }
-                })
+                });
+
+            if request_focus {
+                focus(&inner_response.response);
+            }
+
+            // Let keyboard users change the value without opening the popup
+            if inner_response.response.has_focus() {
+                let step = arrow_step(ui);
+                if step != 0 {
+                    let options: Vec<&str> = optional
+                        .then_some("")
+                        .into_iter()
+                        .chain(possible.iter().map(|p| p.name.as_str()))
+                        .collect();
+                    let current = options.iter().position(|o| o == value);
+                    let next = match current {
+                        Some(i) => (i as i32 + step).clamp(0, options.len() as i32 - 1) as usize,
+                        None => 0,
+                    };
+                    *value = options[next].to_string();
+                }
+            }
+
+            // The help of the selected value
+            match possible.iter().find(|p| p.name == *value) {
+                Some(PossibleValue {
+                    help: Some(help), ..
+                }) => inner_response.response.on_hover_text(help),
+                _ => inner_response.response,
+            }
         };
 
         if is_error {
             ui.reset_style();
         }
 
-        inner_response.response
+        response
+    }
+
+    /// Whether clap would parse `value` as a flag if it was passed on its own
+    pub fn is_hyphenated(&self, value: &str) -> bool {
+        !self.allow_hyphen && value.starts_with('-')
+    }
+
+    fn push_value(&self, args: &mut Vec<String>, value: String) {
+        match &self.call_name {
+            Some(call_name) if self.use_equals || self.is_hyphenated(&value) => {
+                args.push(format!("{}={}", call_name, value))
+            }
+            Some(call_name) => args.extend_from_slice(&[call_name.clone(), value]),
+            // Positional values are separated with `--` instead, see `AppState::get_cmd_args`
+            None => args.push(value),
+        }
     }
 
     pub fn get_cmd_args(&self, mut args: Vec<String>) -> Result<Vec<String>, String> {
@@ -179,22 +686,12 @@ impl<'s> ArgState<'s> {
                 value: (value, _), ..
             } => {
                 if !value.is_empty() {
-                    if let Some(call_name) = self.call_name.as_ref() {
-                        if self.use_equals {
-                            args.push(format!("{}={}", call_name, value));
-                        } else {
-                            args.extend_from_slice(&[call_name.clone(), value.clone()]);
-                        }
-                    } else {
-                        args.push(value.clone());
-                    }
+                    self.push_value(&mut args, value.clone());
                 } else if !self.optional {
-                    return Err(format!(
-                        "{}{}{}",
-                        self.localization.error_is_required.0,
-                        self.name,
-                        self.localization.error_is_required.1
-                    ));
+                    return Err(self
+                        .localization
+                        .error_is_required
+                        .format(&[("name", &self.name)]));
                 }
             }
             ArgKind::MultipleStrings {
@@ -208,8 +705,9 @@ impl<'s> ArgState<'s> {
                 if !values.is_empty() {
                     if let Some(call_name) = &self.call_name {
                         let single = *use_delimiter || values.len() == 1;
+                        let hyphenated = values.iter().any(|(s, _)| self.is_hyphenated(s));
                         match (
-                            self.use_equals,
+                            self.use_equals || hyphenated,
                             *multiple_values,
                             *multiple_occurrences,
                             single,
@@ -254,6 +752,12 @@ impl<'s> ArgState<'s> {
                             (_, false, false, _) => unreachable!(
                                 "Either multiple_values or multiple_occurrences must be true"
                             ),
+                            (true, true, false, false) if hyphenated => {
+                                return Err(self
+                                    .localization
+                                    .error_hyphen_values
+                                    .format(&[("name", &self.name)]))
+                            }
                             (true, true, false, false) => return Err("Can't be represented".into()),
                         }
                     } else {
@@ -263,8 +767,18 @@ impl<'s> ArgState<'s> {
                     }
                 }
             }
-            &ArgKind::Occurences(i) => {
-                for _ in 0..i {
+            ArgKind::Number { value, integer, .. } => match value {
+                Some(value) => self.push_value(&mut args, format_number(*value, *integer)),
+                None if !self.optional => {
+                    return Err(self
+                        .localization
+                        .error_is_required
+                        .format(&[("name", &self.name)]))
+                }
+                None => {}
+            },
+            &ArgKind::Occurences { count, .. } => {
+                for _ in 0..count {
                     args.push(
                         self.call_name
                             .clone()
@@ -272,8 +786,8 @@ impl<'s> ArgState<'s> {
                     );
                 }
             }
-            &ArgKind::Bool(bool) => {
-                if bool {
+            &ArgKind::Bool { value, default } => {
+                if value != default {
                     args.push(self.call_name.clone().unwrap_or_else(|| "true".to_owned()));
                 }
             }
@@ -283,35 +797,86 @@ impl<'s> ArgState<'s> {
     }
 }
 
-impl Widget for &mut ArgState<'_> {
-    fn ui(self, ui: &mut Ui) -> eframe::egui::Response {
-        let localization = self.localization;
-        let label = ui.label(&self.name);
+impl ArgState {
+    /// Shows the label and the value, in two columns of a grid
+    pub fn show(&mut self, ui: &mut Ui, form: &FormContext) -> Response {
+        let flag_names = form.flag_names;
+        let label = match (&self.flags, flag_names) {
+            (Some(flags), FlagNames::Beside) => {
+                ui.horizontal(|ui| {
+                    ui.label(&self.name);
+                    ui.label(RichText::new(flags).monospace().weak());
+                })
+                .response
+            }
+            (Some(flags), FlagNames::Instead) => ui.label(RichText::new(flags).monospace()),
+            _ => ui.label(&self.name),
+        };
 
-        if let Some(desc) = &self.desc {
-            label.on_hover_text(desc);
+        let show_name = self.flags.is_some() && flag_names == FlagNames::Instead;
+        if show_name || self.desc.is_some() || !self.aliases.is_empty() {
+            label.on_hover_ui(|ui| {
+                if show_name {
+                    ui.label(RichText::new(&self.name).strong());
+                }
+                if let Some(desc) = &self.desc {
+                    markdown::show(ui, desc);
+                }
+                if !self.aliases.is_empty() {
+                    ui.label(format!(
+                        "{}: {}",
+                        self.localization.aliases,
+                        self.aliases.join(", ")
+                    ));
+                }
+            });
         }
 
         // Grid column automatically switches here
+        self.show_value(ui, form)
+    }
 
+    /// Shows the widget for editing the value, without the label
+    pub fn show_value(&mut self, ui: &mut Ui, form: &FormContext) -> Response {
+        let localization = self.localization.clone();
         let is_validation_error = self.validation_error.is_some();
+        // The error is about the value clap saw, so it goes away once the value is edited
+        let validated = is_validation_error.then(|| self.kind.clone());
+        let request_focus = std::mem::take(&mut self.request_focus);
+        let mut has_focus = false;
+        let history = form
+            .values
+            .and_then(|values| values.get(&self.id))
+            .map_or(&[][..], Vec::as_slice);
 
-        match &mut self.kind {
+        let response = match &mut self.kind {
             ArgKind::String {
                 value,
                 default,
                 possible,
                 value_hint,
-            } => ArgState::ui_single_row(
-                ui,
-                value,
-                default,
-                possible,
-                *value_hint,
-                self.optional && !self.forbid_empty,
-                is_validation_error,
-                localization,
-            ),
+            } => {
+                let response = ArgState::ui_single_row(
+                    ui,
+                    &self.id,
+                    value,
+                    default,
+                    self.hint.as_deref(),
+                    possible,
+                    *value_hint,
+                    self.output_path,
+                    self.optional && !self.forbid_empty,
+                    is_validation_error,
+                    request_focus,
+                    self.prefill_defaults,
+                    history,
+                    form.working_dir,
+                    &self.dialog,
+                    &localization,
+                );
+                has_focus = response.has_focus();
+                response
+            }
             ArgKind::MultipleStrings {
                 values,
                 default,
@@ -319,37 +884,84 @@ impl Widget for &mut ArgState<'_> {
                 value_hint,
                 ..
             } => {
-                let forbid_empty = self.forbid_empty;
+                let (forbid_empty, output_path) = (self.forbid_empty, self.output_path);
+                let (arg_id, dialog, hint) = (&self.id, &self.dialog, self.hint.as_deref());
+
+                // Pasting multiple lines into a row splits them into rows
+                let focused = values
+                    .iter()
+                    .position(|(_, id)| ui.memory().has_focus(Id::new(*id)));
+                if let Some(index) = focused {
+                    if let Some(text) = take_multiline_paste(ui) {
+                        let mut lines = split_lines(&text).into_iter();
+                        values[index].0 = lines.next().unwrap_or_default();
+                        let rows = lines.map(|line| (line, Uuid::new_v4()));
+                        values.splice(index + 1..index + 1, rows);
+                    }
+                }
+
                 let mut list = ui
                     .vertical(|ui| {
                         let mut remove_index = None;
+                        // Rows come and go, so keyboard focus moves to what takes their place
+                        let mut focus_new_value = false;
 
                         for (index, value) in values.iter_mut().enumerate() {
                             ui.horizontal(|ui| {
-                                if ui.small_button("-").clicked() {
+                                let remove = ui.small_button("-");
+                                if remove.clicked() {
                                     remove_index = Some(index);
+                                    focus_new_value = remove.has_focus();
                                 }
 
-                                ArgState::ui_single_row(
+                                has_focus |= ArgState::ui_single_row(
                                     ui,
+                                    arg_id,
                                     value,
                                     &None,
+                                    hint,
                                     possible,
                                     *value_hint,
+                                    output_path,
                                     !forbid_empty,
                                     is_validation_error,
-                                    localization,
-                                );
+                                    request_focus && index == 0,
+                                    false,
+                                    history,
+                                    form.working_dir,
+                                    dialog,
+                                    &localization,
+                                )
+                                .has_focus();
                             });
                         }
 
                         if let Some(index) = remove_index {
                             values.remove(index);
+                            // The next row, or the button for adding one after the last
+                            if let Some((_, next)) = values.get(index).filter(|_| focus_new_value) {
+                                ui.memory().request_focus(Id::new(*next));
+                                focus_new_value = false;
+                            }
                         }
 
                         ui.horizontal(|ui| {
-                            if ui.button(&localization.new_value).clicked() {
-                                values.push((String::new(), Uuid::new_v4()));
+                            let new_value = ui.button(&localization.new_value);
+                            if (request_focus && values.is_empty()) || focus_new_value {
+                                focus(&new_value);
+                            }
+                            if new_value.clicked() {
+                                let id = Uuid::new_v4();
+                                values.push((String::new(), id));
+                                ui.memory().request_focus(Id::new(id));
+                            }
+
+                            if ui.button(&localization.paste_list).clicked() {
+                                if let Ok(text) =
+                                    arboard::Clipboard::new().and_then(|mut c| c.get_text())
+                                {
+                                    push_values(values, split_lines(&text));
+                                }
                             }
 
                             let text = if default.is_empty() {
@@ -371,28 +983,199 @@ impl Widget for &mut ArgState<'_> {
 
                 if let Some(message) = &self.validation_error {
                     list = list.on_hover_text(message);
-                    if list.changed() {
-                        self.validation_error = None;
-                    }
                 }
 
                 list
             }
-            ArgKind::Occurences(i) => {
+            ArgKind::Number {
+                value,
+                default,
+                integer,
+                range,
+            } => {
+                let (optional, hint) = (self.optional, &self.hint);
+                let inner_response = ui.horizontal(|ui| {
+                    if optional {
+                        let mut set = value.is_some();
+                        if ui.checkbox(&mut set, "").changed() {
+                            *value = set.then(|| initial_number(*default, range));
+                        }
+                    }
+
+                    match value {
+                        Some(value) => {
+                            if is_validation_error {
+                                Klask::set_error_style(ui);
+                            }
+                            let mut drag = DragValue::new(value).clamp_range(range.clone());
+                            drag = if *integer {
+                                drag.speed(1.0).max_decimals(0)
+                            } else {
+                                drag.speed(0.1)
+                            };
+                            let response = ui.add(drag);
+                            if is_validation_error {
+                                ui.reset_style();
+                            }
+                            response
+                        }
+                        None => {
+                            let hint = match (hint, default) {
+                                (Some(hint), _) => hint.clone(),
+                                (_, Some(default)) => format_number(*default, *integer),
+                                (_, None) => localization.optional.clone(),
+                            };
+                            ui.label(RichText::new(hint).weak())
+                        }
+                    }
+                });
+                let response = inner_response.response.union(inner_response.inner);
+                if request_focus {
+                    focus(&response);
+                }
+                has_focus = response.has_focus();
+                response
+            }
+            ArgKind::Occurences { count, labels } if !labels.is_empty() => {
+                let label = |count: i32| labels.get(count as usize).map_or("", String::as_str);
+                let response = ComboBox::from_id_source(("klask_occurrences", &self.id))
+                    .selected_text(label(*count))
+                    .show_ui(ui, |ui| {
+                        for i in 0..labels.len() as i32 {
+                            ui.selectable_value(count, i, label(i));
+                        }
+                    })
+                    .response;
+                if request_focus {
+                    focus(&response);
+                }
+                if response.has_focus() {
+                    *count = (*count + arrow_step(ui)).clamp(0, labels.len() as i32 - 1);
+                }
+                response
+            }
+            ArgKind::Occurences { count, .. } => {
                 ui.horizontal(|ui| {
-                    if ui.small_button("-").clicked() {
-                        *i = (*i - 1).max(0);
+                    let minus = ui.small_button("-");
+                    if minus.clicked() {
+                        *count = (*count - 1).max(0);
                     }
 
-                    ui.label(i.to_string());
+                    ui.label(count.to_string());
+
+                    let plus = ui.small_button("+");
+                    if request_focus {
+                        focus(&plus);
+                    }
+                    if plus.clicked() {
+                        *count += 1;
+                    }
 
-                    if ui.small_button("+").clicked() {
-                        *i += 1;
+                    // Up counts up while either button has focus
+                    if minus.has_focus() || plus.has_focus() {
+                        *count = (*count - arrow_step(ui)).max(0);
                     }
                 })
                 .response
             }
-            ArgKind::Bool(bool) => ui.checkbox(bool, ""),
+            ArgKind::Bool { value, .. } => {
+                let response = ui.checkbox(value, "");
+                if request_focus {
+                    focus(&response);
+                }
+                response
+            }
+        };
+
+        self.has_focus = has_focus;
+        if validated.is_some_and(|validated| validated != self.kind) {
+            self.validation_error = None;
         }
+        response
     }
 }
+
+/// Whether only directories complete the value, None if it isn't a path
+fn path_kind(value_hint: ValueHint) -> Option<bool> {
+    match value_hint {
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::ExecutablePath => Some(false),
+        ValueHint::DirPath => Some(true),
+        _ => None,
+    }
+}
+
+/// [`suggestions::paths`] of the text field, only read again when the value changes
+fn path_completions(
+    ui: &Ui,
+    response: &Response,
+    value: &str,
+    working_dir: Option<&Path>,
+    only_dirs: bool,
+    extensions: &[String],
+) -> Vec<Suggestion> {
+    let id = response.id.with("klask_path_completions");
+    let base = working_dir.unwrap_or_else(|| Path::new("."));
+    let cached: Option<(String, PathBuf, Vec<Suggestion>)> = ui.data().get_temp(id);
+    match cached {
+        Some((typed, dir, paths)) if typed == value && dir == base => paths,
+        _ => {
+            let paths = suggestions::paths(value, base, only_dirs, extensions);
+            let cached = (value.to_string(), base.to_path_buf(), paths.clone());
+            ui.data().insert_temp(id, cached);
+            paths
+        }
+    }
+}
+
+/// The default, or the value closest to zero
+fn initial_number(default: Option<f64>, range: &RangeInclusive<f64>) -> f64 {
+    default.unwrap_or_else(|| 0f64.clamp(*range.start(), *range.end()))
+}
+
+/// Formats the value the way it's parsed, integers without a fractional part
+fn format_number(value: f64, integer: bool) -> String {
+    if integer {
+        format!("{}", value.round() as i128)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Appends values, replacing empty rows
+fn push_values(values: &mut Vec<(String, Uuid)>, new: Vec<String>) {
+    values.retain(|(v, _)| !v.is_empty());
+    values.extend(new.into_iter().map(|v| (v, Uuid::new_v4())));
+}
+
+/// Non-empty lines, trimmed
+fn split_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Removes a paste event with multiple lines, so a text field doesn't receive it
+fn take_multiline_paste(ui: &Ui) -> Option<String> {
+    let mut input = ui.ctx().input_mut();
+    let index = input
+        .events
+        .iter()
+        .position(|e| matches!(e, Event::Paste(text) if split_lines(text).len() > 1))?;
+    match input.events.remove(index) {
+        Event::Paste(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// 1 if the down arrow was pressed, -1 for the up arrow, for stepping through values
+fn arrow_step(ui: &Ui) -> i32 {
+    let input = ui.input();
+    i32::from(input.key_pressed(Key::ArrowDown)) - i32::from(input.key_pressed(Key::ArrowUp))
+}
+
+fn focus(response: &Response) {
+    response.request_focus();
+    response.scroll_to_me(Some(Align::Center));
+}
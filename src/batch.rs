@@ -0,0 +1,394 @@
+use crate::{
+    app_state::AppState,
+    child_app::ChildApp,
+    output::{Output, OutputOptions},
+    ExecutionError, RunSnapshot,
+};
+use clap::Command;
+use eframe::egui::{
+    Button, CollapsingHeader, Color32, Context, DragValue, ProgressBar, RichText, Ui,
+};
+use rfd::FileDialog;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+/// Values of arguments by id, for one run
+type Row = Vec<(String, Vec<String>)>;
+
+/// Runs with argument values from the rows of a CSV or JSON file,
+/// see [`crate::Settings::enable_batch`]
+#[derive(Debug)]
+pub struct Batch {
+    /// Name of the loaded file
+    file: Option<String>,
+    rows: Vec<Run>,
+    concurrency: usize,
+    /// Whether waiting runs are started
+    active: bool,
+    /// Error from loading the file
+    error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Run {
+    values: Row,
+    /// None until the batch starts, or if the values are invalid
+    snapshot: Option<RunSnapshot>,
+    status: Status,
+    output: Output,
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Waiting,
+    Running,
+    Finished(bool),
+    Error(String),
+}
+
+impl Default for Batch {
+    fn default() -> Self {
+        Self {
+            file: None,
+            rows: vec![],
+            concurrency: 4,
+            active: false,
+            error: None,
+        }
+    }
+}
+
+impl Batch {
+    fn load(&mut self, path: &Path) {
+        let result = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| match path.extension() {
+                Some(ext) if ext == "json" => parse_json(&text),
+                _ => parse_csv(&text),
+            });
+
+        match result {
+            Ok(rows) => {
+                self.file = Some(path.to_string_lossy().into_owned());
+                self.rows = rows
+                    .into_iter()
+                    .map(|values| Run {
+                        values,
+                        snapshot: None,
+                        status: Status::Waiting,
+                        output: Output::None,
+                    })
+                    .collect();
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+
+    /// Prepares a run for every row, with the values of the row on top of the form.
+    /// `template` has everything but the arguments, and `app` validates them.
+    pub fn start(&mut self, state: &AppState, template: &RunSnapshot, app: &mut Command<'static>) {
+        for run in &mut self.rows {
+            let mut state = state.clone();
+            let args = run
+                .values
+                .iter()
+                .try_for_each(|(id, values)| state.set_arg(id, values))
+                .and_then(|()| state.get_cmd_args(vec![]));
+            let result = args.map_err(ExecutionError::from).and_then(|args| {
+                app.try_get_matches_from_mut(&args)?;
+                Ok(args)
+            });
+
+            match result {
+                Ok(args) => {
+                    run.snapshot = Some(RunSnapshot {
+                        args,
+                        ..template.clone()
+                    });
+                    run.status = Status::Waiting;
+                }
+                Err(err) => {
+                    run.snapshot = None;
+                    run.status = Status::Error(err.to_string());
+                }
+            }
+            run.output = Output::None;
+        }
+        self.active = true;
+    }
+
+    /// Kills the running runs, and doesn't start the waiting ones
+    pub fn stop(&mut self) {
+        for run in &mut self.rows {
+            if let Output::Child(child, ..) = &mut run.output {
+                child.kill();
+            }
+        }
+        self.active = false;
+    }
+
+    /// Reads the output of the runs and starts waiting ones, up to the concurrency limit.
+    /// Called every frame.
+    pub fn update(&mut self, program: Option<&Path>, post_process: &[Vec<String>], ctx: &Context) {
+        for run in &mut self.rows {
+            run.output.read_child(None);
+            if run.status == Status::Running {
+                if let Output::Child(child, ..) = &mut run.output {
+                    // Output closes slightly before the process exits
+                    if !child.is_running() {
+                        if let Some(success) = child.try_exit_status() {
+                            run.status = Status::Finished(success);
+                        }
+                    }
+                }
+            }
+        }
+
+        if !self.active {
+            return;
+        }
+        let mut running = self
+            .rows
+            .iter()
+            .filter(|run| run.status == Status::Running)
+            .count();
+        for run in &mut self.rows {
+            if running >= self.concurrency {
+                break;
+            }
+            let snapshot = match (&run.status, &run.snapshot) {
+                (Status::Waiting, Some(snapshot)) => snapshot.clone(),
+                _ => continue,
+            };
+
+            let child = ChildApp::run(
+                program,
+                snapshot.args,
+                snapshot.env,
+                snapshot.stdin,
+                snapshot.working_dir,
+                post_process,
+                ctx.clone(),
+            );
+            match child {
+                Ok(mut child) => {
+                    // Nobody can answer prompts of a batch
+                    child.close_stdin();
+                    run.output = Output::new_with_child(child);
+                    run.status = Status::Running;
+                    running += 1;
+                }
+                Err(err) => run.status = Status::Error(err.to_string()),
+            }
+        }
+
+        if running == 0 {
+            self.active = false;
+        }
+    }
+
+    /// Returns true when the user starts the batch
+    pub fn show(&mut self, ui: &mut Ui, busy: bool, options: &OutputOptions) -> bool {
+        let (localization, palette) = (options.localization, &options.palette);
+        let mut start = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.active, Button::new(&localization.select_file))
+                .clicked()
+            {
+                if let Some(file) = FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .add_filter("JSON", &["json"])
+                    .pick_file()
+                {
+                    self.load(&file);
+                }
+            }
+            if let Some(file) = &self.file {
+                ui.label(file);
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        if self.rows.is_empty() {
+            return false;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(&localization.parallel_runs);
+            ui.add_enabled(
+                !self.active,
+                DragValue::new(&mut self.concurrency).clamp_range(1..=64),
+            );
+            if self.active {
+                if ui.button(&localization.kill).clicked() {
+                    self.stop();
+                }
+            } else if ui
+                .add_enabled(!busy, Button::new(&localization.run_batch))
+                .clicked()
+            {
+                start = true;
+            }
+        });
+
+        let done = self
+            .rows
+            .iter()
+            .filter(|run| matches!(run.status, Status::Finished(_) | Status::Error(_)))
+            .count();
+        ui.add(
+            ProgressBar::new(done as f32 / self.rows.len() as f32).text(format!(
+                "{}/{}",
+                done,
+                self.rows.len()
+            )),
+        );
+
+        for (index, run) in self.rows.iter_mut().enumerate() {
+            let status = match &run.status {
+                Status::Waiting => RichText::new("…"),
+                Status::Running => RichText::new("▶"),
+                Status::Finished(true) => RichText::new("✔").color(palette.success()),
+                Status::Finished(false) | Status::Error(_) => {
+                    RichText::new("✖").color(palette.failure())
+                }
+            };
+            let label = match &run.snapshot {
+                Some(snapshot) => snapshot.args.join(" "),
+                None => run
+                    .values
+                    .iter()
+                    .map(|(id, values)| format!("{}={}", id, values.join(",")))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            };
+
+            ui.horizontal(|ui| {
+                ui.label(status);
+                CollapsingHeader::new(format!("{}. {}", index + 1, label))
+                    .id_source(("klask_batch_run", index))
+                    .show(ui, |ui| match &run.status {
+                        Status::Error(err) => {
+                            ui.colored_label(Color32::RED, err);
+                        }
+                        _ => {
+                            run.output.show(ui, options);
+                        }
+                    });
+            });
+        }
+        start
+    }
+}
+
+/// The first line has argument ids, every other line is a run. Empty cells keep
+/// the value from the form, and lines in a cell are separate values.
+fn parse_csv(text: &str) -> Result<Vec<Row>, String> {
+    let mut records = parse_csv_records(text)?.into_iter();
+    let header = match records.next() {
+        Some(header) => header,
+        None => return Ok(vec![]),
+    };
+
+    records
+        .enumerate()
+        .map(|(line, record)| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "Row {} has {} values instead of {}",
+                    line + 1,
+                    record.len(),
+                    header.len()
+                ));
+            }
+            Ok(header
+                .iter()
+                .zip(record)
+                .filter(|(_, cell)| !cell.is_empty())
+                .map(|(id, cell)| {
+                    (
+                        id.trim().to_string(),
+                        cell.lines().map(String::from).collect(),
+                    )
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Splits CSV into records of cells, with quoted cells that can contain commas,
+/// newlines and doubled quotes. Empty lines are skipped.
+fn parse_csv_records(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut cell)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut cell));
+                if record.iter().any(|cell| !cell.is_empty()) || record.len() > 1 {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err("Quoted value isn't closed".into());
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// An array of objects from argument ids to values. Arrays are multiple values,
+/// and missing or null values keep the value from the form.
+fn parse_json(text: &str) -> Result<Vec<Row>, String> {
+    let rows: Vec<serde_json::Map<String, Value>> =
+        serde_json::from_str(text).map_err(|err| err.to_string())?;
+
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(id, value)| {
+                    let values = match value {
+                        Value::Array(values) => values.into_iter().map(json_value).collect(),
+                        value => json_value(value).map(|value| vec![value]),
+                    };
+                    values.map(|values| (id, values))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn json_value(value: Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        value => Err(format!("Unsupported value {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests;
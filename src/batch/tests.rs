@@ -0,0 +1,50 @@
+use super::{parse_csv, parse_json};
+
+fn row(values: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+    values
+        .iter()
+        .map(|(id, values)| {
+            (
+                id.to_string(),
+                values.iter().map(ToString::to_string).collect(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn csv_rows() {
+    let csv = "input, count ,verbose\r\na.txt,1,true\n\n\"b, \"\"c\"\".txt\",,false\n\"x\ny\",2,";
+    assert_eq!(
+        parse_csv(csv).unwrap(),
+        [
+            row(&[
+                ("input", &["a.txt"]),
+                ("count", &["1"]),
+                ("verbose", &["true"])
+            ]),
+            row(&[("input", &["b, \"c\".txt"]), ("verbose", &["false"])]),
+            row(&[("input", &["x", "y"]), ("count", &["2"])]),
+        ]
+    );
+    assert!(parse_csv("a,b\n1\n").is_err());
+    assert!(parse_csv("a\n\"1\n").is_err());
+}
+
+#[test]
+fn json_rows() {
+    let json = r#"[{"input": "a.txt", "count": 1, "verbose": true, "skip": null},
+                   {"input": ["b", "c"]}]"#;
+    assert_eq!(
+        parse_json(json).unwrap(),
+        [
+            row(&[
+                ("count", &["1"]),
+                ("input", &["a.txt"]),
+                ("verbose", &["true"])
+            ]),
+            row(&[("input", &["b", "c"])]),
+        ]
+    );
+    assert!(parse_json(r#"[{"input": {}}]"#).is_err());
+}
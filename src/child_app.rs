@@ -1,39 +1,116 @@
-use crate::{ExecutionError, CHILD_APP_ENV_VAR};
+use crate::{
+    session::{Session, Stream},
+    ExecutionError, CANCEL_FILE_ENV_VAR, CHILD_APP_ENV_VAR,
+};
 use eframe::egui;
+use serde::{Deserialize, Serialize};
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Read, Write},
-    path::PathBuf,
-    process::{Child, Command, Stdio},
-    sync::mpsc::{self, Receiver},
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc,
+    },
     thread,
+    time::{Duration, Instant},
 };
+use uuid::Uuid;
+
+#[cfg(feature = "inprocess-capture")]
+mod in_process;
+#[cfg(feature = "inprocess-capture")]
+pub use in_process::InProcess;
 
 #[derive(Debug)]
 pub struct ChildApp {
-    child: Child,
-    stdout: Option<Receiver<Option<String>>>,
-    stderr: Option<Receiver<Option<String>>>,
+    process: Process,
+    /// Kept open for [`ChildApp::write_stdin`], unless input was passed when starting
+    stdin: Option<ChildStdin>,
+    stdout: Option<Receiver<String>>,
+    stderr: Option<Receiver<String>>,
+    /// Created when the user asks the child to stop, see [`crate::is_cancelled`]
+    cancel_file: PathBuf,
+    started: Instant,
+    /// Log file and whether to keep ANSI escape codes in it
+    log: Option<(File, bool)>,
+    /// Dropped once the output ends, which stops the repaints, see [`ChildApp::spawn_ticker`]
+    ticker: Option<Sender<()>>,
+    /// None if it runs in the current directory, only needed for thumbnails of relative paths
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    working_dir: Option<PathBuf>,
+    /// Input passed when starting, written on a thread, see [`ChildApp::spawn_feeder`]
+    feeding: Option<Arc<Feeding>>,
+    /// Recorded while running, see [`ChildApp::record_session`]
+    session: Option<Session>,
+}
+
+/// Progress of writing the passed input to stdin
+#[derive(Debug, Default)]
+struct Feeding {
+    written: AtomicU64,
+    /// None if the size isn't known
+    total: Option<u64>,
+    done: AtomicBool,
+}
+
+/// Interval of repaints while the child is running, fast enough for the running indicator
+const TICK: Duration = Duration::from_millis(250);
+/// Longest time output is held back to be sent together with more output
+const BATCH_INTERVAL: Duration = Duration::from_millis(10);
+/// Largest batch of output, so huge bursts still show up gradually
+const BATCH_BYTES: usize = 64 * 1024;
+/// Size of a single read of output
+const READ_CHUNK: usize = 8 * 1024;
+/// Size of the chunks passed input is written to stdin in
+const FEED_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug)]
+enum Process {
+    /// The binary followed by the stages of the post-processing pipeline
+    Children(Vec<Child>),
+    /// Closure running on a thread in this process, see [`crate::run_app_in_process`]
+    #[cfg(feature = "inprocess-capture")]
+    Thread(in_process::Thread),
+    /// Output of a recorded session, see [`ChildApp::replay`]
+    Replay {
+        success: bool,
+        exit_code: Option<i32>,
+    },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum StdinType {
     File(String),
     Text(String),
 }
 
 impl ChildApp {
+    /// Runs `program`, or this binary again if it's None,
+    /// piping its stdout through each command of `post_process`
     pub fn run(
+        program: Option<&Path>,
         args: Vec<String>,
         env: Option<Vec<(String, String)>>,
         stdin: Option<StdinType>,
         working_dir: Option<String>,
+        post_process: &[Vec<String>],
         ctx: egui::Context,
     ) -> Result<Self, ExecutionError> {
-        let mut child = Command::new(std::env::current_exe()?);
+        let mut child = match program {
+            Some(program) => Command::new(program),
+            None => {
+                let mut child = Command::new(std::env::current_exe()?);
+                child.env(CHILD_APP_ENV_VAR, "");
+                child
+            }
+        };
+        let cancel_file = std::env::temp_dir().join(format!("klask-cancel-{}", Uuid::new_v4()));
 
         child
-            .env(CHILD_APP_ENV_VAR, "")
+            .env(CANCEL_FILE_ENV_VAR, &cancel_file)
             .args(args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -43,54 +120,192 @@ impl ChildApp {
             child.envs(env);
         }
 
-        if let Some(working_dir) = working_dir {
-            if !working_dir.is_empty() {
-                child.current_dir(PathBuf::from(working_dir).canonicalize()?);
+        let working_dir = match working_dir {
+            Some(working_dir) if !working_dir.is_empty() => {
+                Some(PathBuf::from(working_dir).canonicalize()?)
             }
+            _ => None,
+        };
+        if let Some(working_dir) = &working_dir {
+            child.current_dir(working_dir);
         }
 
         let mut child = child.spawn()?;
-
-        let stdout = Self::spawn_thread_reader(
+        let (stderr_tx, stderr) = mpsc::channel();
+        Self::spawn_thread_reader(
             child
-                .stdout
+                .stderr
                 .take()
                 .ok_or(ExecutionError::NoStdoutOrStderr)?,
+            stderr_tx.clone(),
             ctx.clone(),
         );
 
-        let stderr = Self::spawn_thread_reader(
-            child
-                .stderr
-                .take()
-                .ok_or(ExecutionError::NoStdoutOrStderr)?,
-            ctx,
-        );
+        let mut last_stdout = child
+            .stdout
+            .take()
+            .ok_or(ExecutionError::NoStdoutOrStderr)?;
+        let mut stages = Vec::with_capacity(post_process.len());
+        for command in post_process {
+            let (program, args) = command.split_first().ok_or(ExecutionError::EmptyCommand)?;
+            let mut stage = Command::new(program);
+            stage
+                .args(args)
+                .stdin(last_stdout)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            if let Some(working_dir) = &working_dir {
+                stage.current_dir(working_dir);
+            }
 
-        if let Some(stdin) = stdin {
-            let mut child_stdin = child.stdin.take().unwrap();
-            match stdin {
-                StdinType::Text(text) => {
-                    child_stdin.write_all(text.as_bytes())?;
+            let mut stage = match stage.spawn() {
+                Ok(stage) => stage,
+                Err(err) => {
+                    drop(child.kill());
+                    stages.iter_mut().for_each(|s: &mut Child| drop(s.kill()));
+                    return Err(err.into());
                 }
-                StdinType::File(path) => {
-                    let mut file = File::open(path)?;
-                    std::io::copy(&mut file, &mut child_stdin)?;
-                }
-            }
+            };
+            Self::spawn_thread_reader(
+                stage
+                    .stderr
+                    .take()
+                    .ok_or(ExecutionError::NoStdoutOrStderr)?,
+                stderr_tx.clone(),
+                ctx.clone(),
+            );
+            last_stdout = stage
+                .stdout
+                .take()
+                .ok_or(ExecutionError::NoStdoutOrStderr)?;
+            stages.push(stage);
         }
+        drop(stderr_tx);
+
+        let (stdout_tx, stdout) = mpsc::channel();
+        Self::spawn_thread_reader(last_stdout, stdout_tx, ctx.clone());
+
+        // Passed input is all the input, so stdin is closed after it. It's written on a thread,
+        // so large files don't freeze the GUI and output shows up while they're read.
+        let mut child_stdin = child.stdin.take();
+        let input: Option<(Box<dyn Read + Send>, Option<u64>)> = match stdin {
+            Some(StdinType::Text(text)) if !text.is_empty() => {
+                let total = text.len() as u64;
+                Some((
+                    Box::new(std::io::Cursor::new(text.into_bytes())),
+                    Some(total),
+                ))
+            }
+            Some(StdinType::File(path)) => {
+                let file = File::open(path)?;
+                let total = file.metadata().ok().map(|m| m.len()).filter(|&len| len > 0);
+                Some((Box::new(file), total))
+            }
+            _ => None,
+        };
+        let feeding = input.map(|(input, total)| {
+            Self::spawn_feeder(input, total, child_stdin.take().unwrap(), ctx.clone())
+        });
 
+        stages.insert(0, child);
         Ok(Self {
-            child,
+            process: Process::Children(stages),
+            stdin: child_stdin,
             stdout: Some(stdout),
             stderr: Some(stderr),
+            cancel_file,
+            started: Instant::now(),
+            log: None,
+            ticker: Some(Self::spawn_ticker(ctx)),
+            working_dir,
+            feeding,
+            session: None,
         })
     }
 
-    pub fn read(&mut self) -> String {
-        let mut out = String::new();
+    /// Plays back the output of a recorded session, with the timestamps divided by `speed`.
+    /// Lines sent to stdin are shown as they were echoed.
+    pub fn replay(session: Session, speed: f32, ctx: egui::Context) -> Self {
+        let (stdout_tx, stdout) = mpsc::channel();
+        let (stderr_tx, stderr) = mpsc::channel();
+        let process = Process::Replay {
+            success: session.success,
+            exit_code: session.exit_code,
+        };
+
+        let repaint = ctx.clone();
+        thread::spawn(move || {
+            let started = Instant::now();
+            for event in session.events {
+                if speed.is_finite() {
+                    let at = Duration::from_secs_f64(event.at / f64::from(speed));
+                    thread::sleep(at.saturating_sub(started.elapsed()));
+                }
+                let tx = match event.stream {
+                    Stream::Stderr => &stderr_tx,
+                    Stream::Stdin | Stream::Stdout => &stdout_tx,
+                };
+                // Stopped replaying
+                if tx.send(event.text).is_err() {
+                    return;
+                }
+                repaint.request_repaint();
+            }
+            repaint.request_repaint();
+        });
+
+        Self {
+            process,
+            stdin: None,
+            stdout: Some(stdout),
+            stderr: Some(stderr),
+            cancel_file: std::env::temp_dir().join(format!("klask-cancel-{}", Uuid::new_v4())),
+            started: Instant::now(),
+            log: None,
+            ticker: Some(Self::spawn_ticker(ctx)),
+            working_dir: None,
+            feeding: None,
+            session: None,
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        matches!(self.process, Process::Replay { .. })
+    }
+
+    /// Records the input and output with their timestamps from now on
+    pub fn record_session(&mut self, args: Vec<String>) {
+        self.session = Some(Session::new(args));
+    }
+
+    /// The recorded session of a finished run, with how it exited
+    pub fn take_session(&mut self, success: bool, exit_code: Option<i32>) -> Option<Session> {
+        let mut session = self.session.take()?;
+        session.success = success;
+        session.exit_code = exit_code;
+        Some(session)
+    }
+
+    /// Output received since the last read, in the chunks it arrived in
+    pub fn read(&mut self) -> Vec<String> {
+        let mut out = vec![];
         Self::read_stdio(&mut out, &mut self.stdout);
+        let stdout_chunks = out.len();
         Self::read_stdio(&mut out, &mut self.stderr);
+        if let Some(session) = &mut self.session {
+            let at = self.started.elapsed();
+            for (index, chunk) in out.iter().enumerate() {
+                let stream = if index < stdout_chunks {
+                    Stream::Stdout
+                } else {
+                    Stream::Stderr
+                };
+                session.push(at, stream, chunk);
+            }
+        }
+        if !self.is_running() {
+            self.ticker = None;
+        }
         out
     }
 
@@ -98,43 +313,233 @@ impl ChildApp {
         self.stdout.is_some() || self.stderr.is_some()
     }
 
+    #[cfg(feature = "images")]
+    pub fn working_dir(&self) -> Option<&Path> {
+        self.working_dir.as_deref()
+    }
+
+    pub fn has_stdin(&self) -> bool {
+        self.stdin.is_some()
+    }
+
+    /// Bytes of the passed input written so far, and the size of all of it if it's known.
+    /// None once it's all written, or if the child stopped reading it.
+    pub fn feeding_input(&self) -> Option<(u64, Option<u64>)> {
+        let feeding = self.feeding.as_ref()?;
+        if feeding.done.load(Ordering::Relaxed) || !self.is_running() {
+            return None;
+        }
+        Some((feeding.written.load(Ordering::Relaxed), feeding.total))
+    }
+
+    /// Writes to the open stdin of the child. It's closed if the child stopped reading.
+    pub fn write_stdin(&mut self, text: &str) {
+        if let Some(stdin) = &mut self.stdin {
+            if stdin.write_all(text.as_bytes()).is_err() {
+                self.stdin = None;
+            } else if let Some(session) = &mut self.session {
+                session.push(self.started.elapsed(), Stream::Stdin, text);
+            }
+        }
+    }
+
+    /// Closes stdin, so the child reads the end of the input
+    pub fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Asks the child to stop gracefully
+    pub fn cancel(&mut self) {
+        drop(File::create(&self.cancel_file));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_file.exists()
+    }
+
+    /// Returns whether the child succeeded once it has exited
+    pub fn try_exit_status(&mut self) -> Option<bool> {
+        match &mut self.process {
+            Process::Children(children) => {
+                let mut success = true;
+                for child in children {
+                    success &= child.try_wait().ok().flatten()?.success();
+                }
+                Some(success)
+            }
+            #[cfg(feature = "inprocess-capture")]
+            Process::Thread(thread) => thread.try_success(),
+            Process::Replay { success, .. } => Some(*success),
+        }
+    }
+
+    /// Writes everything passed to [`ChildApp::log`] to a new file at `path`
+    pub fn log_to(&mut self, path: &Path, keep_ansi: bool) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.log = Some((File::create(path)?, keep_ansi));
+        Ok(())
+    }
+
+    pub fn log(&mut self, text: &str) {
+        if let Some((file, keep_ansi)) = &mut self.log {
+            let result = if *keep_ansi {
+                file.write_all(text.as_bytes())
+            } else {
+                cansi::v3::categorise_text(text)
+                    .iter()
+                    .try_for_each(|slice| file.write_all(slice.text.as_bytes()))
+            };
+            // Stop logging instead of failing the run
+            if result.is_err() {
+                self.log = None;
+            }
+        }
+    }
+
+    /// Exit code of the binary, None for in-process runs or if it was killed by a signal
+    pub fn exit_code(&mut self) -> Option<i32> {
+        match &mut self.process {
+            Process::Children(children) => children[0].try_wait().ok().flatten()?.code(),
+            #[cfg(feature = "inprocess-capture")]
+            Process::Thread(_) => None,
+            Process::Replay { exit_code, .. } => *exit_code,
+        }
+    }
+
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
     pub fn kill(&mut self) {
-        drop(self.child.kill());
+        match &mut self.process {
+            Process::Children(children) => children.iter_mut().for_each(|c| drop(c.kill())),
+            // Threads can't be killed, so only ask it to stop and stop showing its output
+            #[cfg(feature = "inprocess-capture")]
+            Process::Thread(_) => self.cancel(),
+            // Dropping the output stops the replay
+            Process::Replay { .. } => {}
+        }
         self.stdout = None;
         self.stderr = None;
+        self.ticker = None;
     }
 
-    fn spawn_thread_reader<R: Read + Send + Sync + 'static>(
-        stdio: R,
-        ctx: egui::Context,
-    ) -> Receiver<Option<String>> {
-        let mut reader = BufReader::new(stdio);
+    /// Repaints every [`TICK`] until the returned sender is dropped. Otherwise
+    /// the GUI is only repainted when output arrives or the user interacts with it.
+    fn spawn_ticker(ctx: egui::Context) -> Sender<()> {
         let (tx, rx) = mpsc::channel();
-        thread::spawn(move || loop {
-            let mut output = String::new();
-            if let Ok(0) = reader.read_line(&mut output) {
-                // End of output
-                drop(tx.send(None));
+        thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(TICK) {
                 ctx.request_repaint();
-                break;
             }
-            // Send returns error only if data will never be received
-            if tx.send(Some(output)).is_err() {
-                break;
+        });
+        tx
+    }
+
+    /// Writes `input` to `stdin` in chunks, closing it at the end. Writes block while
+    /// the pipe is full, so the input is only read as fast as the child reads it.
+    fn spawn_feeder(
+        mut input: Box<dyn Read + Send>,
+        total: Option<u64>,
+        mut stdin: ChildStdin,
+        ctx: egui::Context,
+    ) -> Arc<Feeding> {
+        let feeding = Arc::new(Feeding {
+            total,
+            ..Feeding::default()
+        });
+        let progress = feeding.clone();
+        thread::spawn(move || {
+            let mut buffer = vec![0; FEED_CHUNK];
+            loop {
+                let read = match input.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => read,
+                };
+                // The child stopped reading, or was killed
+                if stdin.write_all(&buffer[..read]).is_err() {
+                    break;
+                }
+                progress.written.fetch_add(read as u64, Ordering::Relaxed);
             }
+            progress.done.store(true, Ordering::Relaxed);
             ctx.request_repaint();
         });
-        rx
+        feeding
+    }
+
+    /// Sends lines read from `stdio` to `tx`. The channel disconnects
+    /// once every reader sending to it reaches the end of its output.
+    ///
+    /// Output is sent as soon as the child stops writing, even in the middle of a line like a
+    /// prompt. While reads keep filling the buffer, output is batched for up to [`BATCH_INTERVAL`]
+    /// or [`BATCH_BYTES`], so fast children don't send a message and repaint for every line.
+    fn spawn_thread_reader<R: Read + Send + Sync + 'static>(
+        mut stdio: R,
+        tx: Sender<String>,
+        ctx: egui::Context,
+    ) {
+        thread::spawn(move || {
+            let mut buffer = [0; READ_CHUNK];
+            // Read bytes that aren't valid text yet, like the start of a split character
+            let mut bytes = vec![];
+            let mut batch = String::new();
+            let mut started = Instant::now();
+            let mut repainted = Instant::now();
+            loop {
+                let read = match stdio.read(&mut buffer) {
+                    Ok(read) => read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => 0,
+                };
+                let end = read == 0;
+                bytes.extend_from_slice(&buffer[..read]);
+                let valid = match std::str::from_utf8(&bytes) {
+                    Err(err) if !end && err.error_len().is_none() => err.valid_up_to(),
+                    _ => bytes.len(),
+                };
+                batch.push_str(&String::from_utf8_lossy(&bytes[..valid]));
+                bytes.drain(..valid);
+
+                // A read that doesn't fill the buffer took everything the child wrote so far
+                let idle = read < buffer.len();
+                if !end && !idle && batch.len() < BATCH_BYTES && started.elapsed() < BATCH_INTERVAL
+                {
+                    continue;
+                }
+
+                // Send returns error only if data will never be received
+                if !batch.is_empty() && tx.send(std::mem::take(&mut batch)).is_err() {
+                    break;
+                }
+                started = Instant::now();
+
+                if end {
+                    drop(tx);
+                    ctx.request_repaint();
+                    break;
+                }
+                // Output that arrives quickly is shown by a later repaint
+                if idle || repainted.elapsed() >= BATCH_INTERVAL {
+                    ctx.request_repaint();
+                    repainted = Instant::now();
+                }
+            }
+        });
     }
 
-    fn read_stdio(output: &mut String, stdio: &mut Option<Receiver<Option<String>>>) {
+    fn read_stdio(output: &mut Vec<String>, stdio: &mut Option<Receiver<String>>) {
         if let Some(receiver) = stdio {
-            for line in receiver.try_iter() {
-                if let Some(line) = line {
-                    output.push_str(&line);
-                } else {
-                    *stdio = None;
-                    return;
+            loop {
+                match receiver.try_recv() {
+                    Ok(chunk) => output.push(chunk),
+                    Err(TryRecvError::Empty) => return,
+                    Err(TryRecvError::Disconnected) => {
+                        *stdio = None;
+                        return;
+                    }
                 }
             }
         }
@@ -144,5 +549,6 @@ impl ChildApp {
 impl Drop for ChildApp {
     fn drop(&mut self) {
         self.kill();
+        drop(fs::remove_file(&self.cancel_file));
     }
 }
@@ -0,0 +1,169 @@
+use super::{ChildApp, Process};
+use crate::{ExecutionError, CANCEL_FILE_ENV_VAR};
+use clap::{ArgMatches, Command};
+use eframe::egui;
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Debug},
+    io::{self, Write},
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+use uuid::Uuid;
+
+/// The closure passed to [`crate::run_app_in_process`]
+#[derive(Clone)]
+pub struct InProcess {
+    pub run: Arc<dyn Fn(&ArgMatches) + Send + Sync>,
+    /// Called when the GUI closes, see [`crate::KlaskApp::exit`]
+    pub exit: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Working directory when the GUI started, relative working directories resolve against it
+    pub startup_dir: PathBuf,
+}
+
+impl Debug for InProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("InProcess")
+    }
+}
+
+#[derive(Debug)]
+pub struct Thread {
+    handle: Option<JoinHandle<bool>>,
+    success: Option<bool>,
+}
+
+impl Thread {
+    /// Returns whether the closure finished without panicking once it has finished
+    pub fn try_success(&mut self) -> Option<bool> {
+        if self.handle.as_ref().is_some_and(JoinHandle::is_finished) {
+            let handle = self.handle.take().unwrap();
+            self.success = Some(handle.join().unwrap_or(false));
+        }
+        self.success
+    }
+}
+
+/// Environment variables and working directory of the process from before a run,
+/// put back when dropped, so they don't leak into the GUI or later runs.
+///
+/// They're changed while the GUI's threads keep running. Reading the environment
+/// on another thread at the same time, even through libc, is a data race on some
+/// platforms like glibc, which is a documented limitation of in-process runs.
+#[derive(Debug, Default)]
+struct ProcessState {
+    env: Vec<(String, Option<OsString>)>,
+    working_dir: Option<PathBuf>,
+}
+
+impl ProcessState {
+    fn set_var(&mut self, key: String, value: impl AsRef<OsStr>) {
+        if !self.env.iter().any(|(saved, _)| *saved == key) {
+            let previous = std::env::var_os(&key);
+            self.env.push((key.clone(), previous));
+        }
+        std::env::set_var(key, value);
+    }
+
+    fn set_current_dir(&mut self, dir: &Path) -> io::Result<()> {
+        if self.working_dir.is_none() {
+            self.working_dir = Some(std::env::current_dir()?);
+        }
+        std::env::set_current_dir(dir)
+    }
+}
+
+impl Drop for ProcessState {
+    fn drop(&mut self) {
+        for (key, previous) in self.env.drain(..).rev() {
+            match previous {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+        if let Some(dir) = self.working_dir.take() {
+            drop(std::env::set_current_dir(dir));
+        }
+    }
+}
+
+impl ChildApp {
+    /// Runs the closure on a thread, capturing stdout and stderr of the whole process
+    /// while it runs. Environment variables and the working directory are also set
+    /// for the whole process, and put back once it finishes.
+    pub fn run_in_process(
+        app: &Command<'static>,
+        args: Vec<String>,
+        f: &InProcess,
+        env: Option<Vec<(String, String)>>,
+        working_dir: Option<String>,
+        ctx: egui::Context,
+    ) -> Result<Self, ExecutionError> {
+        let matches = app.clone().try_get_matches_from(args)?;
+
+        // Dropped on errors below, or on the thread once the closure finishes
+        let mut state = ProcessState::default();
+
+        let cancel_file = std::env::temp_dir().join(format!("klask-cancel-{}", Uuid::new_v4()));
+        state.set_var(CANCEL_FILE_ENV_VAR.to_string(), &cancel_file);
+
+        for (key, value) in env.unwrap_or_default() {
+            state.set_var(key, value);
+        }
+
+        let working_dir = match working_dir {
+            Some(working_dir) if !working_dir.is_empty() => {
+                let dir = f.startup_dir.join(working_dir).canonicalize()?;
+                state.set_current_dir(&dir)?;
+                Some(dir)
+            }
+            _ => None,
+        };
+
+        let (stdout_reader, stdout_writer) = os_pipe::pipe()?;
+        let (stderr_reader, stderr_writer) = os_pipe::pipe()?;
+
+        // Don't capture anything printed earlier
+        io::stdout().flush()?;
+        let stdout_redirect = gag::Redirect::stdout(stdout_writer).map_err(io::Error::from)?;
+        let stderr_redirect = gag::Redirect::stderr(stderr_writer).map_err(io::Error::from)?;
+
+        let f = f.run.clone();
+        let handle = thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| f(&matches)));
+            drop(io::stdout().flush());
+            // Restoring stdout and stderr closes the pipes, which ends the output
+            drop((stdout_redirect, stderr_redirect));
+            drop(state);
+            result.is_ok()
+        });
+
+        let (stdout_tx, stdout) = mpsc::channel();
+        let (stderr_tx, stderr) = mpsc::channel();
+        Self::spawn_thread_reader(stdout_reader, stdout_tx, ctx.clone());
+        Self::spawn_thread_reader(stderr_reader, stderr_tx, ctx.clone());
+
+        Ok(Self {
+            process: Process::Thread(Thread {
+                handle: Some(handle),
+                success: None,
+            }),
+            stdin: None,
+            stdout: Some(stdout),
+            stderr: Some(stderr),
+            cancel_file,
+            started: Instant::now(),
+            log: None,
+            ticker: Some(Self::spawn_ticker(ctx)),
+            working_dir,
+            feeding: None,
+            session: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,82 @@
+use super::InProcess;
+use crate::child_app::ChildApp;
+use clap::Command;
+use eframe::egui;
+use std::{
+    process,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Set in the test process started by [`isolated`]
+const ISOLATED_ENV_VAR: &str = "KLASK_ISOLATED_TEST";
+
+/// Runs the test `name` alone in a new process and returns true, unless this is that process.
+/// In-process runs change the working directory and redirect the output of the whole
+/// process, which would break tests running in parallel.
+fn isolated(name: &str) -> bool {
+    if std::env::var_os(ISOLATED_ENV_VAR).is_some() {
+        return false;
+    }
+    let output = process::Command::new(std::env::current_exe().unwrap())
+        .args([name, "--exact", "--test-threads=1"])
+        .env(ISOLATED_ENV_VAR, "1")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    true
+}
+
+#[test]
+fn relative_working_dir_twice() {
+    if isolated("child_app::in_process::tests::relative_working_dir_twice") {
+        return;
+    }
+
+    let startup_dir =
+        std::env::temp_dir().join(format!("klask-in-process-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(startup_dir.join("sub")).unwrap();
+    let before = std::env::current_dir().unwrap();
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let seen_in_run = seen.clone();
+    let f = InProcess {
+        run: Arc::new(move |_| {
+            let dir = std::env::current_dir().unwrap();
+            let var = std::env::var("KLASK_IN_PROCESS_TEST").ok();
+            seen_in_run.lock().unwrap().push((dir, var));
+        }),
+        exit: None,
+        startup_dir: startup_dir.clone(),
+    };
+
+    let sub = startup_dir.join("sub").canonicalize().unwrap();
+    let app = Command::new("test");
+    for _ in 0..2 {
+        let mut child = ChildApp::run_in_process(
+            &app,
+            vec!["test".into()],
+            &f,
+            Some(vec![("KLASK_IN_PROCESS_TEST".into(), "1".into())]),
+            Some("sub".into()),
+            egui::Context::default(),
+        )
+        .unwrap();
+        assert_eq!(child.working_dir.as_deref(), Some(&*sub));
+        while child.try_exit_status().is_none() {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(child.try_exit_status(), Some(true));
+        assert_eq!(std::env::current_dir().unwrap(), before);
+        assert!(std::env::var_os("KLASK_IN_PROCESS_TEST").is_none());
+    }
+
+    let expected = (sub, Some("1".to_string()));
+    assert_eq!(*seen.lock().unwrap(), [expected.clone(), expected]);
+    std::fs::remove_dir_all(startup_dir).unwrap();
+}
@@ -0,0 +1,152 @@
+use crate::Localization;
+use eframe::egui::{Button, Color32, TextEdit, Ui};
+use rfd::FileDialog;
+use std::{fs, path::Path};
+
+/// State of the tab for editing the file passed to [`crate::Settings::config_arg`].
+#[derive(Debug, Default)]
+pub struct ConfigFile {
+    /// Path the text was loaded from or last saved to
+    loaded: Option<String>,
+    text: String,
+    /// The text when it was loaded or last saved, anything else are unsaved edits
+    saved_text: String,
+    /// Error from the last load or save
+    error: Option<String>,
+    /// Error from checking the syntax of `.toml` and `.json` files
+    syntax_error: Option<String>,
+}
+
+impl ConfigFile {
+    /// Shows the editor for the file at `path`, which is the value of the config argument
+    pub fn show(&mut self, ui: &mut Ui, path: &mut String, localization: &Localization) {
+        ui.horizontal(|ui| {
+            if ui.button(&localization.select_file).clicked() {
+                if let Some(file) = FileDialog::new().pick_file() {
+                    *path = file.to_string_lossy().into_owned();
+                }
+            }
+            ui.text_edit_singleline(path);
+        });
+
+        // Load new files right away, but never overwrite edits without asking
+        let previous = self.follow_path(path);
+        if let Some(previous) = &previous {
+            ui.colored_label(Color32::GOLD, &localization.unsaved_config_edits);
+            ui.horizontal(|ui| {
+                if !previous.is_empty()
+                    && ui
+                        .button(localization.save_to_previous.format(&[("path", previous)]))
+                        .clicked()
+                {
+                    self.save(previous);
+                }
+                if ui.button(&localization.discard_edits).clicked() {
+                    self.open(path);
+                }
+                if ui.button(&localization.keep_edits).clicked() {
+                    self.loaded = Some(path.clone());
+                }
+            });
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!path.is_empty(), Button::new(&localization.load))
+                .clicked()
+            {
+                self.load(path);
+            }
+            if ui
+                .add_enabled(
+                    !path.is_empty() && previous.is_none(),
+                    Button::new(&localization.save),
+                )
+                .clicked()
+            {
+                self.save(path);
+            }
+        });
+
+        if let Some(error) = self.error.as_ref().or(self.syntax_error.as_ref()) {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        let response = ui.add(
+            TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_width(f32::INFINITY),
+        );
+        if response.changed() {
+            self.syntax_error = check_syntax(path, &self.text);
+        }
+    }
+
+    /// Opens the file once the path changes. Returns the previous path instead
+    /// if that would lose edits, so the user can be asked what to do with them.
+    fn follow_path(&mut self, path: &str) -> Option<String> {
+        match &self.loaded {
+            Some(loaded) if loaded == path => None,
+            Some(loaded) if self.text != self.saved_text => Some(loaded.clone()),
+            _ => {
+                self.open(path);
+                None
+            }
+        }
+    }
+
+    /// Loads the file, or starts a new one if it doesn't exist yet
+    fn open(&mut self, path: &str) {
+        if !path.is_empty() && Path::new(path).is_file() {
+            self.load(path);
+        } else {
+            self.text.clear();
+            self.saved_text.clear();
+            self.error = None;
+            self.syntax_error = None;
+            self.loaded = Some(path.to_string());
+        }
+    }
+
+    fn load(&mut self, path: &str) {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                self.syntax_error = check_syntax(path, &text);
+                self.text = text;
+                self.error = None;
+            }
+            Err(err) => {
+                self.text.clear();
+                self.syntax_error = None;
+                self.error = Some(err.to_string());
+            }
+        }
+        self.saved_text = self.text.clone();
+        self.loaded = Some(path.to_string());
+    }
+
+    fn save(&mut self, path: &str) {
+        match fs::write(path, &self.text) {
+            Ok(()) => {
+                self.error = None;
+                self.saved_text = self.text.clone();
+                self.loaded = Some(path.to_string());
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+}
+
+/// Checks `.toml` and `.json` files, other files are never reported as invalid
+fn check_syntax(path: &str, text: &str) -> Option<String> {
+    match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("toml") => text.parse::<toml::Value>().err().map(|e| e.to_string()),
+        Some("json") => serde_json::from_str::<serde_json::Value>(text)
+            .err()
+            .map(|e| e.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests;
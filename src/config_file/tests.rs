@@ -0,0 +1,40 @@
+use super::ConfigFile;
+use std::fs;
+
+#[test]
+fn path_change_keeps_edits() {
+    let dir = std::env::temp_dir().join(format!("klask-config-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    let first = dir.join("first.toml").to_string_lossy().into_owned();
+    let second = dir.join("second.toml").to_string_lossy().into_owned();
+    fs::write(&first, "a = 1").unwrap();
+    fs::write(&second, "b = 2").unwrap();
+
+    let mut config = ConfigFile::default();
+    assert_eq!(config.follow_path(&first), None);
+    assert_eq!(config.text, "a = 1");
+
+    // Unedited text follows the path
+    assert_eq!(config.follow_path(&second), None);
+    assert_eq!(config.text, "b = 2");
+
+    // Edits stay until the user decides what to do with them
+    config.text.push_str("\nc = 3");
+    assert_eq!(config.follow_path(&first), Some(second.clone()));
+    assert_eq!(config.follow_path(&first), Some(second.clone()));
+    assert_eq!(config.text, "b = 2\nc = 3");
+    assert_eq!(fs::read_to_string(&first).unwrap(), "a = 1");
+
+    // Saving them to the previous file lets the new one load
+    config.save(&second);
+    assert_eq!(config.follow_path(&first), None);
+    assert_eq!(config.text, "a = 1");
+    assert_eq!(fs::read_to_string(&second).unwrap(), "b = 2\nc = 3");
+
+    // A path that doesn't exist yet starts empty
+    let new = dir.join("new.toml").to_string_lossy().into_owned();
+    assert_eq!(config.follow_path(&new), None);
+    assert_eq!(config.text, "");
+
+    fs::remove_dir_all(dir).unwrap();
+}
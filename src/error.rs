@@ -10,6 +10,8 @@ pub enum ExecutionError {
     MatchError(clap::Error),
     #[error("Internal error: no child stdout or stderr")]
     NoStdoutOrStderr,
+    #[error("Post-processing command is empty")]
+    EmptyCommand,
     #[error("Validation error in {}: '{}'", .name, .message)]
     ValidationError { name: String, message: String },
     #[error("{0}")]
@@ -0,0 +1,54 @@
+use clap::{AppSettings, Command};
+use clap_complete::Shell;
+use rfd::{FileDialog, MessageDialog, MessageLevel};
+use std::{fs::File, io, path::Path};
+
+/// Shells that completions can be exported for
+pub const SHELLS: [Shell; 4] = [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell];
+
+/// Asks for a file and writes completions for `shell` to it. Errors are shown in a dialog.
+pub fn export_completions(app: &Command, shell: Shell) {
+    let name = bin_name(app);
+    let file_name = match shell {
+        Shell::Bash => format!("{}.bash", name),
+        Shell::Zsh => format!("_{}", name),
+        Shell::Fish => format!("{}.fish", name),
+        Shell::PowerShell => format!("_{}.ps1", name),
+        _ => name.clone(),
+    };
+
+    save(&file_name, |path| {
+        let mut app = standalone(app);
+        clap_complete::generate(shell, &mut app, name, &mut File::create(path)?);
+        Ok(())
+    });
+}
+
+/// Asks for a file and writes a man page to it. Errors are shown in a dialog.
+pub fn export_man_page(app: &Command) {
+    save(&format!("{}.1", bin_name(app)), |path| {
+        clap_mangen::Man::new(standalone(app)).render(&mut File::create(path)?)
+    });
+}
+
+fn bin_name(app: &Command) -> String {
+    app.get_bin_name()
+        .unwrap_or_else(|| app.get_name())
+        .to_string()
+}
+
+/// Klask validates arguments without a binary name, but exported files need one
+fn standalone<'help>(app: &Command<'help>) -> Command<'help> {
+    app.clone().unset_setting(AppSettings::NoBinaryName)
+}
+
+fn save(file_name: &str, write: impl FnOnce(&Path) -> io::Result<()>) {
+    if let Some(path) = FileDialog::new().set_file_name(file_name).save_file() {
+        if let Err(err) = write(&path) {
+            MessageDialog::new()
+                .set_level(MessageLevel::Error)
+                .set_description(&err.to_string())
+                .show();
+        }
+    }
+}
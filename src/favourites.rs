@@ -0,0 +1,84 @@
+use crate::{Localization, RunSnapshot};
+use eframe::egui::{self, Button, Context};
+use std::{fs, path::PathBuf};
+
+/// Runs pinned to a bar at the top of the window, see [`crate::Settings::enable_favourites`].
+/// They are saved in the platform data directory.
+#[derive(Debug)]
+pub struct Favourites {
+    /// None if the platform has no data directory
+    path: Option<PathBuf>,
+    pinned: Vec<RunSnapshot>,
+}
+
+impl Favourites {
+    pub fn load(app_name: &str) -> Self {
+        let path = dirs::data_dir().map(|dir| dir.join(app_name).join("favourites.json"));
+        let pinned = path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default();
+        Self { path, pinned }
+    }
+
+    pub fn pin(&mut self, snapshot: RunSnapshot) {
+        if !self.pinned.contains(&snapshot) {
+            self.pinned.push(snapshot);
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                drop(fs::create_dir_all(dir));
+            }
+            if let Ok(json) = serde_json::to_vec_pretty(&self.pinned) {
+                drop(fs::write(path, json));
+            }
+        }
+    }
+
+    /// Shows a button for each pinned run, returns the clicked one
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        running: bool,
+        localization: &Localization,
+    ) -> Option<RunSnapshot> {
+        if self.pinned.is_empty() {
+            return None;
+        }
+
+        let mut clicked = None;
+        let mut unpin = None;
+        egui::TopBottomPanel::top("klask_favourites").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (index, snapshot) in self.pinned.iter().enumerate() {
+                    let label = if snapshot.args.is_empty() {
+                        localization.run.clone()
+                    } else {
+                        snapshot.args.join(" ")
+                    };
+                    let response = ui.add_enabled(!running, Button::new(label).small());
+                    if response.clicked() {
+                        clicked = Some(snapshot.clone());
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button(&localization.unpin).clicked() {
+                            unpin = Some(index);
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        });
+
+        if let Some(index) = unpin {
+            self.pinned.remove(index);
+            self.save();
+        }
+        clicked
+    }
+}
@@ -0,0 +1,94 @@
+use eframe::egui::{Id, Ui};
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+
+/// Options of the dialogs of an argument
+#[derive(Debug, Clone, Default)]
+pub struct DialogOptions {
+    /// See [`crate::Settings::dialog_bookmarks`]
+    pub bookmarks: Vec<PathBuf>,
+    /// Extensions of the files shown, see [`crate::Settings::file_filters`].
+    /// Empty shows every file.
+    pub extensions: Vec<String>,
+}
+
+/// Extensions of globs like `*.png` in the help of an argument
+pub fn extensions_in(help: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = vec![];
+    for word in help.split(|c: char| c.is_whitespace() || ",;()[]{}'\"`".contains(c)) {
+        let extension = match word.trim_end_matches(['.', ':']).strip_prefix("*.") {
+            Some(extension) => extension,
+            None => continue,
+        };
+        if !extension.is_empty()
+            && extension.chars().all(char::is_alphanumeric)
+            && !extensions.iter().any(|e| e == extension)
+        {
+            extensions.push(extension.to_string());
+        }
+    }
+    extensions
+}
+
+/// Key of the directory of the last file picked in any dialog
+const ANY: &str = "";
+
+fn last_dir_id(key: &str) -> Id {
+    Id::new(("klask_last_dir", key))
+}
+
+/// A dialog opening where the last file for `key` was picked, or where any file was
+/// last picked if nothing was picked for `key` yet
+fn new(ui: &Ui, key: &str) -> FileDialog {
+    let last_dir = ui
+        .data()
+        .get_temp::<PathBuf>(last_dir_id(key))
+        .or_else(|| ui.data().get_temp(last_dir_id(ANY)));
+    match last_dir {
+        Some(dir) => FileDialog::new().set_directory(dir),
+        None => FileDialog::new(),
+    }
+}
+
+/// Remembers the directory of a `picked` file or directory for the next dialogs of `key`
+fn remember(ui: &Ui, key: &str, picked: &Path) {
+    let dir = if picked.is_dir() {
+        Some(picked)
+    } else {
+        picked.parent()
+    };
+    if let Some(dir) = dir {
+        ui.data().insert_temp(last_dir_id(key), dir.to_path_buf());
+        ui.data().insert_temp(last_dir_id(ANY), dir.to_path_buf());
+    }
+}
+
+/// Picks a file or directory in a dialog opening in `dir`, or where the last one for `key`
+/// was picked. `key` is like the id of an argument, so each argument has its own directory.
+/// Only files with one of the `extensions` are shown, unless it's empty.
+pub fn pick(
+    ui: &Ui,
+    key: &str,
+    folder: bool,
+    dir: Option<&Path>,
+    extensions: &[String],
+) -> Option<String> {
+    let mut dialog = match dir {
+        Some(dir) => FileDialog::new().set_directory(dir),
+        None => new(ui, key),
+    };
+    if !folder && !extensions.is_empty() {
+        let extensions: Vec<&str> = extensions.iter().map(String::as_str).collect();
+        dialog = dialog.add_filter(&extensions.join(", "), &extensions);
+    }
+    let picked = if folder {
+        dialog.pick_folder()
+    } else {
+        dialog.pick_file()
+    }?;
+    remember(ui, key, &picked);
+    Some(picked.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests;
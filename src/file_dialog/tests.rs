@@ -0,0 +1,11 @@
+use super::extensions_in;
+
+#[test]
+fn extensions_in_help() {
+    assert_eq!(
+        extensions_in("Image to read (*.png, *.JPG or `*.jpg`), like photo.png"),
+        ["png", "JPG", "jpg"]
+    );
+    assert_eq!(extensions_in("Reads *.csv files."), ["csv"]);
+    assert!(extensions_in("Matches *.* and *.tar.gz").is_empty());
+}
@@ -0,0 +1,77 @@
+use crate::{app_state::AppState, arg_state::ArgOptions, Settings};
+use clap::Command;
+use std::sync::Arc;
+
+/// Values of the form for [`build_args`], the way they're entered in the GUI
+/// ```
+/// # use klask::FormValues;
+/// let values = FormValues::default()
+///     .subcommand("build")
+///     .arg("release", ["true"])
+///     .arg("features", ["gui", "tls"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormValues {
+    /// Names of the subcommands to select, outermost first. Without them
+    /// the first subcommand is selected, like in the GUI.
+    pub subcommands: Vec<String>,
+    /// Values by argument id, in this command or the selected subcommands.
+    /// Flags take "true" or "false", arguments that can be passed multiple times
+    /// take the count, and arguments taking several values take one string per value.
+    pub values: Vec<(String, Vec<String>)>,
+}
+
+impl FormValues {
+    /// Selects the next subcommand
+    pub fn subcommand(mut self, name: impl Into<String>) -> Self {
+        self.subcommands.push(name.into());
+        self
+    }
+
+    /// Sets the values of the argument with clap id `id`
+    pub fn arg<S: Into<String>>(
+        mut self,
+        id: impl Into<String>,
+        values: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.values.push((id.into(), values));
+        self
+    }
+}
+
+/// Returns the arguments klask runs the binary with for the values of the form, without
+/// the name of the binary. Nothing is shown, so it can be used for testing how a clap
+/// definition is filled in, or for reusing how values are quoted and joined.
+/// Errors are the ones shown in the GUI, like missing required values.
+/// ```
+/// # use clap::{Arg, Command};
+/// # use klask::{FormValues, Settings};
+/// let app = Command::new("convert")
+///     .arg(Arg::new("output").long("output").takes_value(true).require_equals(true))
+///     .arg(Arg::new("input").required(true).takes_value(true));
+/// let values = FormValues::default()
+///     .arg("output", ["out.png"])
+///     .arg("input", ["-in.png"]);
+///
+/// assert_eq!(
+///     klask::build_args(&app, &Settings::default(), &values).unwrap(),
+///     ["--output=out.png", "--", "-in.png"]
+/// );
+/// ```
+pub fn build_args(
+    app: &Command<'static>,
+    settings: &Settings,
+    values: &FormValues,
+) -> Result<Vec<String>, String> {
+    let localization = Arc::new(settings.localization.clone());
+    let mut state = AppState::new(app, localization, Arc::new(ArgOptions::new(settings)));
+    state.select(&values.subcommands)?;
+    for (id, values) in &values.values {
+        state.set_arg(id, values)?;
+    }
+    state.get_cmd_args(vec![])
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+use super::{build_args, FormValues};
+use crate::Settings;
+use clap::{FromArgMatches, IntoApp, Parser, Subcommand};
+
+#[derive(Debug, Parser, PartialEq, Eq)]
+struct Tool {
+    #[clap(short, long, parse(from_occurrences))]
+    verbose: i32,
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Debug, Subcommand, PartialEq, Eq)]
+enum Commands {
+    Check,
+    Build {
+        #[clap(long)]
+        release: bool,
+        #[clap(long, multiple_values = true)]
+        features: Vec<String>,
+        #[clap(long, default_value = "target")]
+        out_dir: String,
+    },
+}
+
+fn parse(args: &[String]) -> Tool {
+    let matches = Tool::command()
+        .try_get_matches_from(["tool".into()].iter().chain(args))
+        .unwrap();
+    Tool::from_arg_matches(&matches).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let values = FormValues::default()
+        .arg("verbose", ["2"])
+        .subcommand("build")
+        .arg("release", ["true"])
+        .arg("features", ["gui", "tls"]);
+    let args = build_args(&Tool::command(), &Settings::default(), &values).unwrap();
+    assert_eq!(
+        parse(&args),
+        Tool {
+            verbose: 2,
+            command: Commands::Build {
+                release: true,
+                features: vec!["gui".into(), "tls".into()],
+                out_dir: "target".into(),
+            },
+        }
+    );
+
+    // The first subcommand is selected, like in the GUI
+    let args = build_args(
+        &Tool::command(),
+        &Settings::default(),
+        &FormValues::default(),
+    );
+    assert_eq!(args.unwrap(), ["check"]);
+}
+
+#[test]
+fn settings_apply() {
+    let settings = Settings {
+        prefill_defaults: true,
+        ..Settings::default()
+    };
+    let values = FormValues::default().subcommand("build");
+    let args = build_args(&Tool::command(), &settings, &values).unwrap();
+    assert_eq!(args, ["build", "--out-dir", "target"]);
+}
+
+#[test]
+fn errors() {
+    let app = Tool::command();
+    let unknown = FormValues::default().subcommand("deploy");
+    assert_eq!(
+        build_args(&app, &Settings::default(), &unknown),
+        Err("Unknown subcommand 'deploy'".into())
+    );
+    let invalid = FormValues::default().arg("verbose", ["many"]);
+    assert!(build_args(&app, &Settings::default(), &invalid).is_err());
+}
@@ -0,0 +1,44 @@
+use clap::Command;
+
+/// Renders the long help of the subcommand at `path`, with ANSI colors
+/// similar to how clap colors it in a terminal.
+pub fn render(app: &Command, path: &[String]) -> String {
+    let mut app = app.clone();
+    app.build();
+
+    let mut command = app;
+    for name in path {
+        match command.find_subcommand(name) {
+            Some(subcommand) => command = subcommand.clone(),
+            None => break,
+        }
+    }
+
+    let mut help = vec![];
+    // Writing to a Vec can't fail
+    drop(command.write_long_help(&mut help));
+    colorize(&String::from_utf8_lossy(&help))
+}
+
+/// Headings are yellow, flags and values at the start of an entry are green
+fn colorize(help: &str) -> String {
+    let mut colored = String::new();
+    let mut in_usage = false;
+    for line in help.lines() {
+        let entry = line
+            .strip_prefix("    ")
+            .filter(|rest| !in_usage && !rest.is_empty() && !rest.starts_with(' '));
+
+        if !line.starts_with(' ') && line.ends_with(':') && line == line.to_uppercase() {
+            in_usage = line == "USAGE:";
+            colored.push_str(&format!("\x1b[33m{}\x1b[0m", line));
+        } else if let Some(entry) = entry {
+            let (name, description) = entry.split_at(entry.find("  ").unwrap_or(entry.len()));
+            colored.push_str(&format!("    \x1b[32m{}\x1b[0m{}", name, description));
+        } else {
+            colored.push_str(line);
+        }
+        colored.push('\n');
+    }
+    colored
+}
@@ -0,0 +1,240 @@
+use crate::Localization;
+use eframe::egui::{
+    pos2, text::LayoutJob, vec2, Align2, Color32, ComboBox, Sense, TextEdit, TextFormat, TextStyle,
+    Ui,
+};
+use std::ops::Range;
+
+/// Syntax of the text sent to stdin, chosen next to the editor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    #[default]
+    Plain,
+    Json,
+    Yaml,
+    Sql,
+}
+
+/// Kind of a highlighted piece of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Plain,
+    /// Keys of JSON objects and YAML mappings
+    Key,
+    String,
+    Number,
+    /// Like `true` and `null`
+    Literal,
+    Keyword,
+    Comment,
+}
+
+const SQL_KEYWORDS: &[&str] = &[
+    "add", "all", "alter", "and", "as", "asc", "between", "by", "case", "create", "delete", "desc",
+    "distinct", "drop", "else", "end", "exists", "from", "group", "having", "in", "index", "inner",
+    "insert", "into", "is", "join", "left", "like", "limit", "not", "null", "offset", "on", "or",
+    "order", "outer", "primary", "key", "right", "select", "set", "table", "then", "union",
+    "update", "values", "when", "where", "with",
+];
+
+impl Syntax {
+    const ALL: [Syntax; 4] = [Syntax::Plain, Syntax::Json, Syntax::Yaml, Syntax::Sql];
+
+    fn name(self, localization: &Localization) -> &str {
+        match self {
+            Syntax::Plain => &localization.plain_text,
+            Syntax::Json => "JSON",
+            Syntax::Yaml => "YAML",
+            Syntax::Sql => "SQL",
+        }
+    }
+
+    /// Drop-down for choosing the syntax
+    pub fn show(&mut self, ui: &mut Ui, localization: &Localization) {
+        ComboBox::from_id_source("klask_stdin_syntax")
+            .selected_text(self.name(localization))
+            .show_ui(ui, |ui| {
+                for syntax in Syntax::ALL {
+                    ui.selectable_value(self, syntax, syntax.name(localization));
+                }
+            })
+            .response
+            .on_hover_text(&localization.syntax_highlighting);
+    }
+}
+
+/// Splits `text` into consecutive pieces, which cover all of it
+pub fn tokens(text: &str, syntax: Syntax) -> Vec<(Range<usize>, Token)> {
+    let mut tokens: Vec<(Range<usize>, Token)> = vec![];
+    let mut push = |range: Range<usize>, token| match tokens.last_mut() {
+        Some((last, last_token)) if *last_token == token && last.end == range.start => {
+            last.end = range.end
+        }
+        _ => tokens.push((range, token)),
+    };
+    if syntax == Syntax::Plain {
+        push(0..text.len(), Token::Plain);
+        return tokens;
+    }
+
+    let bytes = text.as_bytes();
+    let line_end = |from: usize| text[from..].find('\n').map_or(text.len(), |i| from + i);
+    let mut i = 0;
+    while i < text.len() {
+        let c = text[i..].chars().next().unwrap();
+        let rest = &text[i..];
+        let previous = text[..i].chars().next_back();
+        let start_of_word = !previous.is_some_and(|p| p.is_alphanumeric() || p == '_');
+
+        let (end, token) = if c == '"' || (c == '\'' && syntax != Syntax::Json) {
+            let end = string_end(text, i, c, syntax != Syntax::Sql);
+            let after = text[end..].trim_start_matches([' ', '\t']);
+            let key = match syntax {
+                Syntax::Json => after.starts_with(':'),
+                _ => syntax == Syntax::Yaml && is_yaml_key_end(after),
+            };
+            (end, if key { Token::Key } else { Token::String })
+        } else if (syntax == Syntax::Yaml && c == '#' && previous.is_none_or(char::is_whitespace))
+            || (syntax == Syntax::Sql && rest.starts_with("--"))
+        {
+            (line_end(i), Token::Comment)
+        } else if syntax == Syntax::Sql && rest.starts_with("/*") {
+            let end = rest[2..].find("*/").map_or(text.len(), |e| i + 2 + e + 2);
+            (end, Token::Comment)
+        } else if syntax == Syntax::Yaml && start_of_word && yaml_key_len(text, i).is_some() {
+            (i + yaml_key_len(text, i).unwrap(), Token::Key)
+        } else if start_of_word
+            && (c.is_ascii_digit()
+                || (c == '-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)))
+        {
+            let len = rest[1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .map_or(rest.len(), |len| len + 1);
+            (i + len, Token::Number)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            let token = match syntax {
+                Syntax::Json if ["true", "false", "null"].contains(&word) => Token::Literal,
+                Syntax::Yaml
+                    if ["true", "false", "null", "yes", "no", "on", "off"]
+                        .contains(&word.to_lowercase().as_str()) =>
+                {
+                    Token::Literal
+                }
+                Syntax::Sql if SQL_KEYWORDS.contains(&word.to_lowercase().as_str()) => {
+                    Token::Keyword
+                }
+                _ => Token::Plain,
+            };
+            (i + len, token)
+        } else {
+            (i + c.len_utf8(), Token::Plain)
+        };
+        push(i..end, token);
+        i = end;
+    }
+    tokens
+}
+
+/// End of the string starting with the `quote` at `start`, after the closing quote
+fn string_end(text: &str, start: usize, quote: char, single_line: bool) -> usize {
+    let mut escaped = false;
+    for (offset, c) in text[start + 1..].char_indices() {
+        let end = start + 1 + offset;
+        match c {
+            '\n' if single_line => return end,
+            '\\' if !escaped && quote == '"' => escaped = true,
+            c if c == quote && !escaped => return end + c.len_utf8(),
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
+
+/// Whether the text after a YAML scalar makes it a key, like `: value`
+fn is_yaml_key_end(after: &str) -> bool {
+    after.starts_with(':') && after[1..].chars().next().is_none_or(char::is_whitespace)
+}
+
+/// Length of an unquoted YAML key starting at `start`, if it's the first thing on its line
+/// after the indentation and list dashes
+fn yaml_key_len(text: &str, start: usize) -> Option<usize> {
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let indentation = |c| c == ' ' || c == '-';
+    if text[start..].starts_with(indentation) || !text[line_start..start].chars().all(indentation) {
+        return None;
+    }
+    let line = &text[start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    line.char_indices()
+        .find(|&(i, c)| c == ':' && is_yaml_key_end(&line[i..]))
+        .map(|(i, _)| i)
+        .filter(|&i| i > 0 && !line[..i].contains(['#', '"', '\'']))
+}
+
+fn color(token: Token, ui: &Ui) -> Color32 {
+    let dark = ui.visuals().dark_mode;
+    let rgb = |dark_rgb: (u8, u8, u8), light_rgb: (u8, u8, u8)| {
+        let (r, g, b) = if dark { dark_rgb } else { light_rgb };
+        Color32::from_rgb(r, g, b)
+    };
+    match token {
+        Token::Plain => ui.visuals().text_color(),
+        Token::Key => rgb((156, 220, 254), (0, 16, 128)),
+        Token::String => rgb((206, 145, 120), (163, 21, 21)),
+        Token::Number => rgb((181, 206, 168), (9, 134, 88)),
+        Token::Literal | Token::Keyword => rgb((86, 156, 214), (0, 0, 255)),
+        Token::Comment => rgb((106, 153, 85), (0, 128, 0)),
+    }
+}
+
+/// Monospace editor with line numbers, highlighting the text with `syntax`
+pub fn editor(ui: &mut Ui, text: &mut String, syntax: Syntax) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let lines = text.split('\n').count();
+    let digits = lines.to_string().len().max(2);
+    let gutter_width = digits as f32 * ui.fonts().glyph_width(&font_id, '0');
+
+    let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+        let mut job = LayoutJob::default();
+        for (range, token) in tokens(text, syntax) {
+            let format = TextFormat::simple(font_id.clone(), color(token, ui));
+            job.append(&text[range], 0.0, format);
+        }
+        job.wrap.max_width = wrap_width;
+        ui.fonts().layout_job(job)
+    };
+
+    ui.horizontal_top(|ui| {
+        let (gutter, _) = ui.allocate_exact_size(vec2(gutter_width, 0.0), Sense::hover());
+        let output = TextEdit::multiline(text)
+            .code_editor()
+            .desired_width(f32::INFINITY)
+            .layouter(&mut layouter)
+            .show(ui);
+
+        // Numbers on the first row of every line, so wrapped lines keep one number
+        let mut number = 1;
+        let mut starts_line = true;
+        for row in &output.galley.rows {
+            if starts_line {
+                let y = output.text_draw_pos.y + row.rect.min.y;
+                ui.painter().text(
+                    pos2(gutter.right(), y),
+                    Align2::RIGHT_TOP,
+                    number.to_string(),
+                    font_id.clone(),
+                    ui.visuals().weak_text_color(),
+                );
+                number += 1;
+            }
+            starts_line = row.ends_with_newline;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,68 @@
+use super::{tokens, Syntax, Token};
+
+fn tokenized(text: &str, syntax: Syntax) -> Vec<(&str, Token)> {
+    let tokens = tokens(text, syntax);
+    assert_eq!(tokens.first().map_or(0, |t| t.0.start), 0);
+    assert_eq!(tokens.last().map_or(0, |t| t.0.end), text.len());
+    tokens
+        .into_iter()
+        .map(|(range, token)| (&text[range], token))
+        .filter(|(_, token)| *token != Token::Plain)
+        .collect()
+}
+
+#[test]
+fn json_tokens() {
+    assert_eq!(
+        tokenized(
+            r#"{"name": "a \"b\"", "n": -1.5e3, "ok": true}"#,
+            Syntax::Json
+        ),
+        [
+            (r#""name""#, Token::Key),
+            (r#""a \"b\"""#, Token::String),
+            (r#""n""#, Token::Key),
+            ("-1.5e3", Token::Number),
+            (r#""ok""#, Token::Key),
+            ("true", Token::Literal),
+        ]
+    );
+}
+
+#[test]
+fn yaml_tokens() {
+    let yaml = "# config\nname: klask # app\nitems:\n  - size: 12\n  - 'x': yes\nurl: http://a\n";
+    assert_eq!(
+        tokenized(yaml, Syntax::Yaml),
+        [
+            ("# config", Token::Comment),
+            ("name", Token::Key),
+            ("# app", Token::Comment),
+            ("items", Token::Key),
+            ("size", Token::Key),
+            ("12", Token::Number),
+            ("'x'", Token::Key),
+            ("yes", Token::Literal),
+            ("url", Token::Key),
+        ]
+    );
+}
+
+#[test]
+fn sql_tokens() {
+    assert_eq!(
+        tokenized(
+            "SELECT id2 FROM t -- all\nwhere s = 'it''s' /* x */",
+            Syntax::Sql
+        ),
+        [
+            ("SELECT", Token::Keyword),
+            ("FROM", Token::Keyword),
+            ("-- all", Token::Comment),
+            ("where", Token::Keyword),
+            ("'it''s'", Token::String),
+            ("/* x */", Token::Comment),
+        ]
+    );
+    assert_eq!(tokens("SELECT", Syntax::Plain), [(0..6, Token::Plain)]);
+}
@@ -0,0 +1,175 @@
+use crate::output::{AnsiPalette, Output, OutputOptions};
+use eframe::egui::{Button, ComboBox, RichText, Ui};
+use similar::{ChangeTag, TextDiff};
+use std::collections::VecDeque;
+
+/// Output of an earlier run, kept so it can be viewed after starting a new one
+#[derive(Debug)]
+pub struct Run {
+    pub args: Vec<String>,
+    pub output: Output,
+}
+
+/// Previous runs, see [`crate::Settings::output_history`]
+#[derive(Debug)]
+pub struct History {
+    runs: VecDeque<Run>,
+    limit: usize,
+    /// Index of the shown run, None shows the current one
+    selected: Option<usize>,
+    /// Indices of the compared runs, the current run has index `runs.len()`
+    compare: Option<(usize, usize)>,
+    diff: Option<Diff>,
+}
+
+/// Changed lines, recomputed when either compared output changes
+#[derive(Debug)]
+struct Diff {
+    /// Indices of the compared runs with the [`Output::generation`] of their output
+    key: [(usize, u64); 2],
+    changes: Vec<(ChangeTag, String)>,
+}
+
+impl History {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            runs: VecDeque::new(),
+            limit,
+            selected: None,
+            compare: None,
+            diff: None,
+        }
+    }
+
+    /// Keeps `run`, forgetting the oldest runs over the limit, and shows the current run again
+    pub fn push(&mut self, run: Run) {
+        self.selected = None;
+        self.compare = None;
+        self.diff = None;
+        if self.limit == 0 {
+            return;
+        }
+
+        self.runs.push_back(run);
+        while self.runs.len() > self.limit {
+            self.runs.pop_front();
+        }
+    }
+
+    /// Shows arrows for switching between runs and the selected run's output,
+    /// or the diff of two runs in compare mode. `args` are the arguments of the current run.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        current: &mut Output,
+        args: &[String],
+        options: &OutputOptions,
+    ) {
+        if self.runs.is_empty() {
+            current.show(ui, options);
+            return;
+        }
+
+        let localization = options.localization;
+        let position = self.selected.unwrap_or(self.runs.len());
+        ui.horizontal(|ui| {
+            if self.compare.is_none() {
+                if ui.add_enabled(position > 0, Button::new("⏴")).clicked() {
+                    self.selected = Some(position - 1);
+                }
+                if ui
+                    .add_enabled(position < self.runs.len(), Button::new("⏵"))
+                    .clicked()
+                {
+                    self.selected = (position + 1 < self.runs.len()).then(|| position + 1);
+                }
+
+                ui.label(localization.run_history.format(&[
+                    ("current", &(position + 1).to_string()),
+                    ("total", &(self.runs.len() + 1).to_string()),
+                ]))
+                .on_hover_text(self.args(position, args).join(" "));
+            }
+
+            let mut comparing = self.compare.is_some();
+            if ui
+                .toggle_value(&mut comparing, &localization.compare_runs)
+                .changed()
+            {
+                self.compare = comparing.then(|| (position.saturating_sub(1), position));
+                self.diff = None;
+            }
+        });
+
+        match self.compare {
+            Some((mut left, mut right)) => {
+                ui.horizontal(|ui| {
+                    self.run_combo(ui, "klask_compare_left", &mut left, args);
+                    self.run_combo(ui, "klask_compare_right", &mut right, args);
+                });
+                if self.compare != Some((left, right)) {
+                    self.compare = Some((left, right));
+                    self.diff = None;
+                }
+
+                let key = [left, right].map(|i| (i, self.output(i, current).generation()));
+                if self.diff.as_ref().map(|diff| diff.key) != Some(key) {
+                    let old = self.output(left, current).text();
+                    let new = self.output(right, current).text();
+                    let changes = TextDiff::from_lines(&old, &new)
+                        .iter_all_changes()
+                        .map(|change| (change.tag(), change.to_string_lossy().into_owned()))
+                        .collect();
+                    self.diff = Some(Diff { key, changes });
+                }
+                self.show_diff(ui, &options.palette);
+            }
+            None => {
+                match self.selected.and_then(|i| self.runs.get_mut(i)) {
+                    Some(run) => run.output.show(ui, options),
+                    None => current.show(ui, options),
+                };
+            }
+        }
+    }
+
+    fn args<'a>(&'a self, index: usize, current: &'a [String]) -> &'a [String] {
+        self.runs.get(index).map_or(current, |run| &run.args)
+    }
+
+    fn output<'a>(&'a self, index: usize, current: &'a Output) -> &'a Output {
+        self.runs.get(index).map_or(current, |run| &run.output)
+    }
+
+    fn run_combo(&self, ui: &mut Ui, id: &str, index: &mut usize, current: &[String]) {
+        let label = |i: usize| format!("{}: {}", i + 1, self.args(i, current).join(" "));
+        ComboBox::from_id_source(id)
+            .selected_text(label(*index))
+            .show_ui(ui, |ui| {
+                for i in 0..=self.runs.len() {
+                    ui.selectable_value(index, i, label(i));
+                }
+            });
+    }
+
+    /// Shows a unified diff of the lines of the compared runs
+    fn show_diff(&self, ui: &mut Ui, palette: &AnsiPalette) {
+        let changes = &self.diff.as_ref().unwrap().changes;
+        ui.vertical(|ui| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+            for (tag, line) in changes {
+                let line = line.trim_end_matches('\n');
+                let text = match tag {
+                    ChangeTag::Equal => RichText::new(format!("  {}", line)),
+                    ChangeTag::Delete => {
+                        RichText::new(format!("- {}", line)).color(palette.failure())
+                    }
+                    ChangeTag::Insert => {
+                        RichText::new(format!("+ {}", line)).color(palette.success())
+                    }
+                };
+                ui.label(text.monospace());
+            }
+        });
+    }
+}
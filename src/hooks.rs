@@ -0,0 +1,193 @@
+use crate::{error::ExecutionError, Localization, RunSnapshot};
+use eframe::egui::{CollapsingHeader, Context, RichText, Ui};
+use std::{
+    io,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+/// Runs the commands of [`crate::Settings::pre_run`] and [`crate::Settings::post_run`]
+/// on a worker thread, so slow hooks don't freeze the GUI
+#[derive(Debug)]
+pub struct Hooks {
+    pre_run: Vec<Vec<String>>,
+    post_run: Vec<Vec<String>>,
+    /// Output of the hooks of the last run
+    output: String,
+    running: Option<Running>,
+}
+
+#[derive(Debug)]
+struct Running {
+    /// Run once the pre-run hooks succeed, None for post-run hooks
+    snapshot: Option<RunSnapshot>,
+    messages: Receiver<Message>,
+}
+
+#[derive(Debug)]
+enum Message {
+    Output(String),
+    /// The command that failed, if any
+    Finished(Option<String>),
+}
+
+impl Hooks {
+    pub fn new(pre_run: Vec<Vec<String>>, post_run: Vec<Vec<String>>) -> Self {
+        Self {
+            pre_run,
+            post_run,
+            output: String::new(),
+            running: None,
+        }
+    }
+
+    pub fn has_pre_run(&self) -> bool {
+        !self.pre_run.is_empty()
+    }
+
+    pub fn has_post_run(&self) -> bool {
+        !self.post_run.is_empty()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Runs the pre-run hooks, [`Hooks::poll`] returns the snapshot once they succeed
+    pub fn run_pre(&mut self, snapshot: RunSnapshot, ctx: Context) {
+        self.output.clear();
+        let env = snapshot.env.clone().unwrap_or_default();
+        let messages = spawn(self.pre_run.clone(), env, snapshot.working_dir.clone(), ctx);
+        self.running = Some(Running {
+            snapshot: Some(snapshot),
+            messages,
+        });
+    }
+
+    /// Runs the post-run hooks of a finished run
+    pub fn run_post(&mut self, snapshot: &RunSnapshot, exit_code: Option<i32>, ctx: Context) {
+        if !self.has_pre_run() {
+            self.output.clear();
+        }
+        let mut env = snapshot.env.clone().unwrap_or_default();
+        env.push((
+            "KLASK_EXIT_CODE".into(),
+            exit_code.map(|code| code.to_string()).unwrap_or_default(),
+        ));
+        let messages = spawn(
+            self.post_run.clone(),
+            env,
+            snapshot.working_dir.clone(),
+            ctx,
+        );
+        self.running = Some(Running {
+            snapshot: None,
+            messages,
+        });
+    }
+
+    /// Returns the snapshot once the pre-run hooks succeed, or the error of the failed hook.
+    /// Post-run hooks only add to the output.
+    pub fn poll(
+        &mut self,
+        localization: &Localization,
+    ) -> Option<Result<RunSnapshot, ExecutionError>> {
+        let running = self.running.as_mut()?;
+        loop {
+            match running.messages.try_recv() {
+                Ok(Message::Output(text)) => self.output.push_str(&text),
+                Ok(Message::Finished(failed)) => {
+                    let snapshot = self.running.take().and_then(|running| running.snapshot);
+                    return match failed {
+                        Some(command) => {
+                            let err = localization
+                                .error_hook_failed
+                                .format(&[("command", &command)]);
+                            self.output.push_str(&err);
+                            self.output.push('\n');
+                            snapshot.map(|_| Err(err.into()))
+                        }
+                        None => snapshot.map(Ok),
+                    };
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    let snapshot = self.running.take().and_then(|running| running.snapshot);
+                    let err = localization.error_hooks_stopped.as_str();
+                    return snapshot.map(|_| Err(err.into()));
+                }
+            }
+        }
+    }
+
+    /// Collapsed output of the hooks of the last run
+    pub fn show(&self, ui: &mut Ui, localization: &Localization) {
+        if self.output.is_empty() {
+            return;
+        }
+        CollapsingHeader::new(&localization.hooks)
+            .id_source("klask_hooks")
+            .show(ui, |ui| {
+                ui.label(RichText::new(&self.output).monospace());
+            });
+    }
+}
+
+/// Runs the commands one after another, stopping at the first one that fails
+fn spawn(
+    commands: Vec<Vec<String>>,
+    env: Vec<(String, String)>,
+    working_dir: Option<String>,
+    ctx: Context,
+) -> Receiver<Message> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut failed = None;
+        for command in commands {
+            let line = command.join(" ");
+            let (text, success) = match run(&command, &env, working_dir.as_deref()) {
+                Ok(output) => (
+                    format!(
+                        "{}{}",
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    ),
+                    output.status.success(),
+                ),
+                Err(err) => (format!("{}\n", err), false),
+            };
+            drop(tx.send(Message::Output(format!("$ {}\n{}", line, text))));
+            ctx.request_repaint();
+            if !success {
+                failed = Some(line);
+                break;
+            }
+        }
+        drop(tx.send(Message::Finished(failed)));
+        ctx.request_repaint();
+    });
+    rx
+}
+
+fn run(
+    command: &[String],
+    env: &[(String, String)],
+    working_dir: Option<&str>,
+) -> io::Result<std::process::Output> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Command is empty"))?;
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null());
+    if let Some(working_dir) = working_dir.filter(|dir| !dir.is_empty()) {
+        command.current_dir(working_dir);
+    }
+    command.output()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,55 @@
+use super::Hooks;
+use crate::{Localization, RunSnapshot};
+use eframe::egui::Context;
+use std::{thread, time::Duration};
+
+fn snapshot() -> RunSnapshot {
+    RunSnapshot {
+        args: vec![],
+        subcommands: vec![],
+        env: Some(vec![("NAME".into(), "klask".into())]),
+        stdin: None,
+        working_dir: None,
+    }
+}
+
+fn wait(hooks: &mut Hooks) -> Option<Result<RunSnapshot, String>> {
+    let localization = Localization::default();
+    for _ in 0..500 {
+        let result = hooks.poll(&localization);
+        if !hooks.is_running() {
+            return result.map(|result| result.map_err(|err| err.to_string()));
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("Hooks didn't finish");
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_run_hooks() {
+    let echo = vec!["sh".into(), "-c".into(), "echo hello $NAME".into()];
+    let mut hooks = Hooks::new(vec![echo.clone()], vec![]);
+    hooks.run_pre(snapshot(), Context::default());
+    assert_eq!(wait(&mut hooks), Some(Ok(snapshot())));
+    assert_eq!(hooks.output, "$ sh -c echo hello $NAME\nhello klask\n");
+
+    let fail = vec!["false".into()];
+    let mut hooks = Hooks::new(vec![fail, echo], vec![]);
+    hooks.run_pre(snapshot(), Context::default());
+    assert_eq!(wait(&mut hooks), Some(Err("Hook 'false' failed".into())));
+    assert_eq!(hooks.output, "$ false\nHook 'false' failed\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn post_run_hooks() {
+    let echo = vec!["sh".into(), "-c".into(), "echo $KLASK_EXIT_CODE".into()];
+    let mut hooks = Hooks::new(vec![], vec![echo, vec![]]);
+    hooks.run_post(&snapshot(), Some(3), Context::default());
+    assert_eq!(wait(&mut hooks), None);
+    assert_eq!(
+        hooks.output,
+        "$ sh -c echo $KLASK_EXIT_CODE\n3\n$ \nCommand is empty\nHook '' failed\n"
+    );
+}
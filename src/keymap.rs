@@ -0,0 +1,140 @@
+use eframe::egui::{Context, Key, Modifiers, Response};
+use std::fmt;
+
+/// A key pressed with modifiers, like `Ctrl+Enter`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shortcut {
+    /// Modifiers that have to be held, others can't be
+    pub modifiers: Modifiers,
+    /// Key that has to be pressed
+    pub key: Key,
+}
+
+impl Shortcut {
+    /// Use [`Modifiers::COMMAND`] for Ctrl, which is ⌘ Command on Mac
+    pub const fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Like [`Shortcut::new`] with [`Modifiers::COMMAND`]
+    pub const fn command(key: Key) -> Self {
+        Self::new(Modifiers::COMMAND, key)
+    }
+
+    /// Returns true if it was pressed this frame, and removes the key press
+    /// so text edits don't see it
+    pub(crate) fn consume(&self, ctx: &Context) -> bool {
+        ctx.input_mut().consume_key(self.modifiers, self.key)
+    }
+}
+
+/// Shown in tooltips, like `Ctrl+Shift+K`
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mac = cfg!(target_os = "macos");
+        let modifiers = [
+            (
+                self.modifiers.ctrl || (self.modifiers.command && !mac),
+                "Ctrl",
+            ),
+            (
+                self.modifiers.mac_cmd || (self.modifiers.command && mac),
+                "Cmd",
+            ),
+            (self.modifiers.alt, if mac { "Option" } else { "Alt" }),
+            (self.modifiers.shift, "Shift"),
+        ];
+        for (_, name) in modifiers.iter().filter(|(pressed, _)| *pressed) {
+            write!(f, "{}+", name)?;
+        }
+        let key = format!("{:?}", self.key);
+        write!(f, "{}", key.strip_prefix("Num").unwrap_or(&key))
+    }
+}
+
+/// Keyboard shortcuts, see [`crate::Settings::keymap`]. Pass None to disable one.
+/// Is marked with `#[non_exhaustive]` so you must construct it like this
+/// ```
+/// # use klask::{Keymap, Shortcut};
+/// # use eframe::egui::Key;
+/// let mut keymap = Keymap::default();
+/// keymap.clear_output = Some(Shortcut::command(Key::E));
+/// keymap.kill = None;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Keymap {
+    /// Runs the binary. Default is Ctrl+Enter.
+    pub run: Option<Shortcut>,
+    /// Runs the binary again with the arguments of the last run. Default is Ctrl+R.
+    pub rerun: Option<Shortcut>,
+    /// Kills the running binary. Default is Ctrl+Shift+K.
+    pub kill: Option<Shortcut>,
+    /// Clears the output, unless the binary is running. Default is Ctrl+L.
+    pub clear_output: Option<Shortcut>,
+    /// Opens the command palette for jumping to an argument or subcommand. Default is Ctrl+P.
+    pub search: Option<Shortcut>,
+    /// Switches to the next tab. Default is Ctrl+PageDown.
+    pub next_tab: Option<Shortcut>,
+    /// Switches to the previous tab. Default is Ctrl+PageUp.
+    pub previous_tab: Option<Shortcut>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            run: Some(Shortcut::command(Key::Enter)),
+            rerun: Some(Shortcut::command(Key::R)),
+            kill: Some(Shortcut::new(
+                Modifiers {
+                    shift: true,
+                    ..Modifiers::COMMAND
+                },
+                Key::K,
+            )),
+            clear_output: Some(Shortcut::command(Key::L)),
+            search: Some(Shortcut::command(Key::P)),
+            next_tab: Some(Shortcut::command(Key::PageDown)),
+            previous_tab: Some(Shortcut::command(Key::PageUp)),
+        }
+    }
+}
+
+/// Pressed shortcuts of a frame, consumed before any widget sees them
+#[derive(Debug, Default)]
+pub(crate) struct Pressed {
+    pub run: bool,
+    pub rerun: bool,
+    pub kill: bool,
+    pub clear_output: bool,
+    pub search: bool,
+    pub next_tab: bool,
+    pub previous_tab: bool,
+}
+
+impl Keymap {
+    pub(crate) fn consume(&self, ctx: &Context) -> Pressed {
+        let consume =
+            |shortcut: &Option<Shortcut>| shortcut.is_some_and(|shortcut| shortcut.consume(ctx));
+        Pressed {
+            run: consume(&self.run),
+            rerun: consume(&self.rerun),
+            kill: consume(&self.kill),
+            clear_output: consume(&self.clear_output),
+            search: consume(&self.search),
+            next_tab: consume(&self.next_tab),
+            previous_tab: consume(&self.previous_tab),
+        }
+    }
+}
+
+/// Shows the shortcut of a button when hovering over it
+pub(crate) fn on_hover_shortcut(response: Response, shortcut: Option<Shortcut>) -> Response {
+    match shortcut {
+        Some(shortcut) => response.on_hover_text(shortcut.to_string()),
+        None => response,
+    }
+}
+
+#[cfg(test)]
+mod tests;
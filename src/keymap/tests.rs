@@ -0,0 +1,18 @@
+use super::{Keymap, Shortcut};
+use eframe::egui::{Key, Modifiers};
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn shortcut_text() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.run.unwrap().to_string(), "Ctrl+Enter");
+    assert_eq!(keymap.kill.unwrap().to_string(), "Ctrl+Shift+K");
+    assert_eq!(
+        Shortcut::new(Modifiers::ALT, Key::Num1).to_string(),
+        "Alt+1"
+    );
+    assert_eq!(
+        Shortcut::new(Modifiers::NONE, Key::PageDown).to_string(),
+        "PageDown"
+    );
+}
@@ -0,0 +1,18 @@
+use clap::ArgMatches;
+
+/// An application that keeps its state between runs, like loaded models or database
+/// connections, see [`crate::run_klask_app`]. Runs happen one at a time on a thread
+/// in this process, so the state is never used by two runs at once.
+pub trait KlaskApp: Send + 'static {
+    /// Called before the first run, on its thread. Default does nothing.
+    fn init(&mut self) {}
+
+    /// Called for every run with the arguments from the GUI.
+    fn run(&mut self, matches: &ArgMatches);
+
+    /// Called after every run, even if it panicked. Default does nothing.
+    fn after_run(&mut self) {}
+
+    /// Called when the GUI closes, unless a run is still going. Default does nothing.
+    fn exit(&mut self) {}
+}
@@ -29,42 +29,144 @@
 //! * The binary runs and there's no `CHILD_APP_ENV_VAR` environment variable ⇾ no user code runs, only the GUI is displayed.
 //! * The "Run" button in the GUI is pressed ⇾ `CHILD_APP_ENV_VAR` is set, the binary is started again. Arguments are passed to `stdin` and `stdout` is intercepted for displaying output.
 //! * The binary is run with `CHILD_APP_ENV_VAR` ⇾ the user-provided closure is run.
+//!
+//! Packagers and users can change the GUI of a binary without rebuilding it with a
+//! `klask.toml` file next to the binary, or at the path in the `KLASK_CONFIG` environment
+//! variable. It overrides the theme, language, window size and which tabs are shown:
+//! ```toml
+//! theme = "dark"          # "default", "light", "dark" or "high-contrast"
+//! language = "polish"     # "default", "english" or "polish"
+//! window_size = [800, 600]
+//!
+//! [tabs]
+//! env = false
+//! stdin = true
+//! working_dir = true
+//! help = true
+//! preferences = false
+//! ```
 
 mod app_state;
 mod arg_state;
+mod batch;
 mod child_app;
+mod config_file;
 mod error;
+#[cfg(feature = "export")]
+mod export;
+mod favourites;
+mod file_dialog;
+mod form_values;
+mod help;
+mod highlight;
+mod history;
+mod hooks;
+mod keymap;
+#[cfg(feature = "inprocess-capture")]
+mod klask_app;
+mod localization;
+mod markdown;
+mod menu;
 /// Additional options for output like progress bars.
 pub mod output;
+mod palette;
+mod preferences;
+mod records;
+mod run_result;
+mod runtime_config;
+mod session;
 mod settings;
+mod substitution;
+mod suggestions;
+#[cfg(all(unix, feature = "terminal"))]
+mod terminal;
+mod validation;
+mod value_history;
 
-use app_state::AppState;
+use app_state::{AppState, DropTarget, StepKind};
+use arg_state::{ArgOptions, FormContext};
+use batch::Batch;
 use child_app::{ChildApp, StdinType};
 use clap::{ArgMatches, Command, FromArgMatches, IntoApp};
+use config_file::ConfigFile;
 use eframe::{
-    egui::{self, Button, Color32, Context, FontData, FontDefinitions, Grid, Style, TextEdit, Ui},
+    egui::{
+        self, Align, Button, Checkbox, Color32, Context, FontData, FontDefinitions, Grid, Id, Key,
+        Modifiers, Style, TextEdit, Ui,
+    },
     CreationContext, Frame,
 };
 use error::ExecutionError;
-use rfd::FileDialog;
+use favourites::Favourites;
+use highlight::Syntax;
+use history::{History, Run};
+use hooks::Hooks;
+use menu::{MenuAction, MenuBar};
+use records::{Records, RunRecord};
+use runtime_config::RuntimeConfig;
+use serde::{Deserialize, Serialize};
+use session::Sessions;
 
-use output::Output;
-pub use settings::{Localization, Settings};
-use std::{borrow::Cow, hash::Hash};
+pub use form_values::{build_args, FormValues};
+pub use keymap::{Keymap, Shortcut};
+#[cfg(feature = "inprocess-capture")]
+pub use klask_app::KlaskApp;
+pub use localization::{Localization, Message, PluralRule};
+use output::{Output, OutputOptions};
+use palette::CommandPalette;
+use preferences::Preferences;
+pub use run_result::RunResult;
+pub use settings::{ArgText, FlagNames, LayoutItem, Settings};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::Hash,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use validation::Validation;
+use value_history::ValueHistory;
 
 const CHILD_APP_ENV_VAR: &str = "KLASK_CHILD_APP";
+const CANCEL_FILE_ENV_VAR: &str = "KLASK_CANCEL_FILE";
+/// Shorter runs don't show a notification, since the window is likely still watched
+#[cfg(feature = "notify")]
+const NOTIFY_AFTER: Duration = Duration::from_secs(10);
+
+/// Returns true after the user pressed the "Stop gracefully" button. Poll it in
+/// long-running code to shut down cleanly instead of being killed.
+/// Always returns false when not running inside klask.
+/// ```no_run
+/// # use clap::App;
+/// # use klask::Settings;
+/// klask::run_app(App::new("Example"), Settings::default(), |_| {
+///     while !klask::is_cancelled() {
+///         // Do some work
+///     }
+///     println!("Stopped, cleaning up");
+/// });
+/// ```
+pub fn is_cancelled() -> bool {
+    std::env::var_os(CANCEL_FILE_ENV_VAR).is_some_and(|path| Path::new(&path).exists())
+}
 
 /// Call with an [`App`] and a closure that contains the code that would normally be in `main`.
+/// The closure can return a `Result`, see [`RunResult`].
 /// ```no_run
 /// # use clap::{App, Arg};
 /// # use klask::Settings;
 /// let app = App::new("Example").arg(Arg::new("debug").short('d'));
-
+///
 /// klask::run_app(app, Settings::default(), |matches| {
 ///    println!("{}", matches.is_present("debug"))
 /// });
 /// ```
-pub fn run_app(app: Command<'static>, settings: Settings, f: impl FnOnce(&ArgMatches)) {
+pub fn run_app<R: RunResult>(
+    app: Command<'static>,
+    settings: Settings,
+    f: impl FnOnce(&ArgMatches) -> R,
+) {
     if std::env::var(CHILD_APP_ENV_VAR).is_ok() {
         std::env::remove_var(CHILD_APP_ENV_VAR);
 
@@ -72,47 +174,220 @@ pub fn run_app(app: Command<'static>, settings: Settings, f: impl FnOnce(&ArgMat
             .try_get_matches()
             .expect("Internal error, arguments should've been verified by the GUI app");
 
-        f(&matches);
+        let code = f(&matches).report();
+        if code != 0 {
+            // Exiting skips flushing stdout
+            drop(std::io::Write::flush(&mut std::io::stdout()));
+            std::process::exit(code);
+        }
     } else {
-        // During validation we don't pass in a binary name
-        let app = app.setting(clap::AppSettings::NoBinaryName);
-        let app_name = app.get_name().to_string();
+        run_gui(app, settings, |_| {});
+    }
+}
 
-        // eframe::run_native requires that Box::new(klask) has 'static
-        // lifetime, so we must leak here. But it never returns (return value !)
-        // so it should be ok.
-        let localization = Box::leak(Box::new(settings.localization));
+/// Like [`run_app`], but the closure returns a future, which runs on a multi-threaded
+/// tokio runtime with every driver enabled. Requires the `async` feature.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// let app = App::new("Example").arg(Arg::new("debug").short('d'));
+///
+/// klask::run_app_async(app, Settings::default(), |matches| async move {
+///    println!("{}", matches.is_present("debug"))
+/// });
+/// ```
+#[cfg(feature = "async")]
+pub fn run_app_async<Fut>(
+    app: Command<'static>,
+    settings: Settings,
+    f: impl FnOnce(ArgMatches) -> Fut,
+) where
+    Fut: std::future::Future,
+    Fut::Output: RunResult,
+{
+    run_app_async_with(app, settings, |future| tokio_runtime().block_on(future), f);
+}
 
-        let mut klask = Klask {
-            state: AppState::new(&app, localization),
-            tab: Tab::Arguments,
-            env: settings.enable_env.map(|desc| (desc, vec![])),
-            stdin: settings
-                .enable_stdin
-                .map(|desc| (desc, StdinType::Text(String::new()))),
-            working_dir: settings
-                .enable_working_dir
-                .map(|desc| (desc, String::new())),
-            output: Output::None,
-            app,
-            custom_font: settings.custom_font,
-            localization,
-            style: settings.style,
-        };
-        let native_options = eframe::NativeOptions::default();
-        eframe::run_native(
-            app_name.as_str(),
-            native_options,
-            Box::new(|cc| {
-                klask.setup(cc);
-                Box::new(klask)
+/// Like [`run_app_async`], but the future is run by `block_on`, for other runtimes
+/// or a runtime with custom settings. Doesn't need the `async` feature.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// # fn block_on(future: impl std::future::Future<Output = ()>) {}
+/// let app = App::new("Example").arg(Arg::new("debug").short('d'));
+///
+/// klask::run_app_async_with(app, Settings::default(), block_on, |matches| async move {
+///    println!("{}", matches.is_present("debug"))
+/// });
+/// ```
+pub fn run_app_async_with<Fut>(
+    app: Command<'static>,
+    settings: Settings,
+    block_on: impl FnOnce(Fut) -> Fut::Output,
+    f: impl FnOnce(ArgMatches) -> Fut,
+) where
+    Fut: std::future::Future,
+    Fut::Output: RunResult,
+{
+    run_app(app, settings, |matches| block_on(f(matches.clone())));
+}
+
+#[cfg(feature = "async")]
+fn tokio_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Internal error, tokio runtime should always build")
+}
+
+/// Like [`run_app`], but the closure runs on a thread in the same process instead of
+/// starting the binary again. Output is captured by redirecting stdout and stderr of the
+/// whole process while the closure runs, so progress bars and other output work the same.
+/// Requires the `inprocess-capture` feature.
+///
+/// Environment variables and the working directory are set for the whole process, input
+/// from [`Settings::enable_stdin`] isn't passed to the closure, and killing only asks the
+/// closure to stop, see [`is_cancelled`], since threads can't be killed.
+///
+/// The environment is changed while the GUI's threads keep running, and changing it while
+/// another thread reads it is a data race on some platforms, like Linux with glibc.
+/// Use [`run_app`] if that's a concern.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// let app = App::new("Example").arg(Arg::new("debug").short('d'));
+///
+/// klask::run_app_in_process(app, Settings::default(), |matches| {
+///    println!("{}", matches.is_present("debug"))
+/// });
+/// ```
+#[cfg(feature = "inprocess-capture")]
+pub fn run_app_in_process(
+    app: Command<'static>,
+    settings: Settings,
+    f: impl Fn(&ArgMatches) + Send + Sync + 'static,
+) {
+    run_gui_in_process(
+        app,
+        settings,
+        child_app::InProcess {
+            run: Arc::new(f),
+            exit: None,
+            startup_dir: std::env::current_dir().unwrap_or_default(),
+        },
+    );
+}
+
+/// Like [`run_app_in_process`], but runs a [`KlaskApp`], which keeps its state between runs.
+/// Requires the `inprocess-capture` feature.
+/// ```no_run
+/// # use clap::{App, Arg, ArgMatches};
+/// # use klask::{KlaskApp, Settings};
+/// struct Counter {
+///     runs: usize,
+/// }
+///
+/// impl KlaskApp for Counter {
+///     fn run(&mut self, matches: &ArgMatches) {
+///         self.runs += 1;
+///         println!("Run {}, debug: {}", self.runs, matches.is_present("debug"));
+///     }
+/// }
+///
+/// let app = App::new("Example").arg(Arg::new("debug").short('d'));
+/// klask::run_klask_app(app, Settings::default(), Counter { runs: 0 });
+/// ```
+#[cfg(feature = "inprocess-capture")]
+pub fn run_klask_app(app: Command<'static>, settings: Settings, klask_app: impl KlaskApp) {
+    // Runs happen one at a time, so the lock is only contended when closing during a run
+    let state = Arc::new(std::sync::Mutex::new((klask_app, false)));
+    let exit_state = state.clone();
+    run_gui_in_process(
+        app,
+        settings,
+        child_app::InProcess {
+            run: Arc::new(move |matches| {
+                let mut state = state
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                let (klask_app, initialized) = &mut *state;
+                if !std::mem::replace(initialized, true) {
+                    klask_app.init();
+                }
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    klask_app.run(matches)
+                }));
+                klask_app.after_run();
+                if let Err(panic) = result {
+                    std::panic::resume_unwind(panic);
+                }
             }),
-        );
+            exit: Some(Arc::new(move || {
+                if let Ok(mut state) = exit_state.try_lock() {
+                    state.0.exit();
+                }
+            })),
+            startup_dir: std::env::current_dir().unwrap_or_default(),
+        },
+    );
+}
+
+#[cfg(feature = "inprocess-capture")]
+fn run_gui_in_process(app: Command<'static>, settings: Settings, in_process: child_app::InProcess) {
+    run_gui(app, settings, |klask| {
+        klask.in_process = Some(in_process);
+        klask.batch = None;
+        // The binary isn't started again, so it can't run in the terminal
+        #[cfg(all(unix, feature = "terminal"))]
+        {
+            klask.terminal = None;
+        }
+    });
+}
+
+/// Builds the GUI from `app`, but runs `program` with the arguments instead of this
+/// binary. Use it to make a GUI for an existing binary by describing its arguments with clap.
+/// Output helpers like progress bars aren't available, since `program` doesn't use klask.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// let app = App::new("ls")
+///     .arg(Arg::new("all").short('a').help("Show hidden files"))
+///     .arg(Arg::new("directory"));
+///
+/// klask::run_command(app, Settings::default(), "ls");
+/// ```
+pub fn run_command(app: Command<'static>, settings: Settings, program: impl Into<PathBuf>) {
+    let program = program.into();
+    run_gui(app, settings, |klask| klask.program = Some(program));
+}
+
+fn run_gui(app: Command<'static>, mut settings: Settings, configure: impl FnOnce(&mut Klask)) {
+    if settings.high_contrast {
+        settings.style.visuals = preferences::high_contrast();
     }
+    let window_size = RuntimeConfig::load().apply(&mut settings);
+
+    let mut klask = Klask::new(app, settings);
+    configure(&mut klask);
+    let app_name = klask.app.get_name().to_string();
+
+    let native_options = eframe::NativeOptions {
+        initial_window_size: window_size,
+        ..Default::default()
+    };
+    eframe::run_native(
+        app_name.as_str(),
+        native_options,
+        Box::new(|cc| {
+            klask.setup(cc);
+            Box::new(klask)
+        }),
+    );
 }
 
 /// Can be used with a struct deriving [`clap::Clap`]. Call with a closure that contains the code that would normally be in `main`.
-/// It's just a wrapper over [`run_app`].
+/// It's just a wrapper over [`run_app`], and the closure can return a `Result` the same way.
 /// ```no_run
 /// # use clap::{App, Arg, Parser};
 /// # use klask::Settings;
@@ -126,36 +401,196 @@ pub fn run_app(app: Command<'static>, settings: Settings, f: impl FnOnce(&ArgMat
 ///     println!("{}", example.debug);
 /// });
 /// ```
-pub fn run_derived<C, F>(settings: Settings, f: F)
+pub fn run_derived<C, R>(settings: Settings, f: impl FnOnce(C) -> R)
 where
     C: IntoApp + FromArgMatches,
-    F: FnOnce(C),
+    R: RunResult,
+{
+    run_derived_with_matches::<C, _>(settings, |c, _| f(c));
+}
+
+/// Like [`run_derived`], but the closure returns a future, which runs on a tokio runtime
+/// like in [`run_app_async`]. Requires the `async` feature.
+/// ```no_run
+/// # use clap::{App, Arg, Parser};
+/// # use klask::Settings;
+/// #[derive(Parser)]
+/// struct Example {
+///     #[clap(short)]
+///     debug: bool,
+/// }
+///
+/// klask::run_derived_async::<Example, _>(Settings::default(), |example| async move {
+///     println!("{}", example.debug);
+/// });
+/// ```
+#[cfg(feature = "async")]
+pub fn run_derived_async<C, Fut>(settings: Settings, f: impl FnOnce(C) -> Fut)
+where
+    C: IntoApp + FromArgMatches,
+    Fut: std::future::Future,
+    Fut::Output: RunResult,
+{
+    run_derived::<C, _>(settings, |c| tokio_runtime().block_on(f(c)));
+}
+
+/// Like [`run_derived`], but the closure also gets the [`ArgMatches`], for information
+/// the struct doesn't have, like the indices of values.
+/// ```no_run
+/// # use clap::{App, Arg, Parser};
+/// # use klask::Settings;
+/// #[derive(Parser)]
+/// struct Example {
+///     #[clap(short)]
+///     include: Vec<String>,
+/// }
+///
+/// klask::run_derived_with_matches::<Example, _>(Settings::default(), |example, matches| {
+///     let indices = matches.indices_of("include").into_iter().flatten();
+///     for (include, index) in example.include.iter().zip(indices) {
+///         println!("{} at {}", include, index);
+///     }
+/// });
+/// ```
+pub fn run_derived_with_matches<C, R>(settings: Settings, f: impl FnOnce(C, &ArgMatches) -> R)
+where
+    C: IntoApp + FromArgMatches,
+    R: RunResult,
 {
     run_app(C::command(), settings, |m| {
         let matches = C::from_arg_matches(m)
             .expect("Internal error, C::from_arg_matches should always succeed");
-        f(matches);
+        f(matches, m)
     });
 }
 
 #[derive(Debug)]
-struct Klask<'s> {
-    state: AppState<'s>,
+struct Klask {
+    state: AppState,
     tab: Tab,
     /// First string is a description
     env: Option<(String, Vec<(String, String)>)>,
     /// First string is a description
     stdin: Option<(String, StdinType)>,
+    /// Syntax highlighted in the stdin text
+    stdin_syntax: Syntax,
     /// First string is a description
     working_dir: Option<(String, String)>,
     output: Output,
+    palette: CommandPalette,
+    validation: Validation,
+    /// See [`Settings::pre_run`] and [`Settings::post_run`]
+    hooks: Hooks,
     // This isn't a generic lifetime because eframe::run_native() requires
     // a 'static lifetime because boxed trait objects default to 'static
     app: Command<'static>,
 
     custom_font: Option<Cow<'static, [u8]>>,
-    localization: &'s Localization,
-    style: Style,
+    /// See [`Settings::output_font`]
+    output_font: Option<Cow<'static, [u8]>>,
+    /// See [`Settings::output_font_size`]
+    output_font_size: Option<f32>,
+    localization: Arc<Localization>,
+    style: Arc<Style>,
+    #[cfg(feature = "notify")]
+    notify_on_finish: bool,
+    /// Set while the child runs, until [`Klask::update_finished`] handles its exit
+    finish_pending: bool,
+    confirm_on_close: bool,
+    close_dialog: CloseDialog,
+    /// Whether the dialog confirming [`Klask::reset_all`] is shown
+    reset_dialog: bool,
+    /// See [`Settings::confirm_kill`]
+    confirm_kill: bool,
+    kill_dialog: bool,
+    /// See [`Settings::disable_run_until_valid`]
+    disable_run_until_valid: bool,
+    /// None if the menu bar is disabled
+    menu_bar: Option<MenuBar>,
+    /// Dropped paths and the arguments the user can choose to fill with them
+    drop_chooser: Option<(Vec<String>, Vec<DropTarget>)>,
+    /// Help rendered for the subcommand path, cached until the selection changes.
+    /// None if the help tab is disabled
+    help: Option<(Vec<String>, String)>,
+    /// See [`Settings::post_process`]
+    post_process: Vec<Vec<String>>,
+    /// Directory from [`Settings::log_dir`] and whether logging is turned on
+    log: Option<(PathBuf, bool)>,
+    log_keep_ansi: bool,
+    /// None if [`Settings::session_dir`] isn't set
+    sessions: Option<Sessions>,
+    /// Id from [`Settings::config_arg`] and the editor state
+    config: Option<(String, ConfigFile)>,
+    /// None if [`Settings::output_history`] is 0
+    history: Option<History>,
+    /// See [`Settings::output_byte_limit`]
+    output_byte_limit: Option<usize>,
+    /// How flags are shown while `show_flags` is on, see [`Settings::flag_names`]
+    flag_names: FlagNames,
+    show_flags: bool,
+    /// See [`Settings::output_renderers`]
+    output_renderers: HashMap<String, output::Renderer>,
+    /// See [`Settings::ansi_palette`], the preferences can override it
+    ansi_palette: output::AnsiPalette,
+    /// None if [`Settings::enable_batch`] is disabled
+    batch: Option<Batch>,
+    /// Index of the shown step, the review comes after the last one.
+    /// None if [`Settings::enable_wizard`] is disabled
+    wizard: Option<usize>,
+    /// None if [`Settings::enable_preferences`] is disabled, loaded in [`Klask::setup`]
+    preferences: Option<Preferences>,
+    /// Line typed for the stdin of the running binary
+    input_line: String,
+    /// None if [`Settings::enable_terminal`] is disabled
+    #[cfg(all(unix, feature = "terminal"))]
+    terminal: Option<terminal::Terminal>,
+    /// None if [`Settings::run_records`] is disabled
+    records: Option<Records>,
+    /// Used for re-running and labelling the current output
+    last_run: Option<RunSnapshot>,
+    /// See [`Settings::enable_substitution`]
+    enable_substitution: bool,
+    /// See [`Settings::capture_panics`]
+    capture_panics: bool,
+    /// See [`Settings::close_on_success`]
+    close_on_success: Option<Duration>,
+    /// When the window closes, set once a run succeeds and cleared by cancelling
+    closing_at: Option<Instant>,
+    /// See [`Settings::keymap`]
+    keymap: Keymap,
+    /// Runs started so far, for the `{n}` placeholder
+    run_count: usize,
+    /// None if [`Settings::enable_favourites`] is disabled
+    favourites: Option<Favourites>,
+    /// None if [`Settings::value_history`] is 0
+    value_history: Option<ValueHistory>,
+    /// Values of the run being started, remembered once it has passed validation and started
+    entered_values: Option<Vec<(String, String)>>,
+    /// Set by [`run_command`]
+    program: Option<PathBuf>,
+    /// Set by [`run_app_in_process`]
+    #[cfg(feature = "inprocess-capture")]
+    in_process: Option<child_app::InProcess>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CloseDialog {
+    Hidden,
+    Shown,
+    /// The user confirmed, so the next close request isn't intercepted
+    Confirmed,
+}
+
+/// Everything needed to repeat a run, independent of the state of the form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RunSnapshot {
+    args: Vec<String>,
+    /// Names of the selected subcommands
+    #[serde(default)]
+    subcommands: Vec<String>,
+    env: Option<Vec<(String, String)>>,
+    stdin: Option<StdinType>,
+    working_dir: Option<String>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -163,41 +598,206 @@ enum Tab {
     Arguments,
     Env,
     Stdin,
+    Config,
+    Records,
+    Batch,
+    Preferences,
+    Help,
+    #[cfg(all(unix, feature = "terminal"))]
+    Terminal,
+}
+
+impl Tab {
+    fn label(self, localization: &Localization) -> &str {
+        match self {
+            Tab::Arguments => &localization.arguments,
+            Tab::Env => &localization.env_variables,
+            Tab::Stdin => &localization.input,
+            Tab::Config => &localization.config_file,
+            Tab::Records => &localization.run_records,
+            Tab::Batch => &localization.batch,
+            Tab::Preferences => &localization.preferences,
+            Tab::Help => &localization.help,
+            #[cfg(all(unix, feature = "terminal"))]
+            Tab::Terminal => &localization.terminal,
+        }
+    }
 }
 
-impl eframe::App for Klask<'_> {
-    fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+impl eframe::App for Klask {
+    fn on_exit(&mut self, _gl: &eframe::glow::Context) {
+        #[cfg(feature = "inprocess-capture")]
+        if let Some(exit) = self.in_process.as_ref().and_then(|f| f.exit.as_ref()) {
+            exit();
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        if let Some(preferences) = &self.preferences {
+            eframe::set_value(storage, preferences::STORAGE_KEY, preferences);
+        }
+    }
+
+    // Only the preferences are saved, the window and widgets start fresh every time
+    fn persist_native_window(&self) -> bool {
+        false
+    }
+
+    fn persist_egui_memory(&self) -> bool {
+        false
+    }
+
+    fn on_exit_event(&mut self) -> bool {
+        if self.confirm_on_close
+            && self.is_child_running()
+            && self.close_dialog != CloseDialog::Confirmed
+        {
+            self.close_dialog = CloseDialog::Shown;
+            false
+        } else {
+            true
+        }
+    }
+
+    fn update(&mut self, ctx: &Context, frame: &mut Frame) {
+        // Consume shortcuts before any widget sees them, so
+        // multiline text edits don't insert a newline
+        // Keys like Ctrl+R and Ctrl+L are for the binary in the terminal
+        #[cfg(all(unix, feature = "terminal"))]
+        let terminal_focused = self.terminal.is_some() && terminal::Terminal::has_focus(ctx);
+        #[cfg(not(all(unix, feature = "terminal")))]
+        let terminal_focused = false;
+        let shortcuts = if terminal_focused {
+            keymap::Pressed::default()
+        } else {
+            self.keymap.consume(ctx)
+        };
+
+        if shortcuts.search {
+            self.palette.toggle();
+        }
+        if shortcuts.next_tab || shortcuts.previous_tab {
+            let tabs = self.tabs();
+            if let Some(index) = tabs.iter().position(|tab| *tab == self.tab) {
+                let step = if shortcuts.next_tab {
+                    1
+                } else {
+                    tabs.len() - 1
+                };
+                self.tab = tabs[(index + step) % tabs.len()];
+            }
+        }
+        if self.palette.show(ctx, &mut self.state, &self.localization) {
+            self.tab = Tab::Arguments;
+        }
+        let ansi_palette = self.output_palette();
+
+        if let Some(result) = self.validation.poll(&self.localization) {
+            match result {
+                Ok(snapshot) => self.run_validated(snapshot, ctx.clone()),
+                Err(err) => {
+                    #[cfg(all(unix, feature = "terminal"))]
+                    if let Some(terminal) = &mut self.terminal {
+                        terminal.take_pending();
+                    }
+                    self.set_output(Output::Err(err));
+                }
+            }
+        }
+        match self.hooks.poll(&self.localization) {
+            Some(Ok(snapshot)) => self.launch(snapshot, ctx.clone()),
+            Some(Err(err)) => self.set_output(Output::Err(err)),
+            None => {}
+        }
+
+        let running = self.is_busy();
+        let can_rerun = !running && self.last_run.is_some();
+        if shortcuts.rerun && can_rerun {
+            self.rerun(ctx.clone());
+        }
+        if shortcuts.kill && self.is_child_running() {
+            self.request_kill();
+        }
+        if shortcuts.clear_output && !running {
+            self.set_output(Output::None);
+        }
+        if let Some(menu_bar) = &mut self.menu_bar {
+            match menu_bar.show(
+                ctx,
+                frame,
+                &self.app,
+                running,
+                can_rerun,
+                &self.localization,
+            ) {
+                Some(MenuAction::Run) => self.start_execution(ctx.clone()),
+                Some(MenuAction::Rerun) => self.rerun(ctx.clone()),
+                Some(MenuAction::StopGracefully) => {
+                    if let Output::Child(child, ..) = &mut self.output {
+                        child.cancel();
+                    }
+                }
+                Some(MenuAction::Kill) => self.request_kill(),
+                Some(MenuAction::Quit) => frame.quit(),
+                None => {}
+            }
+        }
+        if let Some(favourites) = &mut self.favourites {
+            if let Some(snapshot) = favourites.show(ctx, running, &self.localization) {
+                self.execute(snapshot, ctx.clone());
+            }
+        }
+
+        if self.reset_dialog {
+            self.show_reset_dialog(ctx);
+        }
+        if self.kill_dialog {
+            self.show_kill_dialog(ctx);
+        }
+        if self.close_dialog == CloseDialog::Shown {
+            self.show_close_dialog(ctx, frame);
+        }
+
+        let dropped: Vec<String> = ctx
+            .input()
+            .raw
+            .dropped_files
+            .iter()
+            .filter_map(|file| file.path.as_ref())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        if !dropped.is_empty() {
+            self.tab = Tab::Arguments;
+            if let Err(targets) = self.state.drop_paths(&dropped) {
+                self.drop_chooser = Some((dropped, targets));
+            }
+        }
+        if self.drop_chooser.is_some() {
+            self.show_drop_chooser(ctx);
+        }
+
+        self.update_child(ctx);
+        if let Some(closing_at) = self.closing_at {
+            self.update_closing(ctx, frame, closing_at);
+        }
+
+        // Running progress bars and, while scrolled up, the last line stay visible
+        if self.output.has_active_progress() || self.output.is_scrolled_up() {
+            egui::TopBottomPanel::bottom("klask_active_progress").show(ctx, |ui| {
+                ui.add_space(4.0);
+                self.output.show_active_progress(ui, &self.localization);
+            });
+        }
+
+        let native_pixels_per_point = frame.info().native_pixels_per_point;
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
                 // Tab selection
-                let tab_count =
-                    1 + usize::from(self.env.is_some()) + usize::from(self.stdin.is_some());
-
-                if tab_count > 1 {
-                    ui.columns(tab_count, |ui| {
-                        let mut index = 0;
-
-                        ui[index].selectable_value(
-                            &mut self.tab,
-                            Tab::Arguments,
-                            &self.localization.arguments,
-                        );
-                        index += 1;
-
-                        if self.env.is_some() {
-                            ui[index].selectable_value(
-                                &mut self.tab,
-                                Tab::Env,
-                                &self.localization.env_variables,
-                            );
-                            index += 1;
-                        }
-                        if self.stdin.is_some() {
-                            ui[index].selectable_value(
-                                &mut self.tab,
-                                Tab::Stdin,
-                                &self.localization.input,
-                            );
+                let tabs = self.tabs();
+                if tabs.len() > 1 {
+                    ui.columns(tabs.len(), |ui| {
+                        for (ui, tab) in ui.iter_mut().zip(tabs) {
+                            ui.selectable_value(&mut self.tab, tab, tab.label(&self.localization));
                         }
                     });
 
@@ -207,7 +807,31 @@ impl eframe::App for Klask<'_> {
                 // Display selected tab
                 match self.tab {
                     Tab::Arguments => {
-                        ui.add(&mut self.state);
+                        // Only worth toggling when there are flags to show
+                        if self.show_flags || self.state.has_flags() {
+                            ui.checkbox(&mut self.show_flags, &self.localization.show_flags);
+                        }
+                        let flag_names = if self.show_flags {
+                            self.flag_names
+                        } else {
+                            FlagNames::Hidden
+                        };
+                        let working_dir = self
+                            .working_dir
+                            .as_ref()
+                            .filter(|(_, dir)| !dir.is_empty())
+                            .and_then(|(_, dir)| Some(std::env::current_dir().ok()?.join(dir)));
+                        let values = self.value_history.as_ref().map(ValueHistory::values);
+                        let form = FormContext {
+                            flag_names,
+                            working_dir: working_dir.as_deref(),
+                            values: values.as_ref(),
+                        };
+                        if self.wizard.is_some() {
+                            self.update_wizard(ui, &form);
+                        } else {
+                            self.state.show(ui, &form);
+                        }
 
                         // Working dir
                         if let Some((ref desc, path)) = &mut self.working_dir {
@@ -215,11 +839,13 @@ impl eframe::App for Klask<'_> {
                                 ui.label(desc);
                             }
 
-                            let localization = self.localization;
+                            let localization = &self.localization;
                             ui.horizontal(|ui| {
                                 if ui.button(&localization.select_directory).clicked() {
-                                    if let Some(file) = FileDialog::new().pick_folder() {
-                                        *path = file.to_string_lossy().into_owned();
+                                    if let Some(dir) =
+                                        file_dialog::pick(ui, "klask_working_dir", true, None, &[])
+                                    {
+                                        *path = dir;
                                     }
                                 }
                                 ui.add(
@@ -232,34 +858,120 @@ impl eframe::App for Klask<'_> {
                     }
                     Tab::Env => self.update_env(ui),
                     Tab::Stdin => self.update_stdin(ui),
+                    Tab::Config => self.update_config(ui),
+                    Tab::Records => {
+                        let records = self.records.as_mut().unwrap();
+                        records.show(ui, &self.localization, &ansi_palette)
+                    }
+                    Tab::Batch => {
+                        let busy = self.is_busy();
+                        let options = OutputOptions {
+                            renderers: &self.output_renderers,
+                            localization: &self.localization,
+                            palette: ansi_palette,
+                        };
+                        let batch = self.batch.as_mut().unwrap();
+                        if batch.show(ui, busy, &options) {
+                            self.start_batch();
+                        }
+                    }
+                    Tab::Preferences => {
+                        #[cfg(feature = "notify")]
+                        let notify_on_finish = Some(self.notify_on_finish);
+                        #[cfg(not(feature = "notify"))]
+                        let notify_on_finish = None;
+                        let output_font_size = self.output_font_size;
+                        let preferences = self.preferences.as_mut().unwrap();
+                        if preferences.show(
+                            ui,
+                            &self.localization,
+                            notify_on_finish,
+                            output_font_size,
+                        ) {
+                            self.apply_preferences(ctx, native_pixels_per_point);
+                        }
+                    }
+                    Tab::Help => self.update_help(ui, &ansi_palette),
+                    #[cfg(all(unix, feature = "terminal"))]
+                    Tab::Terminal => {
+                        let busy = self.is_busy();
+                        let terminal = self.terminal.as_mut().unwrap();
+                        if terminal.show(ui, busy, &self.localization, &ansi_palette) {
+                            self.start_execution(ctx.clone());
+                        }
+                    }
                 }
 
                 // Run button row
                 ui.horizontal(|ui| {
-                    if ui
-                        .add_enabled(
-                            !self.is_child_running(),
-                            Button::new(&self.localization.run),
+                    let problems = if self.disable_run_until_valid {
+                        self.problems()
+                    } else {
+                        vec![]
+                    };
+                    let can_run = !self.is_busy() && problems.is_empty();
+                    let mut run = ui.add_enabled(can_run, Button::new(&self.localization.run));
+                    if !problems.is_empty() {
+                        run = run.on_disabled_hover_text(format!(
+                            "{}\n{}",
+                            self.localization.run_blocked,
+                            problems.join("\n")
+                        ));
+                    }
+                    if (keymap::on_hover_shortcut(run, self.keymap.run).clicked() || shortcuts.run)
+                        && can_run
+                    {
+                        self.start_execution(ctx.clone());
+                    }
+
+                    if self.last_run.is_some()
+                        && keymap::on_hover_shortcut(
+                            ui.add_enabled(!self.is_busy(), Button::new(&self.localization.rerun)),
+                            self.keymap.rerun,
                         )
                         .clicked()
                     {
-                        match self.try_start_execution(ctx.clone()) {
-                            Ok(child) => {
-                                // Reset
-                                self.state.update_validation_error("", "");
-                                self.output = Output::new_with_child(child);
-                            }
-                            Err(err) => {
-                                if let ExecutionError::ValidationError { name, message } = &err {
-                                    self.state.update_validation_error(name, message);
-                                }
-                                self.output = Output::Err(err);
+                        self.rerun(ctx.clone());
+                    }
+
+                    if let (Some(favourites), Some(last_run)) =
+                        (&mut self.favourites, &self.last_run)
+                    {
+                        if ui
+                            .small_button("📌")
+                            .on_hover_text(&self.localization.pin)
+                            .clicked()
+                        {
+                            favourites.pin(last_run.clone());
+                        }
+                    }
+
+                    if ui.button(&self.localization.reset_all).clicked() {
+                        self.reset_dialog = true;
+                    }
+
+                    if self.is_child_running() {
+                        if let Output::Child(child, ..) = &mut self.output {
+                            if ui
+                                .add_enabled(
+                                    !child.is_cancelled(),
+                                    Button::new(&self.localization.stop_gracefully),
+                                )
+                                .clicked()
+                            {
+                                child.cancel();
                             }
                         }
                     }
 
-                    if self.is_child_running() && ui.button(&self.localization.kill).clicked() {
-                        self.kill_child();
+                    if self.is_child_running()
+                        && keymap::on_hover_shortcut(
+                            ui.button(&self.localization.kill),
+                            self.keymap.kill,
+                        )
+                        .clicked()
+                    {
+                        self.request_kill();
                     }
 
                     if self.is_child_running() {
@@ -269,21 +981,181 @@ impl eframe::App for Klask<'_> {
                         }
                         ui.label(running_text);
                     }
+
+                    if let Some(progress) = self.output.input_progress() {
+                        ui.label(
+                            self.localization
+                                .feeding_input
+                                .format(&[("progress", &progress)]),
+                        );
+                    }
+
+                    if self.validation.is_pending() {
+                        ui.label(&self.localization.validating);
+                    }
+
+                    let running = self.is_child_running();
+                    if let Some((_, enabled)) = &mut self.log {
+                        let response = ui.add_enabled(
+                            !running,
+                            Checkbox::new(enabled, &self.localization.save_log),
+                        );
+
+                        // Clicked in the banner of truncated output
+                        let save_log = Id::new(output::SAVE_LOG_ID);
+                        if ui.data().get_temp(save_log) == Some(true) {
+                            *enabled = true;
+                            response.scroll_to_me(Some(Align::Center));
+                        }
+                        ui.data().insert_temp(save_log, false);
+                    }
+
+                    if let Some(sessions) = &mut self.sessions {
+                        if let Some(session) = sessions.show(ui, running, &self.localization) {
+                            let child = ChildApp::replay(session, sessions.speed(), ctx.clone());
+                            self.set_output(Output::new_with_child(child));
+                        }
+                    }
                 });
 
-                ui.add(&mut self.output);
+                if self.enable_substitution {
+                    self.show_command_preview(ui);
+                }
+                self.hooks.show(ui, &self.localization);
+                ui.scope(|ui| {
+                    let size = self
+                        .preferences
+                        .as_ref()
+                        .and_then(|preferences| preferences.output_font_size)
+                        .or(self.output_font_size);
+                    if let Some(size) = size {
+                        output::set_font_size(ui, size);
+                    }
+                    let options = OutputOptions {
+                        renderers: &self.output_renderers,
+                        localization: &self.localization,
+                        palette: ansi_palette,
+                    };
+                    match &mut self.history {
+                        Some(history) => {
+                            let args = self.last_run.as_ref().map_or(&[][..], |run| &run.args);
+                            history.show(ui, &mut self.output, args, &options)
+                        }
+                        None => {
+                            self.output.show(ui, &options);
+                        }
+                    }
+                });
+
+                if self.output.accepts_input() {
+                    self.update_input(ui);
+                }
             });
         });
     }
 }
 
-impl Klask<'_> {
+impl Klask {
+    fn new(app: Command<'static>, settings: Settings) -> Self {
+        // During validation we don't pass in a binary name
+        let app = app.setting(clap::AppSettings::NoBinaryName);
+
+        // Shared with the state of every argument
+        let options = Arc::new(ArgOptions::new(&settings));
+        let localization = Arc::new(settings.localization);
+
+        let records = if settings.run_records {
+            Some(Records::new(
+                app.get_name(),
+                settings.run_record_limit,
+                settings.run_record_output,
+            ))
+        } else {
+            None
+        };
+        let favourites = settings
+            .enable_favourites
+            .then(|| Favourites::load(app.get_name()));
+        let value_limit = settings.value_history;
+        let value_history =
+            (value_limit > 0).then(|| ValueHistory::load(app.get_name(), value_limit));
+        Klask {
+            state: AppState::new(&app, localization.clone(), options),
+            tab: Tab::Arguments,
+            env: settings.enable_env.map(|desc| (desc, vec![])),
+            stdin: settings
+                .enable_stdin
+                .map(|desc| (desc, StdinType::Text(String::new()))),
+            stdin_syntax: Syntax::default(),
+            working_dir: settings
+                .enable_working_dir
+                .map(|desc| (desc, String::new())),
+            output: Output::None,
+            palette: CommandPalette::default(),
+            validation: Validation::new(app.clone()),
+            hooks: Hooks::new(settings.pre_run, settings.post_run),
+            app,
+            custom_font: settings.custom_font,
+            output_font: settings.output_font,
+            output_font_size: settings.output_font_size,
+            localization,
+            style: Arc::new(settings.style),
+            #[cfg(feature = "notify")]
+            notify_on_finish: settings.notify_on_finish,
+            finish_pending: false,
+            confirm_on_close: settings.confirm_on_close,
+            close_dialog: CloseDialog::Hidden,
+            reset_dialog: false,
+            confirm_kill: settings.confirm_kill,
+            disable_run_until_valid: settings.disable_run_until_valid,
+            kill_dialog: false,
+            menu_bar: settings.enable_menu_bar.then(MenuBar::default),
+            drop_chooser: None,
+            help: settings.enable_help_tab.then(|| (vec![], String::new())),
+            post_process: settings.post_process,
+            log: settings.log_dir.map(|dir| (dir, true)),
+            log_keep_ansi: settings.log_keep_ansi,
+            sessions: settings.session_dir.map(Sessions::new),
+            config: settings.config_arg.map(|arg| (arg, ConfigFile::default())),
+            history: (settings.output_history > 0).then_some(History::new(settings.output_history)),
+            output_byte_limit: settings.output_byte_limit,
+            flag_names: match settings.flag_names {
+                FlagNames::Hidden => FlagNames::Beside,
+                flag_names => flag_names,
+            },
+            show_flags: settings.flag_names != FlagNames::Hidden,
+            input_line: String::new(),
+            output_renderers: settings.output_renderers,
+            ansi_palette: settings.ansi_palette,
+            batch: settings.enable_batch.then(Batch::default),
+            preferences: settings.enable_preferences.then(Preferences::default),
+            wizard: settings.enable_wizard.then_some(0),
+            #[cfg(all(unix, feature = "terminal"))]
+            terminal: settings.enable_terminal.then(terminal::Terminal::default),
+            records,
+            last_run: None,
+            enable_substitution: settings.enable_substitution,
+            capture_panics: settings.capture_panics,
+            close_on_success: settings.close_on_success,
+            closing_at: None,
+            keymap: settings.keymap,
+            run_count: 0,
+            favourites,
+            value_history,
+            entered_values: None,
+            program: None,
+            #[cfg(feature = "inprocess-capture")]
+            in_process: None,
+        }
+    }
+
     fn setup(&mut self, cc: &CreationContext) {
         cc.egui_ctx.set_style(self.style.clone());
 
+        let mut fonts = FontDefinitions::default();
+        let changed_fonts = self.custom_font.is_some() || self.output_font.is_some();
         if let Some(custom_font) = self.custom_font.take() {
             let font_name = String::from("custom_font");
-            let mut fonts = FontDefinitions::default();
 
             fonts.font_data.insert(
                 font_name.clone(),
@@ -305,16 +1177,213 @@ impl Klask<'_> {
                 .entry(egui::FontFamily::Monospace)
                 .or_default()
                 .push(font_name);
+        }
+        if let Some(output_font) = self.output_font.take() {
+            let font_name = String::from("output_font");
+
+            fonts.font_data.insert(
+                font_name.clone(),
+                FontData {
+                    font: output_font,
+                    index: 0,
+                    tweak: Default::default(),
+                },
+            );
 
+            fonts
+                .families
+                .entry(egui::FontFamily::Monospace)
+                .or_default()
+                .insert(0, font_name);
+        }
+        if changed_fonts {
             cc.egui_ctx.set_fonts(fonts);
         }
+
+        if self.preferences.is_some() {
+            let preferences = Preferences::load(cc.storage);
+            // Nothing is entered yet, so the form can be built again
+            if let Some(localization) = preferences.localization() {
+                self.localization = Arc::new(localization);
+                self.state.relocalize(&self.app, self.localization.clone());
+            }
+            self.preferences = Some(preferences);
+            self.apply_preferences(&cc.egui_ctx, cc.integration_info.native_pixels_per_point);
+        }
     }
 
-    fn try_start_execution(&mut self, ctx: egui::Context) -> Result<ChildApp, ExecutionError> {
-        let args = self.state.get_cmd_args(vec![])?;
+    /// Applies the theme and scale chosen in the preferences tab
+    fn apply_preferences(&self, ctx: &Context, native_pixels_per_point: Option<f32>) {
+        if let Some(preferences) = &self.preferences {
+            ctx.set_style(preferences.style(&self.style));
+            if let Some(native) = native_pixels_per_point {
+                ctx.set_pixels_per_point(native * preferences.scale.unwrap_or(1.0));
+            }
+        }
+    }
 
-        // Check for validation errors
-        self.app.try_get_matches_from_mut(args.iter())?;
+    /// The colors chosen in the preferences tab, or [`Settings::ansi_palette`]
+    fn output_palette(&self) -> output::AnsiPalette {
+        match &self.preferences {
+            Some(preferences) => preferences.ansi_palette(self.ansi_palette),
+            None => self.ansi_palette,
+        }
+    }
+
+    /// The choice in the preferences tab, or [`Settings::notify_on_finish`]
+    #[cfg(feature = "notify")]
+    fn notify_on_finish(&self) -> bool {
+        self.preferences
+            .as_ref()
+            .and_then(|preferences| preferences.notify_on_finish)
+            .unwrap_or(self.notify_on_finish)
+    }
+
+    /// Runs with the arguments and settings currently in the form, once they're validated.
+    /// Does nothing while there are [`Klask::problems`] and runs are disabled until valid.
+    fn start_execution(&mut self, ctx: egui::Context) {
+        if self.disable_run_until_valid && !self.problems().is_empty() {
+            #[cfg(all(unix, feature = "terminal"))]
+            if let Some(terminal) = &mut self.terminal {
+                terminal.take_pending();
+            }
+            return;
+        }
+
+        match self
+            .snapshot()
+            .and_then(|snapshot| self.substitute(snapshot))
+        {
+            Ok(snapshot) => {
+                if self.value_history.is_some() {
+                    let mut entered = vec![];
+                    self.state.entered_values(&mut entered);
+                    self.entered_values = Some(entered);
+                }
+                self.validation.start(snapshot, ctx)
+            }
+            Err(err) => self.set_output(Output::Err(err)),
+        }
+    }
+
+    /// Starts a run for every row of the batch file, with the rest of the form as it is
+    fn start_batch(&mut self) {
+        match self.snapshot() {
+            Ok(template) => {
+                let batch = self.batch.as_mut().unwrap();
+                batch.start(&self.state, &template, &mut self.app);
+            }
+            Err(err) => self.set_output(Output::Err(err)),
+        }
+    }
+
+    /// Repeats the last run, even if the form has changed since
+    fn rerun(&mut self, ctx: egui::Context) {
+        if let Some(snapshot) = self.last_run.clone() {
+            self.execute(snapshot, ctx);
+        }
+    }
+
+    /// Runs a validated snapshot, in the terminal if the run was started there
+    fn run_validated(&mut self, snapshot: RunSnapshot, ctx: egui::Context) {
+        #[cfg(all(unix, feature = "terminal"))]
+        if let Some(terminal) = &mut self.terminal {
+            if terminal.take_pending() {
+                if terminal.start(
+                    self.program.as_deref(),
+                    snapshot.args,
+                    snapshot.env,
+                    snapshot.working_dir,
+                    ctx,
+                ) {
+                    self.remember_values();
+                } else {
+                    self.entered_values = None;
+                }
+                return;
+            }
+        }
+        self.execute(snapshot, ctx)
+    }
+
+    /// Runs a snapshot, after the pre-run hooks if there are any
+    fn execute(&mut self, snapshot: RunSnapshot, ctx: egui::Context) {
+        if self.hooks.has_pre_run() {
+            self.hooks.run_pre(snapshot, ctx);
+        } else {
+            self.launch(snapshot, ctx);
+        }
+    }
+
+    fn launch(&mut self, snapshot: RunSnapshot, ctx: egui::Context) {
+        match self
+            .try_start_execution(snapshot.clone(), ctx)
+            .and_then(|child| self.open_log(child))
+        {
+            Ok(mut child) => {
+                if let Some(Sessions { record: true, .. }) = &self.sessions {
+                    child.record_session(snapshot.args.clone());
+                }
+
+                // Reset
+                self.remember_values();
+                self.state.update_validation_error("", "");
+                self.closing_at = None;
+                self.set_output(Output::new_with_child(child));
+                self.last_run = Some(snapshot);
+                self.run_count += 1;
+            }
+            Err(err) => self.set_output(Output::Err(err)),
+        }
+    }
+
+    /// Adds the values of the run that just started to [`Settings::value_history`]
+    fn remember_values(&mut self) {
+        if let (Some(value_history), Some(entered)) =
+            (&mut self.value_history, self.entered_values.take())
+        {
+            value_history.remember(entered);
+        }
+    }
+
+    /// Replaces the output, keeping the previous one in the history
+    fn set_output(&mut self, output: Output) {
+        if let Output::Err(err) = &output {
+            // The run didn't start, so its values aren't worth suggesting
+            self.entered_values = None;
+            if let ExecutionError::ValidationError { name, message } = err {
+                self.state.update_validation_error(name, message);
+            }
+        }
+
+        let previous = std::mem::replace(&mut self.output, output);
+        if let (Some(history), Output::Child(..)) = (&mut self.history, &previous) {
+            history.push(Run {
+                args: self
+                    .last_run
+                    .as_ref()
+                    .map(|run| run.args.clone())
+                    .unwrap_or_default(),
+                output: previous,
+            });
+        }
+    }
+
+    /// Problems with the form that stop it from running, see [`Settings::disable_run_until_valid`]
+    fn problems(&self) -> Vec<String> {
+        let mut problems = self.state.problems();
+        if let Some((_, env)) = &self.env {
+            if env.iter().any(|(key, _)| key.is_empty()) {
+                problems.push(self.localization.error_env_var_cant_be_empty.clone());
+            }
+        }
+        problems
+    }
+
+    /// Captures everything needed to run. Arguments are validated by clap
+    /// afterwards, see [`Validation`].
+    fn snapshot(&self) -> Result<RunSnapshot, ExecutionError> {
+        let args = self.state.get_cmd_args(vec![])?;
 
         if self
             .env
@@ -329,28 +1398,486 @@ impl Klask<'_> {
                 .into());
         }
 
-        ChildApp::run(
+        Ok(RunSnapshot {
             args,
-            self.env.clone().map(|(_, env)| env),
-            self.stdin.clone().map(|(_, stdin)| stdin),
-            self.working_dir.clone().map(|(_, dir)| dir),
+            subcommands: self.state.current_path(),
+            env: self.env.clone().map(|(_, env)| env),
+            stdin: self.stdin.clone().map(|(_, stdin)| stdin),
+            working_dir: self.working_dir.clone().map(|(_, dir)| dir),
+        })
+    }
+
+    /// Expands placeholders in argument and env values, see [`Settings::enable_substitution`]
+    fn substitute(&self, mut snapshot: RunSnapshot) -> Result<RunSnapshot, ExecutionError> {
+        if !self.enable_substitution {
+            return Ok(snapshot);
+        }
+
+        let variables = substitution::Variables::new(self.run_count + 1);
+        let expand = |value: &mut String| -> Result<(), ExecutionError> {
+            if substitution::has_placeholders(value) {
+                *value = substitution::expand(value, &variables, &self.localization)?;
+            }
+            Ok(())
+        };
+        snapshot.args.iter_mut().try_for_each(expand)?;
+        if let Some(env) = &mut snapshot.env {
+            env.iter_mut().try_for_each(|(_, value)| expand(value))?;
+        }
+        Ok(snapshot)
+    }
+
+    /// The command as it would be typed in a shell, with the environment variables first
+    fn command_line(&self, snapshot: RunSnapshot) -> String {
+        let mut command: Vec<String> = snapshot
+            .env
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        command.push(self.app.get_name().to_string());
+        command.extend(snapshot.args);
+        command.join(" ")
+    }
+
+    /// Shows one step of the form, or the review of the command after the last one
+    fn update_wizard(&mut self, ui: &mut Ui, form: &FormContext) {
+        let mut steps = vec![];
+        self.state.wizard_steps(&mut vec![], &mut steps);
+        // Steps disappear when another subcommand is chosen
+        let index = self.wizard.unwrap_or_default().min(steps.len());
+
+        ui.label(self.localization.wizard_step.format(&[
+            ("current", &(index + 1).to_string()),
+            ("total", &(steps.len() + 1).to_string()),
+        ]));
+        match steps.get(index) {
+            Some(step) => {
+                ui.heading(match &step.kind {
+                    StepKind::Args(Some(heading)) => heading,
+                    StepKind::Args(None) => &self.localization.arguments,
+                    StepKind::Subcommand => &self.localization.subcommand,
+                });
+                self.state.show_wizard_step(ui, step, &step.path, form);
+            }
+            None => {
+                ui.heading(&self.localization.review);
+                match self
+                    .snapshot()
+                    .and_then(|snapshot| self.substitute(snapshot))
+                {
+                    Ok(snapshot) => {
+                        ui.monospace(self.command_line(snapshot));
+                    }
+                    Err(err) => {
+                        ui.colored_label(Color32::RED, err.to_string());
+                    }
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(index > 0, Button::new(&self.localization.back))
+                .clicked()
+            {
+                self.wizard = Some(index - 1);
+            }
+            if index < steps.len() && ui.button(&self.localization.next).clicked() {
+                self.wizard = Some(index + 1);
+            }
+        });
+    }
+
+    /// The command with placeholders expanded, if the form has any
+    fn show_command_preview(&self, ui: &mut Ui) {
+        let snapshot = match self.snapshot() {
+            Ok(snapshot) => snapshot,
+            Err(_) => return,
+        };
+        let env = snapshot.env.iter().flatten().map(|(_, value)| value);
+        if !snapshot
+            .args
+            .iter()
+            .chain(env)
+            .any(|value| substitution::has_placeholders(value))
+        {
+            return;
+        }
+
+        match self.substitute(snapshot) {
+            Ok(snapshot) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(&self.localization.command_preview);
+                    ui.monospace(self.command_line(snapshot));
+                });
+            }
+            Err(err) => {
+                ui.colored_label(Color32::RED, err.to_string());
+            }
+        }
+    }
+
+    fn try_start_execution(
+        &self,
+        mut snapshot: RunSnapshot,
+        ctx: egui::Context,
+    ) -> Result<ChildApp, ExecutionError> {
+        if self.capture_panics && std::env::var_os("RUST_BACKTRACE").is_none() {
+            let env = snapshot.env.get_or_insert_with(Vec::new);
+            if !env.iter().any(|(key, _)| key == "RUST_BACKTRACE") {
+                env.push(("RUST_BACKTRACE".into(), "1".into()));
+            }
+        }
+
+        #[cfg(feature = "inprocess-capture")]
+        if let Some(f) = &self.in_process {
+            return ChildApp::run_in_process(
+                &self.app,
+                snapshot.args,
+                f,
+                snapshot.env,
+                snapshot.working_dir,
+                ctx,
+            );
+        }
+
+        ChildApp::run(
+            self.program.as_deref(),
+            snapshot.args,
+            snapshot.env,
+            snapshot.stdin,
+            snapshot.working_dir,
+            &self.post_process,
             ctx,
         )
     }
 
+    fn open_log(&self, mut child: ChildApp) -> Result<ChildApp, ExecutionError> {
+        if let Some((dir, true)) = &self.log {
+            // Colons aren't allowed in file names on Windows
+            let timestamp = humantime::format_rfc3339_seconds(SystemTime::now())
+                .to_string()
+                .replace(':', "-");
+            let path = dir.join(format!("{}-{}.log", self.app.get_name(), timestamp));
+            child.log_to(&path, self.log_keep_ansi)?;
+        }
+        Ok(child)
+    }
+
+    /// Counts down to closing the window after a successful run, see [`Settings::close_on_success`]
+    fn update_closing(&mut self, ctx: &Context, frame: &mut Frame, closing_at: Instant) {
+        let left = closing_at.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            frame.quit();
+            return;
+        }
+
+        egui::TopBottomPanel::top("klask_closing").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let seconds = left.as_secs_f32().ceil() as u64;
+                ui.label(self.localization.format_count(
+                    &self.localization.closing_in,
+                    seconds,
+                    &[],
+                ));
+                if ui.button(&self.localization.cancel).clicked() {
+                    self.closing_at = None;
+                }
+            });
+        });
+        // Keep counting down without input
+        ctx.request_repaint();
+    }
+
+    fn show_close_dialog(&mut self, ctx: &Context, frame: &mut Frame) {
+        egui::Window::new("klask_close_dialog")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(&self.localization.close_confirmation);
+                ui.horizontal(|ui| {
+                    if ui.button(&self.localization.kill_and_exit).clicked() {
+                        self.close_dialog = CloseDialog::Confirmed;
+                        self.kill_child();
+                        frame.quit();
+                    }
+                    if ui.button(&self.localization.cancel).clicked()
+                        || ui.input_mut().consume_key(Modifiers::NONE, Key::Escape)
+                    {
+                        self.close_dialog = CloseDialog::Hidden;
+                    }
+                });
+            });
+    }
+
+    fn show_reset_dialog(&mut self, ctx: &Context) {
+        egui::Window::new("klask_reset_dialog")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(&self.localization.reset_all_confirmation);
+                ui.horizontal(|ui| {
+                    if ui.button(&self.localization.reset_all).clicked() {
+                        self.reset_all();
+                        self.reset_dialog = false;
+                    }
+                    if ui.button(&self.localization.cancel).clicked()
+                        || ui.input_mut().consume_key(Modifiers::NONE, Key::Escape)
+                    {
+                        self.reset_dialog = false;
+                    }
+                });
+            });
+    }
+
+    fn show_kill_dialog(&mut self, ctx: &Context) {
+        let started = match &self.output {
+            Output::Child(child, ..) if child.is_running() => child.started(),
+            // Finished on its own
+            _ => {
+                self.kill_dialog = false;
+                return;
+            }
+        };
+        egui::Window::new("klask_kill_dialog")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(
+                    self.localization
+                        .kill_confirmation
+                        .format(&[("duration", &format_duration(started.elapsed()))]),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(&self.localization.kill).clicked() {
+                        self.kill_child();
+                        self.kill_dialog = false;
+                    }
+                    if ui.button(&self.localization.cancel).clicked()
+                        || ui.input_mut().consume_key(Modifiers::NONE, Key::Escape)
+                    {
+                        self.kill_dialog = false;
+                    }
+                });
+            });
+    }
+
+    /// Kills the child, or asks first if [`Settings::confirm_kill`] is enabled
+    fn request_kill(&mut self) {
+        if self.confirm_kill {
+            self.kill_dialog = true;
+        } else {
+            self.kill_child();
+        }
+    }
+
+    /// Resets the arguments, environment variables, input and working directory
+    fn reset_all(&mut self) {
+        self.state.reset();
+        if let Some(step) = &mut self.wizard {
+            *step = 0;
+        }
+        if let Some((_, env)) = &mut self.env {
+            env.clear();
+        }
+        if let Some((_, stdin)) = &mut self.stdin {
+            *stdin = StdinType::Text(String::new());
+        }
+        if let Some((_, working_dir)) = &mut self.working_dir {
+            working_dir.clear();
+        }
+    }
+
+    fn show_drop_chooser(&mut self, ctx: &Context) {
+        let mut close = false;
+        if let Some((paths, targets)) = self.drop_chooser.take() {
+            egui::Window::new("klask_drop_chooser")
+                .title_bar(false)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(&self.localization.drop_files_into);
+                    for target in &targets {
+                        if ui.button(&target.label).clicked() {
+                            self.state.fill_paths(&target.path, target.arg, &paths);
+                            close = true;
+                        }
+                    }
+                    if ui.button(&self.localization.cancel).clicked()
+                        || ui.input_mut().consume_key(Modifiers::NONE, Key::Escape)
+                    {
+                        close = true;
+                    }
+                });
+
+            if !close {
+                self.drop_chooser = Some((paths, targets));
+            }
+        }
+    }
+
+    /// Shows a notification and saves a run record once the child exits
+    /// Reads new output and handles runs that finished since the last frame
+    fn update_child(&mut self, ctx: &Context) {
+        self.output.read_child(self.output_byte_limit);
+        #[cfg(all(unix, feature = "terminal"))]
+        if let Some(terminal) = &mut self.terminal {
+            terminal.update(&self.localization);
+        }
+        if let Some(batch) = &mut self.batch {
+            batch.update(self.program.as_deref(), &self.post_process, ctx);
+        }
+        self.update_finished(ctx);
+    }
+
+    fn update_finished(&mut self, ctx: &Context) {
+        let child = match &mut self.output {
+            Output::Child(child, ..) => child,
+            _ => return,
+        };
+
+        if child.is_running() {
+            self.finish_pending = true;
+        } else if self.finish_pending {
+            // Output closes slightly before the process exits
+            match child.try_exit_status() {
+                Some(success) => {
+                    self.finish_pending = false;
+                    // Replays only show output, they aren't runs
+                    if child.is_replay() {
+                        return;
+                    }
+                    let duration = child.started().elapsed();
+                    let exit_code = child.exit_code();
+                    if let Some(sessions) = &mut self.sessions {
+                        if let Some(session) = child.take_session(success, exit_code) {
+                            sessions.save(&session, self.app.get_name());
+                        }
+                    }
+                    if self.capture_panics {
+                        self.output.detect_panic();
+                    }
+                    if success {
+                        self.closing_at = self.close_on_success.map(|delay| Instant::now() + delay);
+                    }
+
+                    let output = &self.output;
+                    if let (Some(records), Some(run)) = (&mut self.records, &self.last_run) {
+                        let started = SystemTime::now() - duration;
+                        records.save(RunRecord {
+                            started: humantime::format_rfc3339_millis(started).to_string(),
+                            args: run.args.clone(),
+                            subcommands: run.subcommands.clone(),
+                            env: run.env.clone().unwrap_or_default(),
+                            working_dir: run.working_dir.clone().filter(|dir| !dir.is_empty()),
+                            success,
+                            exit_code,
+                            duration_secs: duration.as_secs_f64(),
+                            output: records.save_output.then(|| output.text()),
+                        });
+                    }
+
+                    #[cfg(feature = "notify")]
+                    if self.notify_on_finish() && duration >= NOTIFY_AFTER {
+                        let message = if success {
+                            &self.localization.notification_success
+                        } else {
+                            &self.localization.notification_failure
+                        };
+                        let body = message.format(&[("duration", &format_duration(duration))]);
+                        let summary = self.app.get_name().to_string();
+
+                        // Showing a notification can block, so don't stall the GUI
+                        std::thread::spawn(move || {
+                            drop(
+                                notify_rust::Notification::new()
+                                    .summary(&summary)
+                                    .body(&body)
+                                    .show(),
+                            )
+                        });
+                    }
+
+                    if self.hooks.has_post_run() {
+                        if let Some(run) = &self.last_run {
+                            self.hooks.run_post(run, exit_code, ctx.clone());
+                        }
+                    }
+                }
+                None => ctx.request_repaint(),
+            }
+        }
+    }
+
     fn kill_child(&mut self) {
-        if let Output::Child(child, _) = &mut self.output {
+        if let Output::Child(child, ..) = &mut self.output {
             child.kill();
         }
     }
 
+    /// Tabs in the order they're shown
+    fn tabs(&self) -> Vec<Tab> {
+        let optional = [
+            (Tab::Env, self.env.is_some()),
+            (Tab::Stdin, self.stdin.is_some()),
+            (Tab::Config, self.config.is_some()),
+            (Tab::Records, self.records.is_some()),
+            (Tab::Batch, self.batch.is_some()),
+            (Tab::Preferences, self.preferences.is_some()),
+            (Tab::Help, self.help.is_some()),
+            #[cfg(all(unix, feature = "terminal"))]
+            (Tab::Terminal, self.terminal.is_some()),
+        ];
+        std::iter::once(Tab::Arguments)
+            .chain(
+                optional
+                    .iter()
+                    .filter_map(|&(tab, shown)| shown.then_some(tab)),
+            )
+            .collect()
+    }
+
     fn is_child_running(&self) -> bool {
         match &self.output {
-            Output::Child(child, _) => child.is_running(),
+            Output::Child(child, ..) => child.is_running(),
             _ => false,
         }
     }
 
+    /// A new run can't start while the child or hooks run or arguments are validated
+    fn is_busy(&self) -> bool {
+        self.is_child_running() || self.validation.is_pending() || self.hooks.is_running()
+    }
+
+    /// Field for answering prompts of the running binary
+    fn update_input(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let end_input = ui.button(&self.localization.end_input);
+            let response = ui.add(
+                TextEdit::singleline(&mut self.input_line)
+                    .hint_text(&self.localization.send_input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY),
+            );
+            if response.lost_focus() && ui.input().key_pressed(Key::Enter) {
+                self.output
+                    .send_input(&std::mem::take(&mut self.input_line));
+                response.request_focus();
+            }
+            if end_input.clicked() {
+                self.output.close_stdin();
+            }
+        });
+    }
+
     fn update_env(&mut self, ui: &mut Ui) {
         let (ref desc, env) = self.env.as_mut().unwrap();
 
@@ -378,11 +1905,27 @@ impl Klask<'_> {
                                 Klask::set_error_style(ui);
                             }
 
-                            ui.text_edit_singleline(key);
+                            let response = ui.text_edit_singleline(key);
 
                             if key.is_empty() {
                                 ui.reset_style();
                             }
+
+                            // Variables of this process are inherited by the child
+                            let inherited = if response.has_focus() {
+                                let mut vars: Vec<_> = std::env::vars()
+                                    .map(|(name, value)| {
+                                        suggestions::Suggestion::new(name, Some(value))
+                                    })
+                                    .collect();
+                                vars.sort_by(|a, b| a.text.cmp(&b.text));
+                                suggestions::matching(key, vars)
+                            } else {
+                                vec![]
+                            };
+                            if let Some(name) = suggestions::show(ui, &response, &inherited) {
+                                *key = name;
+                            }
                         });
 
                         ui.horizontal(|ui| {
@@ -436,21 +1979,63 @@ impl Klask<'_> {
             StdinType::File(path) => {
                 ui.horizontal(|ui| {
                     if ui.button(&localization.select_file).clicked() {
-                        if let Some(file) = FileDialog::new().pick_file() {
-                            *path = file.to_string_lossy().into_owned();
+                        if let Some(file) = file_dialog::pick(ui, "klask_stdin", false, None, &[]) {
+                            *path = file;
+                        }
+                    }
+                    // Like a path copied from a file manager, which may be quoted
+                    if ui.button(&localization.paste_clipboard).clicked() {
+                        if let Some(text) = clipboard_text() {
+                            let line = text.lines().next().unwrap_or_default().trim();
+                            *path = line.trim_matches(['"', '\'']).to_string();
                         }
                     }
                     ui.text_edit_singleline(path);
                 });
             }
             StdinType::Text(text) => {
-                ui.text_edit_multiline(text);
+                let syntax = &mut self.stdin_syntax;
+                ui.horizontal(|ui| {
+                    syntax.show(ui, localization);
+                    if ui.button(&localization.paste_clipboard).clicked() {
+                        if let Some(clipboard) = clipboard_text() {
+                            *text = clipboard;
+                        }
+                    }
+                });
+                highlight::editor(ui, text, self.stdin_syntax);
             }
         };
     }
 
+    fn update_config(&mut self, ui: &mut Ui) {
+        let (arg, config) = self.config.as_mut().unwrap();
+
+        match self.state.value_mut(arg) {
+            Some(path) => config.show(ui, path, &self.localization),
+            None => {
+                ui.label(&self.localization.config_file_unavailable);
+            }
+        }
+    }
+
+    fn update_help(&mut self, ui: &mut Ui, palette: &output::AnsiPalette) {
+        let path = self.state.current_path();
+        let (cached_path, help) = self.help.as_mut().unwrap();
+
+        if help.is_empty() || *cached_path != path {
+            *help = help::render(&self.app, &path);
+            *cached_path = path;
+        }
+
+        ui.scope(|ui| {
+            ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+            output::format_output(ui, help, palette);
+        });
+    }
+
     fn set_error_style(ui: &mut Ui) {
-        let mut style = ui.style_mut();
+        let style = ui.style_mut();
         style.visuals.widgets.inactive.bg_stroke.color = Color32::RED;
         style.visuals.widgets.inactive.bg_stroke.width = 1.0;
         style.visuals.widgets.hovered.bg_stroke.color = Color32::RED;
@@ -460,3 +2045,27 @@ impl Klask<'_> {
         style.visuals.selection.stroke.color = Color32::RED;
     }
 }
+
+/// Formats a duration like `1h 2m 3s`, leaving out leading zero units
+/// Text in the clipboard, None if it's empty or can't be read
+fn clipboard_text() -> Option<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .ok()
+        .filter(|text| !text.is_empty())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests;
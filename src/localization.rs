@@ -0,0 +1,817 @@
+// Structs are marked as `#[non_exhaustive]` to allow adding
+// other strings later without breaking compatibility.
+
+use std::collections::HashMap;
+
+/// Localization for builtin strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Localization {
+    /// Displays when the value is optional. Default is "(Optional)".
+    pub optional: String,
+    /// Button text for opening a dialog for file selection. Default is "Select file...".
+    pub select_file: String,
+    /// Button text for opening a dialog for directory selection. Default is "Select directory...".
+    pub select_directory: String,
+    /// Shown when hovering over the button for opening a dialog in one of
+    /// [`crate::Settings::dialog_bookmarks`], and the menu of bookmarked lines of the output.
+    /// Default is "Bookmarks".
+    pub bookmarks: String,
+    /// Button text for creating a new field for multi-value arguments and environment variables. Default is "New value".
+    pub new_value: String,
+    /// Button text for adding each line in the clipboard as a value of a multi-value argument.
+    /// Default is "Paste list".
+    pub paste_list: String,
+    /// Button text for resetting multi-value arguments. Default is "Reset".
+    pub reset: String,
+    /// Button text for resetting multi-value arguments to default. Default is "Reset to default".
+    pub reset_to_default: String,
+    /// Error text when an argument is required. `{name}` is replaced with the argument name.
+    /// Default is "Argument '{name}' is required".
+    pub error_is_required: Message,
+    /// Error text when several values starting with `-` can't be passed to an argument.
+    /// `{name}` is replaced with the argument name.
+    /// Default is "Argument '{name}' can't take several values starting with '-'".
+    pub error_hyphen_values: Message,
+    /// Error text when a value in a file is for an argument that doesn't exist.
+    /// `{name}` is replaced with the id of the argument. Default is "Unknown argument '{name}'".
+    pub error_unknown_argument: Message,
+    /// Error text when a subcommand to select doesn't exist, see [`crate::build_args`].
+    /// `{name}` is replaced with the name of the subcommand. Default is "Unknown subcommand '{name}'".
+    pub error_unknown_subcommand: Message,
+    /// Error text when a value in a file can't be used for an argument. `{name}` is replaced
+    /// with the argument name and `{value}` with the value.
+    /// Default is "Invalid value '{value}' for argument '{name}'".
+    pub error_invalid_value: Message,
+    /// Error text when a hook command fails. `{command}` is replaced with the command.
+    /// Default is "Hook '{command}' failed".
+    pub error_hook_failed: Message,
+    /// Error text when a value has a placeholder that doesn't exist, see
+    /// [`crate::Settings::enable_substitution`]. `{placeholder}` is replaced with it.
+    /// Default is "Unknown placeholder '{placeholder}'".
+    pub error_unknown_placeholder: Message,
+    /// Text for the arguments tab. Default is "Arguments".
+    pub arguments: String,
+    /// Text for the environment variables tab. Default is "Environment variables".
+    pub env_variables: String,
+    /// Error displayed when user tries to pass an environment variable with no name.
+    /// Default is "Environment variable can't be empty".
+    pub error_env_var_cant_be_empty: String,
+    /// Text for the help tab, see [`crate::Settings::enable_help_tab`]. Default is "Help".
+    pub help: String,
+    /// Text for the config file tab, see [`crate::Settings::config_arg`]. Default is "Config file".
+    pub config_file: String,
+    /// Button text for loading the config file. Default is "Load".
+    pub load: String,
+    /// Button text for saving the config file. Default is "Save".
+    pub save: String,
+    /// Text in the config file tab when the selected subcommands don't take a config file.
+    /// Default is "The selected subcommand doesn't take a config file".
+    pub config_file_unavailable: String,
+    /// Shown in the config file tab when the path changes while the text has unsaved edits.
+    /// Default is "The path changed, but the edits to the previous file aren't saved".
+    pub unsaved_config_edits: String,
+    /// Button that saves the edits to the previous config file. `{path}` is replaced with its
+    /// path. Default is "Save them to '{path}'".
+    pub save_to_previous: Message,
+    /// Button that drops the edits and loads the new config file. Default is "Discard them".
+    pub discard_edits: String,
+    /// Button that keeps the edits, to save them to the new config file.
+    /// Default is "Keep them for the new file".
+    pub keep_edits: String,
+    /// Label of the controls for switching between the outputs of previous runs,
+    /// see [`crate::Settings::output_history`]. `{current}` is replaced with the number
+    /// of the shown run and `{total}` with the number of runs. Default is "Run {current} of {total}".
+    pub run_history: Message,
+    /// Toggle for comparing the outputs of two runs. Default is "Compare".
+    pub compare_runs: String,
+    /// Text for the tab with saved run records, see [`crate::Settings::run_records`].
+    /// Default is "Past runs".
+    pub run_records: String,
+    /// Header of the statistics of saved runs. Default is "Statistics".
+    pub statistics: String,
+    /// Column of run statistics. Default is "Subcommand".
+    pub subcommand: String,
+    /// Column of run statistics. Default is "Runs".
+    pub runs: String,
+    /// Column of run statistics. Default is "Success rate".
+    pub success_rate: String,
+    /// Column of run statistics. Default is "Average".
+    pub average: String,
+    /// Column of run statistics. Default is "Median".
+    pub median: String,
+    /// Column of run statistics. Default is "95th percentile".
+    pub percentile_95: String,
+    /// Label of the exit code in run records. Default is "Exit code".
+    pub exit_code: String,
+    /// Label of the duration in run records. Default is "Duration".
+    pub duration: String,
+    /// Header of the saved output in run records. Default is "Output".
+    pub output: String,
+    /// Text for the input tab. Default is "Input".
+    pub input: String,
+    /// Text for the button when user wants to write text for input in the input tab. Default is "Text".
+    pub text: String,
+    /// Text for the button when user wants to select file for input in the input tab. Default is "File".
+    pub file: String,
+    /// Shown when hovering over the drop-down for choosing the syntax of stdin text.
+    /// Default is "Syntax highlighting".
+    pub syntax_highlighting: String,
+    /// Syntax of stdin text that isn't highlighted. Default is "Plain text".
+    pub plain_text: String,
+    /// Button text for replacing the stdin text, or the path of the stdin file,
+    /// with the text in the clipboard. Default is "Paste clipboard".
+    pub paste_clipboard: String,
+    /// Text displayed as a hint for the working directory field. Default is "Working directory".
+    pub working_directory: String,
+    /// Shown when hovering over relative paths of arguments when the working directory is set.
+    /// `{path}` is replaced with the path they resolve to. Default is "Resolves to '{path}'".
+    pub resolves_to: Message,
+    /// Shown when hovering over the warning next to paths of arguments that don't exist
+    /// in the working directory. `{path}` is replaced with the resolved path.
+    /// Default is "'{path}' doesn't exist".
+    pub path_doesnt_exist: Message,
+    /// Button text for running the binary. Default is "Run".
+    pub run: String,
+    /// Button text for repeating the last run with the same arguments and settings (`Ctrl+R`).
+    /// Default is "Re-run".
+    pub rerun: String,
+    /// Tooltip of the button for pinning the last run to the favourites bar,
+    /// see [`crate::Settings::enable_favourites`]. Default is "Pin to favourites".
+    pub pin: String,
+    /// Context menu entry for removing a run from the favourites bar. Default is "Unpin".
+    pub unpin: String,
+    /// Button text for killing the binary. Default is "Kill".
+    pub kill: String,
+    /// Button text for asking the binary to stop, see [`crate::is_cancelled`]. Default is "Stop gracefully".
+    pub stop_gracefully: String,
+    /// Text that shows when the binary is running. There will be animated dots ("...") displayed after it.
+    /// Default is "Running".
+    pub running: String,
+    /// Text that shows while the arguments are validated, before the binary starts. Default is "Validating".
+    pub validating: String,
+    /// Text that shows while the input passed in the input tab is written to the binary.
+    /// `{progress}` is replaced with how much was written, like "42%".
+    /// Default is "Feeding input ({progress})".
+    pub feeding_input: Message,
+    /// Notification text when the binary exits successfully, see [`crate::Settings::notify_on_finish`].
+    /// `{duration}` is replaced with how long it ran. Default is "Finished in {duration}".
+    pub notification_success: Message,
+    /// Notification text when the binary fails, see [`crate::Settings::notify_on_finish`].
+    /// `{duration}` is replaced with how long it ran. Default is "Failed after {duration}".
+    pub notification_failure: Message,
+    /// Text of the dialog shown when closing the window while the binary is running,
+    /// see [`crate::Settings::confirm_on_close`]. Default is "A run is in progress. Kill it and exit?".
+    pub close_confirmation: String,
+    /// Button text for closing the window anyway. Default is "Kill and exit".
+    pub kill_and_exit: String,
+    /// Button text for cancelling an action. Default is "Cancel".
+    pub cancel: String,
+    /// Button text for resetting every argument, environment variable, input and
+    /// working directory. Default is "Reset all".
+    pub reset_all: String,
+    /// Text of the dialog confirming [`Localization::reset_all`].
+    /// Default is "Reset everything to the defaults?".
+    pub reset_all_confirmation: String,
+    /// Text of the dialog confirming killing the binary, see [`crate::Settings::confirm_kill`].
+    /// `{duration}` is replaced with how long it has been running.
+    /// Default is "The binary has been running for {duration}. Kill it?".
+    pub kill_confirmation: Message,
+    /// Text above the list of problems when hovering over the disabled Run button, see
+    /// [`crate::Settings::disable_run_until_valid`]. Default is "Fix these before running:".
+    pub run_blocked: String,
+    /// Checkbox text for showing the flags of arguments, see [`crate::Settings::flag_names`].
+    /// Default is "Show flags".
+    pub show_flags: String,
+    /// Label for the aliases of an argument, shown when hovering over its name.
+    /// Default is "Aliases".
+    pub aliases: String,
+    /// Text for the terminal tab, see [`crate::Settings::enable_terminal`]. Default is "Terminal".
+    pub terminal: String,
+    /// Button text for running the binary in the terminal tab. Default is "Run in terminal".
+    pub run_in_terminal: String,
+    /// Hint of the field for sending input to the running binary. Default is "Input, sent with Enter".
+    pub send_input: String,
+    /// Button text for closing the input of the running binary. Default is "End input".
+    pub end_input: String,
+    /// Text for the batch tab, see [`crate::Settings::enable_batch`]. Default is "Batch".
+    pub batch: String,
+    /// Label of the number of batch runs at the same time. Default is "Parallel runs".
+    pub parallel_runs: String,
+    /// Button text for starting a batch. Default is "Run batch".
+    pub run_batch: String,
+    /// Header of the output of hooks, see [`crate::Settings::pre_run`]. Default is "Hooks".
+    pub hooks: String,
+    /// Label of the command with placeholders expanded. Default is "Command".
+    pub command_preview: String,
+    /// Text for the preferences tab, see [`crate::Settings::enable_preferences`]. Default is "Preferences".
+    pub preferences: String,
+    /// Label of the theme preference. Default is "Theme".
+    pub theme: String,
+    /// Name of the choice that keeps what the author of the app set. Default is "Default".
+    pub default: String,
+    /// Name of the light theme. Default is "Light".
+    pub theme_light: String,
+    /// Name of the dark theme. Default is "Dark".
+    pub theme_dark: String,
+    /// Name of the high-contrast theme, see [`crate::Settings::high_contrast`]. Default is "High contrast".
+    pub theme_high_contrast: String,
+    /// Label of the preference for the colors of the output, see [`crate::Settings::ansi_palette`].
+    /// Default is "Output colors".
+    pub ansi_palette: String,
+    /// Name of the colors of most terminals. Default is "Standard".
+    pub palette_standard: String,
+    /// Name of the colors for color blindness. Default is "Colorblind-safe".
+    pub palette_deuteranopia: String,
+    /// Label of the UI scale preference. Default is "UI scale".
+    pub ui_scale: String,
+    /// Label of the language preference. Default is "Language".
+    pub language: String,
+    /// Text next to a preference that's applied after restarting. Default is "Applied after restarting".
+    pub restart_to_apply: String,
+    /// Label of the output font size preference. Default is "Output font size".
+    pub output_font_size: String,
+    /// Label of the preference for notifications when a run finishes. Default is "Notify when finished".
+    pub notify_on_finish: String,
+    /// Button text for resetting the preferences. Default is "Reset preferences".
+    pub reset_preferences: String,
+    /// Text above every step of the wizard. `{current}` is replaced with the number
+    /// of the step and `{total}` with the number of steps. Default is "Step {current} of {total}".
+    pub wizard_step: Message,
+    /// Button text for the previous step of the wizard, see [`crate::Settings::enable_wizard`]. Default is "Back".
+    pub back: String,
+    /// Button text for the next step of the wizard. Default is "Next".
+    pub next: String,
+    /// Title of the last step of the wizard, which shows the command. Default is "Review".
+    pub review: String,
+    /// Title of the section with the panic of the binary, see [`crate::Settings::capture_panics`].
+    /// Default is "The program crashed".
+    pub program_crashed: String,
+    /// Header of the backtrace of a panic. Default is "Backtrace".
+    pub backtrace: String,
+    /// Countdown before closing the window, see [`crate::Settings::close_on_success`].
+    /// Default is "Closing in {count} second" and "Closing in {count} seconds".
+    pub closing_in: Message,
+    /// Text for the file menu, see [`crate::Settings::enable_menu_bar`]. Default is "File".
+    pub menu_file: String,
+    /// Text for the run menu. Default is "Run".
+    pub menu_run: String,
+    /// Text for the view menu. Default is "View".
+    pub menu_view: String,
+    /// Text for the help menu. Default is "Help".
+    pub menu_help: String,
+    /// Menu entry for saving shell completions of the binary to a file.
+    /// Default is "Export shell completions".
+    pub export_completions: String,
+    /// Menu entry for saving a man page of the binary to a file. Default is "Export man page".
+    pub export_man_page: String,
+    /// Menu entry for closing the window. Default is "Quit".
+    pub quit: String,
+    /// Menu entry for switching to the light theme. Default is "Light theme".
+    pub light_theme: String,
+    /// Menu entry for switching to the dark theme. Default is "Dark theme".
+    pub dark_theme: String,
+    /// Menu entry for zooming in. Default is "Zoom in".
+    pub zoom_in: String,
+    /// Menu entry for zooming out. Default is "Zoom out".
+    pub zoom_out: String,
+    /// Menu entry for resetting the zoom. Default is "Reset zoom".
+    pub reset_zoom: String,
+    /// Menu entry and title of the window with information about the binary. Default is "About".
+    pub about: String,
+    /// Text of the dialog asking which argument to fill with files dropped onto the window.
+    /// Default is "Fill which argument with the dropped files?".
+    pub drop_files_into: String,
+    /// Checkbox for saving the output to a log file, see [`crate::Settings::log_dir`].
+    /// Default is "Save output to a log file".
+    pub save_log: String,
+    /// Checkbox for recording the input and output of runs, see [`crate::Settings::session_dir`].
+    /// Default is "Record session".
+    pub record_session: String,
+    /// Button text for choosing a recorded session and playing back its output.
+    /// Default is "Replay session...".
+    pub replay_session: String,
+    /// Shown when hovering over the drop-down for choosing how fast sessions are replayed.
+    /// Default is "Replay speed".
+    pub replay_speed: String,
+    /// Hint text for the search field of the command palette (opened with `Ctrl+P`).
+    /// Default is "Jump to argument or subcommand...".
+    pub palette_hint: String,
+    /// Menu for copying the output to the clipboard. Default is "Copy output".
+    pub copy_output: String,
+    /// Menu entry for copying the output without colors. Default is "Plain text".
+    pub copy_plain_text: String,
+    /// Menu entry for copying the output with ANSI escape codes. Default is "Text with ANSI colors".
+    pub copy_ansi_text: String,
+    /// Menu entry for copying the output as HTML. Default is "HTML with colors".
+    pub copy_html: String,
+    /// Toggle for showing the raw text of the output next to everything else. Default is "Split view".
+    pub split_view: String,
+    /// Shown when hovering over the split view toggle.
+    /// Default is "Raw text next to progress, tables and other widgets".
+    pub split_view_hover: String,
+    /// Shown when hovering over the button below running progress bars that scrolls to the end
+    /// of the output. Default is "Scroll to the end".
+    pub scroll_to_end: String,
+    /// Button text for showing a block of CSV or TSV in the output as a table.
+    /// Default is "View as table".
+    pub view_as_table: String,
+    /// Button text for showing a table in the output as text again. Default is "View as text".
+    pub view_as_text: String,
+    /// Shown when hovering over the level of a line of output, which filters the output by it.
+    /// `{level}` is replaced with the level. Default is "Show only {level} lines".
+    pub show_only_level: Message,
+    /// Shown when hovering over the level of a line of output while it filters the output.
+    /// Default is "Show all lines".
+    pub show_all_lines: String,
+    /// Button text for removing the filter by a level of the output. `{level}` is replaced
+    /// with the level. Default is "{level} only".
+    pub level_only: Message,
+    /// Context menu entry for copying the path of a value in JSON in the output.
+    /// Default is "Copy path".
+    pub copy_json_path: String,
+    /// Context menu entry for copying a value in JSON in the output. Default is "Copy value".
+    pub copy_json_value: String,
+    /// Error text when the thread validating the arguments stops without a result.
+    /// Default is "Validation stopped unexpectedly".
+    pub error_validation_stopped: String,
+    /// Error text when the thread running hook commands stops without a result.
+    /// Default is "Hooks stopped unexpectedly".
+    pub error_hooks_stopped: String,
+    /// Display names for possible values, keyed by the argument id and the raw value, so
+    /// arguments sharing a value can name it differently. The raw value is still passed
+    /// to the binary. Values without an entry are displayed as they are.
+    /// ```
+    /// # use klask::Localization;
+    /// let mut loc = Localization::default();
+    /// loc.possible_value_names.insert(
+    ///     ("region".into(), "eu-west-1".into()),
+    ///     "Europe (Ireland)".into(),
+    /// );
+    /// ```
+    pub possible_value_names: HashMap<(String, String), String>,
+    /// Rule for choosing between plural forms of a [`Message`]. Default is [`PluralRule::OneOther`].
+    pub plural_rule: PluralRule,
+}
+
+impl Localization {
+    /// Picks a built-in language pack matching the system locale, falling back to english.
+    /// Built-in languages are english and polish.
+    /// ```no_run
+    /// # use klask::{Localization, Settings};
+    /// let mut settings = Settings::default();
+    /// settings.localization = Localization::system_default();
+    /// ```
+    pub fn system_default() -> Self {
+        sys_locale::get_locale()
+            .map(|locale| Self::from_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// Picks a built-in language pack for a locale like `pl_PL.UTF-8` or `pl-PL`,
+    /// falling back to english.
+    /// ```
+    /// # use klask::Localization;
+    /// assert_eq!(Localization::from_locale("pl_PL.UTF-8"), Localization::polish());
+    /// assert_eq!(Localization::from_locale("C"), Localization::default());
+    /// ```
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match language.as_str() {
+            "pl" => Self::polish(),
+            _ => Self::default(),
+        }
+    }
+
+    /// Built-in polish language pack.
+    pub fn polish() -> Self {
+        Self {
+            optional: "(Opcjonalne)".into(),
+            select_file: "Wybierz plik...".into(),
+            select_directory: "Wybierz folder...".into(),
+            bookmarks: "Zakładki".into(),
+            new_value: "Nowa wartość".into(),
+            paste_list: "Wklej listę".into(),
+            reset: "Wyczyść".into(),
+            reset_to_default: "Przywróć domyślną".into(),
+            error_is_required: "Argument '{name}' jest wymagany".into(),
+            error_hyphen_values:
+                "Argument '{name}' nie może przyjąć kilku wartości zaczynających się od '-'".into(),
+            error_unknown_argument: "Nieznany argument '{name}'".into(),
+            error_unknown_subcommand: "Nieznane podpolecenie '{name}'".into(),
+            error_invalid_value: "Nieprawidłowa wartość '{value}' argumentu '{name}'".into(),
+            error_hook_failed: "Polecenie '{command}' nie powiodło się".into(),
+            error_unknown_placeholder: "Nieznany symbol zastępczy '{placeholder}'".into(),
+            arguments: "Argumenty".into(),
+            env_variables: "Zmienne środowiskowe".into(),
+            error_env_var_cant_be_empty: "Zmienna środowiskowa nie może być pusta".into(),
+            input: "Wejście".into(),
+            help: "Pomoc".into(),
+            config_file: "Plik konfiguracyjny".into(),
+            run_history: "Uruchomienie {current} z {total}".into(),
+            compare_runs: "Porównaj".into(),
+            run_records: "Poprzednie uruchomienia".into(),
+            statistics: "Statystyki".into(),
+            subcommand: "Podkomenda".into(),
+            runs: "Uruchomienia".into(),
+            success_rate: "Odsetek sukcesów".into(),
+            average: "Średnia".into(),
+            median: "Mediana".into(),
+            percentile_95: "95. percentyl".into(),
+            exit_code: "Kod wyjścia".into(),
+            duration: "Czas trwania".into(),
+            output: "Wynik".into(),
+            load: "Wczytaj".into(),
+            save: "Zapisz".into(),
+            config_file_unavailable: "Wybrana podkomenda nie przyjmuje pliku konfiguracyjnego"
+                .into(),
+            unsaved_config_edits:
+                "Ścieżka się zmieniła, ale zmiany w poprzednim pliku nie są zapisane".into(),
+            save_to_previous: "Zapisz je w '{path}'".into(),
+            discard_edits: "Odrzuć je".into(),
+            keep_edits: "Zachowaj je dla nowego pliku".into(),
+            text: "Tekst".into(),
+            file: "Plik".into(),
+            syntax_highlighting: "Podświetlanie składni".into(),
+            plain_text: "Zwykły tekst".into(),
+            paste_clipboard: "Wklej ze schowka".into(),
+            working_directory: "Katalog roboczy".into(),
+            resolves_to: "Wskazuje na '{path}'".into(),
+            path_doesnt_exist: "'{path}' nie istnieje".into(),
+            run: "Uruchom".into(),
+            rerun: "Uruchom ponownie".into(),
+            pin: "Przypnij do ulubionych".into(),
+            unpin: "Odepnij".into(),
+            kill: "Zakończ".into(),
+            stop_gracefully: "Zatrzymaj łagodnie".into(),
+            running: "Działa".into(),
+            validating: "Sprawdzanie".into(),
+            feeding_input: "Przekazywanie wejścia ({progress})".into(),
+            notification_success: "Zakończono po {duration}".into(),
+            notification_failure: "Błąd po {duration}".into(),
+            close_confirmation: "Program jest uruchomiony. Zakończyć go i wyjść?".into(),
+            kill_and_exit: "Zakończ i wyjdź".into(),
+            cancel: "Anuluj".into(),
+            reset_all: "Wyczyść wszystko".into(),
+            reset_all_confirmation: "Przywrócić wszystko do wartości domyślnych?".into(),
+            kill_confirmation: "Program działa od {duration}. Zakończyć go?".into(),
+            run_blocked: "Przed uruchomieniem popraw:".into(),
+            show_flags: "Pokaż flagi".into(),
+            aliases: "Aliasy".into(),
+            terminal: "Terminal".into(),
+            run_in_terminal: "Uruchom w terminalu".into(),
+            send_input: "Wejście, wysyłane Enterem".into(),
+            end_input: "Zakończ wejście".into(),
+            batch: "Seria".into(),
+            parallel_runs: "Równoległe uruchomienia".into(),
+            run_batch: "Uruchom serię".into(),
+            hooks: "Polecenia pomocnicze".into(),
+            command_preview: "Polecenie".into(),
+            preferences: "Preferencje".into(),
+            theme: "Motyw".into(),
+            default: "Domyślny".into(),
+            theme_light: "Jasny".into(),
+            theme_dark: "Ciemny".into(),
+            theme_high_contrast: "Wysoki kontrast".into(),
+            ansi_palette: "Kolory wyjścia".into(),
+            palette_standard: "Standardowe".into(),
+            palette_deuteranopia: "Dla daltonistów".into(),
+            ui_scale: "Skala interfejsu".into(),
+            language: "Język".into(),
+            restart_to_apply: "Zostanie zastosowany po ponownym uruchomieniu".into(),
+            output_font_size: "Rozmiar czcionki wyniku".into(),
+            notify_on_finish: "Powiadamiaj o zakończeniu".into(),
+            reset_preferences: "Przywróć domyślne preferencje".into(),
+            wizard_step: "Krok {current} z {total}".into(),
+            back: "Wstecz".into(),
+            next: "Dalej".into(),
+            review: "Podsumowanie".into(),
+            program_crashed: "Program uległ awarii".into(),
+            backtrace: "Ślad stosu".into(),
+            closing_in: Message::plural([
+                "Zamknięcie za {count} sekundę",
+                "Zamknięcie za {count} sekundy",
+                "Zamknięcie za {count} sekund",
+            ]),
+            menu_file: "Plik".into(),
+            menu_run: "Uruchamianie".into(),
+            menu_view: "Widok".into(),
+            menu_help: "Pomoc".into(),
+            export_completions: "Eksportuj uzupełnianie powłoki".into(),
+            export_man_page: "Eksportuj stronę man".into(),
+            quit: "Wyjdź".into(),
+            light_theme: "Jasny motyw".into(),
+            dark_theme: "Ciemny motyw".into(),
+            zoom_in: "Powiększ".into(),
+            zoom_out: "Pomniejsz".into(),
+            reset_zoom: "Przywróć powiększenie".into(),
+            about: "O programie".into(),
+            drop_files_into: "Które pole wypełnić upuszczonymi plikami?".into(),
+            save_log: "Zapisz wynik do pliku".into(),
+            record_session: "Nagrywaj sesję".into(),
+            replay_session: "Odtwórz sesję...".into(),
+            replay_speed: "Prędkość odtwarzania".into(),
+            palette_hint: "Przejdź do argumentu lub podkomendy...".into(),
+            copy_output: "Kopiuj wynik".into(),
+            copy_plain_text: "Zwykły tekst".into(),
+            copy_ansi_text: "Tekst z kolorami ANSI".into(),
+            copy_html: "HTML z kolorami".into(),
+            split_view: "Podziel widok".into(),
+            split_view_hover: "Surowy tekst obok postępu, tabel i innych elementów".into(),
+            scroll_to_end: "Przewiń na koniec".into(),
+            view_as_table: "Pokaż jako tabelę".into(),
+            view_as_text: "Pokaż jako tekst".into(),
+            show_only_level: "Pokaż tylko wiersze {level}".into(),
+            show_all_lines: "Pokaż wszystkie wiersze".into(),
+            level_only: "Tylko {level}".into(),
+            copy_json_path: "Kopiuj ścieżkę".into(),
+            copy_json_value: "Kopiuj wartość".into(),
+            error_validation_stopped: "Walidacja nieoczekiwanie się zatrzymała".into(),
+            error_hooks_stopped: "Polecenia pomocnicze nieoczekiwanie się zatrzymały".into(),
+            possible_value_names: HashMap::new(),
+            plural_rule: PluralRule::Polish,
+        }
+    }
+
+    pub(crate) fn possible_value_name<'a>(&'a self, arg_id: &str, value: &'a str) -> &'a str {
+        self.possible_value_names
+            .get(&(arg_id.to_string(), value.to_string()))
+            .map_or(value, String::as_str)
+    }
+
+    /// Formats a message that depends on a count, choosing the plural form with
+    /// [`Localization::plural_rule`]. `{count}` is replaced with the count.
+    /// ```
+    /// # use klask::{Localization, Message, PluralRule};
+    /// let mut loc = Localization::default();
+    /// loc.plural_rule = PluralRule::Polish;
+    /// let files = Message::plural(["{count} plik", "{count} pliki", "{count} plików"]);
+    /// assert_eq!(loc.format_count(&files, 1, &[]), "1 plik");
+    /// assert_eq!(loc.format_count(&files, 22, &[]), "22 pliki");
+    /// assert_eq!(loc.format_count(&files, 12, &[]), "12 plików");
+    /// ```
+    pub fn format_count(&self, message: &Message, count: u64, params: &[(&str, &str)]) -> String {
+        let form = self.plural_rule.form(count);
+        message.format_form(form, &[params, &[("count", &count.to_string())]].concat())
+    }
+}
+
+/// A localizable message. Parts of the text in braces, like `{name}`, are replaced
+/// with values when the message is displayed. Which placeholders are available is
+/// listed in the documentation of each [`Localization`] field.
+///
+/// Messages that depend on a number can have multiple plural forms, which are chosen
+/// with [`Localization::plural_rule`]. In that case `{count}` is also available.
+/// ```
+/// # use klask::{Localization, Message, PluralRule};
+/// let mut loc = Localization::default();
+/// loc.error_is_required = "Argument '{name}' jest wymagany".into();
+/// loc.plural_rule = PluralRule::Polish;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    forms: Vec<String>,
+}
+
+impl Message {
+    /// Message with a single form.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            forms: vec![text.into()],
+        }
+    }
+
+    /// Message with plural forms, in the order given by the [`PluralRule`].
+    /// If there are fewer forms than the rule uses, the last form is used for the rest.
+    pub fn plural(forms: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let forms: Vec<String> = forms.into_iter().map(Into::into).collect();
+        assert!(!forms.is_empty(), "Message must have at least one form");
+        Self { forms }
+    }
+
+    /// Replaces placeholders with values, using the first form.
+    /// ```
+    /// # use klask::Message;
+    /// let message = Message::new("Argument '{name}' is required");
+    /// assert_eq!(message.format(&[("name", "Input")]), "Argument 'Input' is required");
+    /// ```
+    pub fn format(&self, params: &[(&str, &str)]) -> String {
+        self.format_form(0, params)
+    }
+
+    fn format_form(&self, form: usize, params: &[(&str, &str)]) -> String {
+        let mut text = self.forms[form.min(self.forms.len() - 1)].clone();
+        for (key, value) in params {
+            text = text.replace(&format!("{{{}}}", key), value);
+        }
+        text
+    }
+}
+
+impl From<&str> for Message {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Message {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Rule for choosing a plural form of a [`Message`] from a count.
+/// Forms are numbered in the order they are listed here.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PluralRule {
+    /// No plural forms, e.g. Chinese, Japanese, Korean.
+    NoPlural,
+    /// One, other. E.g. English, German, Spanish, Italian.
+    OneOther,
+    /// Zero and one, other. E.g. French, Brazilian Portuguese.
+    ZeroOneOther,
+    /// One, few (2-4, 22-24, ...), many. Polish.
+    Polish,
+    /// One (1, 21, ...), few (2-4, 22-24, ...), many. E.g. Russian, Ukrainian.
+    EastSlavic,
+    /// One, few (2-4), other. E.g. Czech, Slovak.
+    Czech,
+}
+
+impl PluralRule {
+    fn form(self, n: u64) -> usize {
+        let few = (2..=4).contains(&(n % 10)) && !(12..=14).contains(&(n % 100));
+        match self {
+            PluralRule::NoPlural => 0,
+            PluralRule::OneOther => usize::from(n != 1),
+            PluralRule::ZeroOneOther => usize::from(n > 1),
+            PluralRule::Polish if n == 1 => 0,
+            PluralRule::Polish if few => 1,
+            PluralRule::Polish => 2,
+            PluralRule::EastSlavic if n % 10 == 1 && n % 100 != 11 => 0,
+            PluralRule::EastSlavic if few => 1,
+            PluralRule::EastSlavic => 2,
+            PluralRule::Czech if n == 1 => 0,
+            PluralRule::Czech if (2..=4).contains(&n) => 1,
+            PluralRule::Czech => 2,
+        }
+    }
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        Self {
+            optional: "(Optional)".into(),
+            select_file: "Select file...".into(),
+            select_directory: "Select directory...".into(),
+            bookmarks: "Bookmarks".into(),
+            new_value: "New value".into(),
+            paste_list: "Paste list".into(),
+            reset: "Reset".into(),
+            reset_to_default: "Reset to default".into(),
+            error_is_required: "Argument '{name}' is required".into(),
+            error_hyphen_values: "Argument '{name}' can't take several values starting with '-'"
+                .into(),
+            error_unknown_argument: "Unknown argument '{name}'".into(),
+            error_unknown_subcommand: "Unknown subcommand '{name}'".into(),
+            error_invalid_value: "Invalid value '{value}' for argument '{name}'".into(),
+            error_hook_failed: "Hook '{command}' failed".into(),
+            error_unknown_placeholder: "Unknown placeholder '{placeholder}'".into(),
+            arguments: "Arguments".into(),
+            env_variables: "Environment variables".into(),
+            error_env_var_cant_be_empty: "Environment variable can't be empty".into(),
+            input: "Input".into(),
+            help: "Help".into(),
+            config_file: "Config file".into(),
+            run_history: "Run {current} of {total}".into(),
+            compare_runs: "Compare".into(),
+            run_records: "Past runs".into(),
+            statistics: "Statistics".into(),
+            subcommand: "Subcommand".into(),
+            runs: "Runs".into(),
+            success_rate: "Success rate".into(),
+            average: "Average".into(),
+            median: "Median".into(),
+            percentile_95: "95th percentile".into(),
+            exit_code: "Exit code".into(),
+            duration: "Duration".into(),
+            output: "Output".into(),
+            load: "Load".into(),
+            save: "Save".into(),
+            config_file_unavailable: "The selected subcommand doesn't take a config file".into(),
+            unsaved_config_edits:
+                "The path changed, but the edits to the previous file aren't saved".into(),
+            save_to_previous: "Save them to '{path}'".into(),
+            discard_edits: "Discard them".into(),
+            keep_edits: "Keep them for the new file".into(),
+            text: "Text".into(),
+            file: "File".into(),
+            syntax_highlighting: "Syntax highlighting".into(),
+            plain_text: "Plain text".into(),
+            paste_clipboard: "Paste clipboard".into(),
+            working_directory: "Working directory".into(),
+            resolves_to: "Resolves to '{path}'".into(),
+            path_doesnt_exist: "'{path}' doesn't exist".into(),
+            run: "Run".into(),
+            rerun: "Re-run".into(),
+            pin: "Pin to favourites".into(),
+            unpin: "Unpin".into(),
+            kill: "Kill".into(),
+            stop_gracefully: "Stop gracefully".into(),
+            running: "Running".into(),
+            validating: "Validating".into(),
+            feeding_input: "Feeding input ({progress})".into(),
+            notification_success: "Finished in {duration}".into(),
+            notification_failure: "Failed after {duration}".into(),
+            close_confirmation: "A run is in progress. Kill it and exit?".into(),
+            kill_and_exit: "Kill and exit".into(),
+            cancel: "Cancel".into(),
+            reset_all: "Reset all".into(),
+            reset_all_confirmation: "Reset everything to the defaults?".into(),
+            kill_confirmation: "The binary has been running for {duration}. Kill it?".into(),
+            run_blocked: "Fix these before running:".into(),
+            show_flags: "Show flags".into(),
+            aliases: "Aliases".into(),
+            terminal: "Terminal".into(),
+            run_in_terminal: "Run in terminal".into(),
+            send_input: "Input, sent with Enter".into(),
+            end_input: "End input".into(),
+            batch: "Batch".into(),
+            parallel_runs: "Parallel runs".into(),
+            run_batch: "Run batch".into(),
+            hooks: "Hooks".into(),
+            command_preview: "Command".into(),
+            preferences: "Preferences".into(),
+            theme: "Theme".into(),
+            default: "Default".into(),
+            theme_light: "Light".into(),
+            theme_dark: "Dark".into(),
+            theme_high_contrast: "High contrast".into(),
+            ansi_palette: "Output colors".into(),
+            palette_standard: "Standard".into(),
+            palette_deuteranopia: "Colorblind-safe".into(),
+            ui_scale: "UI scale".into(),
+            language: "Language".into(),
+            restart_to_apply: "Applied after restarting".into(),
+            output_font_size: "Output font size".into(),
+            notify_on_finish: "Notify when finished".into(),
+            reset_preferences: "Reset preferences".into(),
+            wizard_step: "Step {current} of {total}".into(),
+            back: "Back".into(),
+            next: "Next".into(),
+            review: "Review".into(),
+            program_crashed: "The program crashed".into(),
+            backtrace: "Backtrace".into(),
+            closing_in: Message::plural([
+                "Closing in {count} second",
+                "Closing in {count} seconds",
+            ]),
+            menu_file: "File".into(),
+            menu_run: "Run".into(),
+            menu_view: "View".into(),
+            menu_help: "Help".into(),
+            export_completions: "Export shell completions".into(),
+            export_man_page: "Export man page".into(),
+            quit: "Quit".into(),
+            light_theme: "Light theme".into(),
+            dark_theme: "Dark theme".into(),
+            zoom_in: "Zoom in".into(),
+            zoom_out: "Zoom out".into(),
+            reset_zoom: "Reset zoom".into(),
+            about: "About".into(),
+            drop_files_into: "Fill which argument with the dropped files?".into(),
+            save_log: "Save output to a log file".into(),
+            record_session: "Record session".into(),
+            replay_session: "Replay session...".into(),
+            replay_speed: "Replay speed".into(),
+            palette_hint: "Jump to argument or subcommand...".into(),
+            copy_output: "Copy output".into(),
+            copy_plain_text: "Plain text".into(),
+            copy_ansi_text: "Text with ANSI colors".into(),
+            copy_html: "HTML with colors".into(),
+            split_view: "Split view".into(),
+            split_view_hover: "Raw text next to progress, tables and other widgets".into(),
+            scroll_to_end: "Scroll to the end".into(),
+            view_as_table: "View as table".into(),
+            view_as_text: "View as text".into(),
+            show_only_level: "Show only {level} lines".into(),
+            show_all_lines: "Show all lines".into(),
+            level_only: "{level} only".into(),
+            copy_json_path: "Copy path".into(),
+            copy_json_value: "Copy value".into(),
+            error_validation_stopped: "Validation stopped unexpectedly".into(),
+            error_hooks_stopped: "Hooks stopped unexpectedly".into(),
+            possible_value_names: HashMap::new(),
+            plural_rule: PluralRule::OneOther,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,18 @@
+use super::Localization;
+
+#[test]
+fn possible_value_names_by_arg() {
+    let mut loc = Localization::default();
+    let name = |id: &str, value: &str| (id.to_string(), value.to_string());
+    loc.possible_value_names
+        .insert(name("color", "auto"), "When it's a terminal".into());
+    loc.possible_value_names
+        .insert(name("threads", "auto"), "One per core".into());
+
+    assert_eq!(
+        loc.possible_value_name("color", "auto"),
+        "When it's a terminal"
+    );
+    assert_eq!(loc.possible_value_name("threads", "auto"), "One per core");
+    assert_eq!(loc.possible_value_name("paging", "auto"), "auto");
+}
@@ -0,0 +1,200 @@
+//! Lightweight markdown rendering for help and about text. Supports
+//! paragraphs, headings, bullet and numbered lists, fenced code blocks,
+//! inline code, emphasis and links.
+use eframe::egui::{vec2, Label, RichText, Ui};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Paragraph(String),
+    Heading(String),
+    Item { marker: String, text: String },
+    Code(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Inline {
+    Text(String),
+    Code(String),
+    Strong(String),
+    Emphasis(String),
+    Link { text: String, url: String },
+}
+
+pub fn show(ui: &mut Ui, text: &str) {
+    ui.vertical(|ui| {
+        for block in parse_blocks(text) {
+            match block {
+                Block::Paragraph(text) => show_inline(ui, &text),
+                Block::Heading(text) => {
+                    ui.label(RichText::new(text).strong());
+                }
+                Block::Item { marker, text } => {
+                    ui.horizontal(|ui| {
+                        ui.add_space(8.0);
+                        ui.label(marker);
+                        show_inline(ui, &text);
+                    });
+                }
+                Block::Code(code) => {
+                    ui.label(RichText::new(code).code());
+                }
+            }
+        }
+    });
+}
+
+fn show_inline(ui: &mut Ui, text: &str) {
+    let previous = ui.style().spacing.item_spacing;
+    ui.style_mut().spacing.item_spacing = vec2(0.0, previous.y);
+
+    ui.horizontal_wrapped(|ui| {
+        for inline in parse_inline(text) {
+            match inline {
+                Inline::Text(text) => ui.add(Label::new(text)),
+                Inline::Code(code) => ui.add(Label::new(RichText::new(code).code())),
+                Inline::Strong(text) => ui.add(Label::new(RichText::new(text).strong())),
+                Inline::Emphasis(text) => ui.add(Label::new(RichText::new(text).italics())),
+                Inline::Link { text, url } => ui.hyperlink_to(text, url),
+            };
+        }
+    });
+
+    ui.style_mut().spacing.item_spacing = previous;
+}
+
+fn parse_blocks(text: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut code: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            match code.take() {
+                Some(code) => blocks.push(Block::Code(code.trim_end().to_string())),
+                None => code = Some(String::new()),
+            }
+            continue;
+        }
+
+        if let Some(code) = &mut code {
+            code.push_str(line);
+            code.push('\n');
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            // Start a new paragraph on the next line
+            blocks.push(Block::Paragraph(String::new()));
+        } else if trimmed.trim_start_matches('#').starts_with(' ') {
+            blocks.push(Block::Heading(
+                trimmed.trim_start_matches('#').trim().to_string(),
+            ));
+        } else if let Some((marker, rest)) = list_marker(trimmed) {
+            blocks.push(Block::Item {
+                marker,
+                text: rest.to_string(),
+            });
+        } else {
+            match blocks.last_mut() {
+                Some(Block::Paragraph(text)) | Some(Block::Item { text, .. }) => {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(trimmed);
+                }
+                _ => blocks.push(Block::Paragraph(trimmed.to_string())),
+            }
+        }
+    }
+
+    if let Some(code) = code {
+        blocks.push(Block::Code(code.trim_end().to_string()));
+    }
+
+    blocks.retain(|b| !matches!(b, Block::Paragraph(text) if text.is_empty()));
+    blocks
+}
+
+fn list_marker(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            return Some(("•".into(), rest.trim_start()));
+        }
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some((line[..digits + 1].to_string(), rest.trim_start()));
+        }
+    }
+
+    None
+}
+
+fn parse_inline(text: &str) -> Vec<Inline> {
+    let mut inlines = vec![];
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let parsed = match c {
+            '`' => delimited(rest, "`", "`").map(|(inner, len)| (Inline::Code(inner.into()), len)),
+            '*' if rest.starts_with("**") => {
+                delimited(rest, "**", "**").map(|(inner, len)| (Inline::Strong(inner.into()), len))
+            }
+            // Don't treat underscores in snake_case as emphasis
+            '*' | '_' if !plain.ends_with(char::is_alphanumeric) => {
+                let delim = &rest[..1];
+                delimited(rest, delim, delim)
+                    .filter(|(inner, _)| !inner.starts_with(' '))
+                    .map(|(inner, len)| (Inline::Emphasis(inner.into()), len))
+            }
+            '[' => link(rest),
+            _ => None,
+        };
+
+        match parsed {
+            Some((inline, len)) => {
+                if !plain.is_empty() {
+                    inlines.push(Inline::Text(std::mem::take(&mut plain)));
+                }
+                inlines.push(inline);
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        inlines.push(Inline::Text(plain));
+    }
+
+    inlines
+}
+
+/// Returns the text between `open` and `close` and the length of the whole match.
+fn delimited<'a>(text: &'a str, open: &str, close: &str) -> Option<(&'a str, usize)> {
+    let inner = text.strip_prefix(open)?;
+    let end = inner.find(close)?;
+    (end > 0).then(|| (&inner[..end], open.len() + end + close.len()))
+}
+
+fn link(text: &str) -> Option<(Inline, usize)> {
+    let (label, label_len) = delimited(text, "[", "]")?;
+    let (url, url_len) = delimited(&text[label_len..], "(", ")")?;
+    Some((
+        Inline::Link {
+            text: label.to_string(),
+            url: url.to_string(),
+        },
+        label_len + url_len,
+    ))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use super::{parse_blocks, parse_inline, Block, Inline};
+
+#[test]
+fn blocks() {
+    let text = "First line\nsecond line\n\n# Heading\n- one\n  continued\n2. two\n\n```\ncode\n```";
+    assert_eq!(
+        parse_blocks(text),
+        vec![
+            Block::Paragraph("First line second line".into()),
+            Block::Heading("Heading".into()),
+            Block::Item {
+                marker: "•".into(),
+                text: "one continued".into()
+            },
+            Block::Item {
+                marker: "2.".into(),
+                text: "two".into()
+            },
+            Block::Code("code".into()),
+        ]
+    );
+}
+
+#[test]
+fn inline() {
+    assert_eq!(
+        parse_inline("Use `--flag` or **bold** _it_ [docs](https://docs.rs) snake_case_name"),
+        vec![
+            Inline::Text("Use ".into()),
+            Inline::Code("--flag".into()),
+            Inline::Text(" or ".into()),
+            Inline::Strong("bold".into()),
+            Inline::Text(" ".into()),
+            Inline::Emphasis("it".into()),
+            Inline::Text(" ".into()),
+            Inline::Link {
+                text: "docs".into(),
+                url: "https://docs.rs".into()
+            },
+            Inline::Text(" snake_case_name".into()),
+        ]
+    );
+}
@@ -0,0 +1,139 @@
+#[cfg(feature = "export")]
+use crate::export;
+use crate::{localization::Localization, markdown, preferences};
+use clap::Command;
+use eframe::egui::{self, Align2, Context, Visuals};
+use eframe::Frame;
+
+/// Something the user picked in the menu bar that the app has to handle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MenuAction {
+    Run,
+    Rerun,
+    StopGracefully,
+    Kill,
+    Quit,
+}
+
+#[derive(Debug, Default)]
+pub struct MenuBar {
+    about_open: bool,
+}
+
+impl MenuBar {
+    const ZOOM_STEP: f32 = 1.1;
+
+    /// Shows the menu bar at the top of the window and the about window if it's open.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        frame: &Frame,
+        app: &Command,
+        running: bool,
+        can_rerun: bool,
+        localization: &Localization,
+    ) -> Option<MenuAction> {
+        let mut action = None;
+
+        egui::TopBottomPanel::top("klask_menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button(&localization.menu_file, |ui| {
+                    #[cfg(feature = "export")]
+                    {
+                        ui.menu_button(&localization.export_completions, |ui| {
+                            for shell in export::SHELLS {
+                                if ui.button(shell.to_string()).clicked() {
+                                    export::export_completions(app, shell);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        if ui.button(&localization.export_man_page).clicked() {
+                            export::export_man_page(app);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                    }
+                    if ui.button(&localization.quit).clicked() {
+                        action = Some(MenuAction::Quit);
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button(&localization.menu_run, |ui| {
+                    let items = [
+                        (&localization.run, !running, MenuAction::Run),
+                        (&localization.rerun, can_rerun, MenuAction::Rerun),
+                        (
+                            &localization.stop_gracefully,
+                            running,
+                            MenuAction::StopGracefully,
+                        ),
+                        (&localization.kill, running, MenuAction::Kill),
+                    ];
+                    for (text, enabled, item) in items {
+                        if ui.add_enabled(enabled, egui::Button::new(text)).clicked() {
+                            action = Some(item);
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+                ui.menu_button(&localization.menu_view, |ui| {
+                    if ui.button(&localization.light_theme).clicked() {
+                        ctx.set_visuals(Visuals::light());
+                        ui.close_menu();
+                    }
+                    if ui.button(&localization.dark_theme).clicked() {
+                        ctx.set_visuals(Visuals::dark());
+                        ui.close_menu();
+                    }
+                    if ui.button(&localization.theme_high_contrast).clicked() {
+                        ctx.set_visuals(preferences::high_contrast());
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button(&localization.zoom_in).clicked() {
+                        ctx.set_pixels_per_point(ctx.pixels_per_point() * Self::ZOOM_STEP);
+                    }
+                    if ui.button(&localization.zoom_out).clicked() {
+                        ctx.set_pixels_per_point(ctx.pixels_per_point() / Self::ZOOM_STEP);
+                    }
+                    if ui.button(&localization.reset_zoom).clicked() {
+                        ctx.set_pixels_per_point(
+                            frame.info().native_pixels_per_point.unwrap_or(1.0),
+                        );
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button(&localization.menu_help, |ui| {
+                    if ui.button(&localization.about).clicked() {
+                        self.about_open = true;
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+
+        egui::Window::new(&localization.about)
+            .open(&mut self.about_open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.heading(app.get_name());
+                if let Some(version) = app.get_version() {
+                    ui.label(version);
+                }
+                if let Some(author) = app.get_author() {
+                    ui.label(author);
+                }
+                if let Some(about) = app.get_long_about().or_else(|| app.get_about()) {
+                    markdown::show(ui, about);
+                }
+            });
+
+        action
+    }
+}
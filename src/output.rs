@@ -1,11 +1,57 @@
 use crate::child_app::ChildApp;
 use crate::error::ExecutionError;
-use cansi::{v3::CategorisedSlice, Color, Intensity};
-use eframe::egui::{vec2, Color32, Label, ProgressBar, RichText, Ui, Widget};
-use linkify::{LinkFinder, LinkKind};
-use std::collections::hash_map::DefaultHasher;
+use crate::localization::Localization;
+use cansi::{Color, Intensity};
+use eframe::egui::{
+    vec2, Align, CollapsingHeader, Color32, Grid, Id, Label, ProgressBar, Response, RichText,
+    Sense, TextEdit, TextStyle, Ui,
+};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{hash_map::DefaultHasher, BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
 use std::io::Write;
+use std::ops::Range;
+#[cfg(feature = "images")]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+mod ansi;
+mod copy;
+#[cfg(feature = "images")]
+mod image;
+#[cfg(feature = "indicatif")]
+mod indicatif_target;
+mod json;
+mod lines;
+#[cfg(feature = "log")]
+mod logger;
+mod panic;
+#[cfg(feature = "qr")]
+mod qr;
+mod table;
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+
+#[cfg(all(unix, feature = "terminal"))]
+pub(crate) use ansi::apply_sgr;
+use ansi::Ansi;
+#[cfg(feature = "indicatif")]
+pub use indicatif_target::indicatif_draw_target;
+#[cfg(all(unix, feature = "terminal"))]
+pub(crate) use lines::Style;
+use lines::{Line, Lines, Link, Span};
+#[cfg(feature = "log")]
+pub use logger::Logger;
+use panic::Panic;
+#[cfg(feature = "qr")]
+use qr::QrCode;
+#[cfg(feature = "tracing")]
+pub use tracing_layer::tracing_subscriber;
+
+#[cfg(test)]
+mod tests;
 
 /// Displays a progress bar in the output. First call creates
 /// a progress bar and future calls update it.
@@ -50,221 +96,2217 @@ pub fn progress_bar(description: &str, value: f32) {
 /// }
 /// ```
 pub fn progress_bar_with_id(id: impl Hash, description: &str, value: f32) {
-    let mut h = DefaultHasher::new();
-    id.hash(&mut h);
-    OutputType::ProgressBar(description.to_string(), value).send(h.finish());
+    OutputType::ProgressBar {
+        description: description.to_string(),
+        value,
+        parent: None,
+        state: ProgressState::Running,
+    }
+    .send(hash(id));
 }
 
-#[derive(Debug)]
-pub(crate) enum Output {
-    None,
-    Err(ExecutionError),
-    Child(ChildApp, Vec<(u64, OutputType)>),
+/// Displays a progress bar for transferring bytes, with human-readable sizes and
+/// throughput added to the description. First call creates a progress bar and
+/// future calls update it. Throughput is measured from the first call, or from the
+/// last one with `done_bytes` of 0, so an id can be reused for another transfer.
+///
+/// Id is any hashable value that uniquely identifies a progress bar.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         let total = 10 * 1024 * 1024;
+///         for done in (0..=total).step_by(4096) {
+///             klask::output::progress_bytes("download", "Downloading", done, total);
+///         }
+///     });
+/// }
+/// ```
+pub fn progress_bytes(id: impl Hash, description: &str, done_bytes: u64, total_bytes: u64) {
+    let id = hash(id);
+    let elapsed = {
+        let mut starts = BYTES_STARTS.lock().unwrap();
+        let starts = starts.get_or_insert_with(HashMap::new);
+        if done_bytes == 0 {
+            starts.remove(&id);
+        }
+        starts
+            .entry(id)
+            .or_insert_with(Instant::now)
+            .elapsed()
+            .as_secs_f64()
+    };
+
+    let mut text = format!(
+        "{} {} / {}",
+        description,
+        format_bytes(done_bytes),
+        format_bytes(total_bytes)
+    );
+    if elapsed > 0.0 && done_bytes > 0 {
+        text.push_str(&format!(
+            " ({}/s)",
+            format_bytes((done_bytes as f64 / elapsed) as u64)
+        ));
+    }
+
+    let value = if total_bytes == 0 {
+        1.0
+    } else {
+        done_bytes as f32 / total_bytes as f32
+    };
+
+    OutputType::ProgressBar {
+        description: text,
+        value,
+        parent: None,
+        state: ProgressState::Running,
+    }
+    .send(id);
 }
 
-impl Output {
-    pub fn new_with_child(child: ChildApp) -> Self {
-        Self::Child(child, vec![])
+/// When each bar of [`progress_bytes`] started, until it finishes, fails or is removed
+static BYTES_STARTS: Mutex<Option<HashMap<u64, Instant>>> = Mutex::new(None);
+
+/// Forgets when a bar of [`progress_bytes`] started, since it's done
+fn forget_start(id: u64) {
+    if let Some(starts) = &mut *BYTES_STARTS.lock().unwrap() {
+        starts.remove(&id);
     }
 }
 
-impl Widget for &mut Output {
-    fn ui(self, ui: &mut Ui) -> eframe::egui::Response {
-        match self {
-            Output::None => ui.vertical(|_| {}).response,
-            Output::Err(err) => ui.colored_label(Color32::RED, err.to_string()),
-            Output::Child(child, output) => {
-                // Update
-                let str = child.read();
-                let mut iter = str.split(MAGIC);
-
-                if let Some(text) = iter.next() {
-                    if !text.is_empty() {
-                        output.push((0, OutputType::Text(text.to_string())));
-                    }
-                }
+/// Formats a size in bytes with binary units, like `1.5 MiB`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
 
-                while let Some(id) = iter.next() {
-                    if let Ok(id) = id.parse() {
-                        if let Some(new) = OutputType::parse(&mut iter) {
-                            if let Some((_, exists)) = output.iter_mut().find(|(i, _)| *i == id) {
-                                *exists = new;
-                            } else {
-                                output.push((id, new));
-                            }
-                        }
-                    }
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
 
-                    if let Some(text) = iter.next() {
-                        // Get rid of the newline
-                        let text = &text[1..];
-                        if !text.is_empty() {
-                            output.push((0, OutputType::Text(text.to_string())));
-                        }
-                    }
-                }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
 
-                // View
-                ui.vertical(|ui| {
-                    if ui.button("Copy output").clicked() {
-                        ui.ctx().output().copied_text = output
-                            .iter()
-                            .map(|(_, o)| match o {
-                                OutputType::Text(text) => text,
-                                OutputType::ProgressBar(text, _) => text,
-                            })
-                            .flat_map(|text| cansi::v3::categorise_text(text))
-                            .map(|slice| slice.text)
-                            .collect::<String>();
-                    }
+/// Marks a progress bar as finished. It stops animating and displays a checkmark.
+/// Finishing a group finishes the bars inside it that are still running.
+/// Id is the same as passed to [`progress_bar_with_id`], or the description for [`progress_bar`].
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         for i in 0..=100 {
+///             klask::output::progress_bar("Download", i as f32 / 100.0);
+///         }
+///         klask::output::progress_finish("Download");
+///     });
+/// }
+/// ```
+pub fn progress_finish(id: impl Hash) {
+    let id = hash(id);
+    forget_start(id);
+    OutputType::ProgressState(ProgressState::Finished).send(id);
+}
 
-                    for (_, o) in output {
-                        match o {
-                            OutputType::Text(ref text) => format_output(ui, text),
-                            OutputType::ProgressBar(ref mess, value) => {
-                                // Get rid of the ending newline
-                                ui.add(
-                                    ProgressBar::new(*value)
-                                        .text(&mess[..mess.len() - 1])
-                                        .animate(true),
-                                );
-                            }
-                        }
-                    }
-                })
-                .response
-            }
+/// Marks a progress bar as failed. It stops animating, turns red and displays the message.
+/// Failing a group fails the bars inside it that are still running.
+/// Id is the same as passed to [`progress_bar_with_id`], or the description for [`progress_bar`].
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         klask::output::progress_bar("Download", 0.3);
+///         klask::output::progress_fail("Download", "Connection lost");
+///     });
+/// }
+/// ```
+pub fn progress_fail(id: impl Hash, message: &str) {
+    let id = hash(id);
+    forget_start(id);
+    OutputType::ProgressState(ProgressState::Failed(message.to_string())).send(id);
+}
+
+/// Removes a progress bar or group from the output, for example a transient bar
+/// once its phase completes. Removing a group also removes everything inside it.
+/// Id is the same as passed to [`progress_bar_with_id`], or the description for [`progress_bar`].
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         for i in 0..=100 {
+///             klask::output::progress_bar("Preparing", i as f32 / 100.0);
+///         }
+///         klask::output::remove("Preparing");
+///     });
+/// }
+/// ```
+pub fn remove(id: impl Hash) {
+    let id = hash(id);
+    forget_start(id);
+    OutputType::Remove.send(id);
+}
+
+/// Creates or updates a group of progress bars. Bars and groups created with the
+/// returned handle are displayed indented under it, and the group itself shows
+/// their average progress.
+///
+/// Id is any hashable value that uniquely identifies a group.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         let files = klask::output::progress_group("files", "Copying files");
+///         for i in 0..=100 {
+///             files.progress_bar("a.txt", i as f32 / 100.0);
+///             files.progress_bar("b.txt", i as f32 / 200.0);
+///         }
+///     });
+/// }
+/// ```
+pub fn progress_group(id: impl Hash, description: &str) -> ProgressGroup {
+    ProgressGroup::create(hash(id), description, None)
+}
+
+/// Handle to a group created with [`progress_group`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgressGroup {
+    id: u64,
+}
+
+impl ProgressGroup {
+    fn create(id: u64, description: &str, parent: Option<u64>) -> Self {
+        OutputType::ProgressGroup {
+            description: description.to_string(),
+            parent,
+        }
+        .send(id);
+        Self { id }
+    }
+
+    /// Same as [`progress_bar`], but the bar is displayed inside this group.
+    pub fn progress_bar(&self, description: &str, value: f32) {
+        self.progress_bar_with_id(description, description, value);
+    }
+
+    /// Same as [`progress_bar_with_id`], but the bar is displayed inside this group.
+    /// Ids only need to be unique inside the group.
+    pub fn progress_bar_with_id(&self, id: impl Hash, description: &str, value: f32) {
+        OutputType::ProgressBar {
+            description: description.to_string(),
+            value,
+            parent: Some(self.id),
+            state: ProgressState::Running,
         }
+        .send(hash((self.id, id)));
+    }
+
+    /// Same as [`progress_finish`], for a bar or group inside this group.
+    pub fn finish(&self, id: impl Hash) {
+        OutputType::ProgressState(ProgressState::Finished).send(hash((self.id, id)));
+    }
+
+    /// Same as [`progress_fail`], for a bar or group inside this group.
+    pub fn fail(&self, id: impl Hash, message: &str) {
+        OutputType::ProgressState(ProgressState::Failed(message.to_string()))
+            .send(hash((self.id, id)));
+    }
+
+    /// Same as [`remove`], for a bar or group inside this group.
+    pub fn remove(&self, id: impl Hash) {
+        OutputType::Remove.send(hash((self.id, id)));
+    }
+
+    /// Creates or updates a group nested inside this group.
+    pub fn group(&self, id: impl Hash, description: &str) -> ProgressGroup {
+        Self::create(hash((self.id, id)), description, Some(self.id))
     }
 }
 
-#[derive(Debug)]
-pub(crate) enum OutputType {
-    Text(String),
-    ProgressBar(String, f32),
+/// Adds a named anchor to the output, which can be jumped to from the bookmarks menu.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         klask::output::mark("Phase 2 start");
+///     });
+/// }
+/// ```
+pub fn mark(label: &str) {
+    OutputType::Mark(label.to_string()).send(0);
 }
 
-/// Unicode non-character. Used for sending messages between GUI and user's program
-const MAGIC: char = '\u{5FFFE}';
+/// Displays a QR code of the text, for phones to scan. Hovering shows the text
+/// and clicking copies it. Text too long for a QR code, over 2331 bytes,
+/// is printed instead. Requires the `qr` feature.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         klask::output::qr_code("https://github.com/MichalGniadek/klask");
+///     });
+/// }
+/// ```
+#[cfg(feature = "qr")]
+pub fn qr_code(data: &str) {
+    match QrCode::encode(data) {
+        Some(qr) => OutputType::QrCode(Box::new(qr)).send(0),
+        None => println!("{}", data),
+    }
+}
 
-fn send_message(data: &[&str]) {
-    let stdout = std::io::stdout();
-    let mut lock = stdout.lock();
-    for d in data {
-        write!(&mut lock, "{}{}", MAGIC, d).unwrap();
+/// Displays a value as a collapsible JSON tree. Right-clicking an entry copies its path
+/// or its value. Lines of plain output that are JSON objects or arrays are shown the same way.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// #[derive(serde::Serialize)]
+/// struct Release {
+///     version: String,
+///     assets: Vec<String>,
+/// }
+///
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         klask::output::json(&Release {
+///             version: "1.0.0".into(),
+///             assets: vec!["app.tar.gz".into(), "app.zip".into()],
+///         });
+///     });
+/// }
+/// ```
+pub fn json(value: &impl serde::Serialize) {
+    match serde_json::to_value(value) {
+        Ok(value) => OutputType::Json(Box::new(value)).send(0),
+        Err(err) => OutputType::Error(err.to_string()).send(0),
     }
-    writeln!(&mut lock, "{}", MAGIC).unwrap();
 }
 
-impl OutputType {
-    const PROGRESS_BAR_STR: &'static str = "progress-bar";
+/// Sets a value in the results table, which is displayed above the rest of the output.
+/// Setting a key again updates its value in place.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// fn main() {
+///     klask::run_app(App::new("Example"), Settings::default(), |matches| {
+///         klask::output::set_result("Files copied", 120);
+///         klask::output::set_result("Total size", "1.2 GB");
+///     });
+/// }
+/// ```
+pub fn set_result(key: &str, value: impl std::fmt::Display) {
+    OutputType::Result {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+    .send(hash((OutputType::RESULT_STR, key)));
+}
 
-    pub fn send(self, id: u64) {
-        // Make sure to get rid of any newlines
-        match self {
-            Self::Text(s) => print!("{}", s),
-            Self::ProgressBar(desc, value) => send_message(&[
-                &id.to_string(),
-                Self::PROGRESS_BAR_STR,
-                &desc.replace('\n', " "),
-                &value.to_string(),
-            ]),
+/// Sends a message of a custom type, which is displayed by the [`Renderer`] registered
+/// for `kind` in [`crate::Settings::output_renderers`]. Messages of types without
+/// a renderer are displayed as text.
+///
+/// If the message should be updated later, use [`custom_with_id`].
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::{output::Renderer, Settings};
+/// fn main() {
+///     let mut settings = Settings::default();
+///     settings.output_renderers.insert(
+///         "gauge".into(),
+///         Renderer::new(|ui, data| {
+///             let value: f32 = data[1].parse().unwrap_or_default();
+///             ui.add(eframe::egui::ProgressBar::new(value).text(&data[0]));
+///         }),
+///     );
+///     klask::run_app(App::new("Example"), settings, |matches| {
+///         klask::output::custom("gauge", &["Pressure", "0.7"]);
+///     });
+/// }
+/// ```
+pub fn custom(kind: &str, data: &[&str]) {
+    OutputType::custom(kind, data).send(0);
+}
+
+/// Like [`custom`], but a message with the same id and kind replaces the previous one.
+pub fn custom_with_id(id: impl Hash, kind: &str, data: &[&str]) {
+    OutputType::custom(kind, data).send(hash((OutputType::CUSTOM_STR, kind, id)));
+}
+
+/// Displays the fields of messages sent with [`custom`], see
+/// [`crate::Settings::output_renderers`].
+#[derive(Clone)]
+pub struct Renderer(Arc<RenderFn>);
+
+type RenderFn = dyn Fn(&mut Ui, &[String]) + Send + Sync;
+
+impl Renderer {
+    /// Takes a function that displays the fields of a message
+    pub fn new(render: impl Fn(&mut Ui, &[String]) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(render))
+    }
+}
+
+impl std::fmt::Debug for Renderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Renderer")
+    }
+}
+
+impl PartialEq for Renderer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Appends to the last entry if it's also plain text, so lines split
+/// between reads are joined and repeated lines can be collapsed
+fn push_text(output: &mut Vec<(u64, OutputType)>, text: &str, ansi: &mut Ansi) {
+    match output.last_mut() {
+        Some((0, OutputType::Lines(lines))) => lines.push_str(text, ansi),
+        _ => {
+            let mut lines = Lines::default();
+            lines.push_str(text, ansi);
+            output.push((0, OutputType::Lines(lines)));
         }
     }
+}
 
-    pub fn parse<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Option<Self> {
-        match iter.next() {
-            // Add a newline here for copying out text
-            Some(Self::PROGRESS_BAR_STR) => Some(Self::ProgressBar(
-                format!("{}\n", iter.next().unwrap_or_default()),
-                iter.next().and_then(|s| s.parse().ok()).unwrap_or_default(),
-            )),
-            _ => None,
+/// Drops the oldest chunks of plain text until it takes at most `limit` bytes.
+/// The chunk that's being appended to is always kept.
+fn truncate(output: &mut [(u64, OutputType)], limit: usize) {
+    let mut total: usize = output
+        .iter()
+        .map(|(_, o)| match o {
+            OutputType::Lines(lines) => lines.bytes(),
+            _ => 0,
+        })
+        .sum();
+    let last = output
+        .iter()
+        .rposition(|(_, o)| matches!(o, OutputType::Lines(_)));
+
+    for (index, (_, o)) in output.iter_mut().enumerate() {
+        if total <= limit {
+            break;
+        }
+        if let OutputType::Lines(lines) = o {
+            let keep = usize::from(Some(index) == last);
+            while total > limit && lines.chunk_count() > keep {
+                total -= lines.drop_oldest_chunk();
+            }
         }
     }
 }
 
-fn format_output(ui: &mut Ui, text: &str) {
-    let output = cansi::v3::categorise_text(text);
+/// Id of temporary data for the link to [`crate::Settings::log_dir`] in the truncation banner.
+/// It's only shown when the value is set, and becomes true when the link is clicked.
+pub(crate) const SAVE_LOG_ID: &str = "klask_save_log";
 
-    let previous = ui.style().spacing.item_spacing;
-    ui.style_mut().spacing.item_spacing = vec2(0.0, 0.0);
+/// Splits the child's output into plain text and messages. A message cut off at the
+/// end of a read is kept until the rest arrives, so each read is only parsed once.
+#[derive(Debug, Default)]
+pub(crate) struct Parser {
+    pending: String,
+    /// Styles continue over text split by messages
+    ansi: Ansi,
+}
 
-    ui.horizontal_wrapped(|ui| {
-        for CategorisedSlice {
-            text,
-            fg,
-            bg,
-            intensity,
-            italic,
-            underline,
-            strikethrough,
-            ..
-        } in output
-        {
-            for span in LinkFinder::new().spans(text) {
-                match span.kind() {
-                    Some(LinkKind::Url) => ui.hyperlink(span.as_str()),
-                    Some(LinkKind::Email) => {
-                        ui.hyperlink_to(span.as_str(), format!("mailto:{}", span.as_str()))
-                    }
-                    Some(_) | None => {
-                        let mut text = RichText::new(span.as_str());
+#[derive(Debug)]
+enum Parsed {
+    Text(String),
+    Message(u64, OutputType),
+}
 
-                        if let Some(fg) = fg {
-                            text = text.color(ansi_color_to_egui(fg));
-                        }
+impl Parser {
+    fn feed(&mut self, new: &str) -> Vec<Parsed> {
+        let data = if self.pending.is_empty() {
+            Cow::Borrowed(new)
+        } else {
+            let mut data = std::mem::take(&mut self.pending);
+            data.push_str(new);
+            Cow::Owned(data)
+        };
 
-                        if let Some(bg) = bg {
-                            if bg != Color::Black {
-                                text = text.background_color(ansi_color_to_egui(bg));
-                            }
-                        }
+        let mut parsed = vec![];
+        let mut rest = &*data;
+        while let Some(start) = rest.find(MAGIC) {
+            if start > 0 {
+                parsed.push(Parsed::Text(rest[..start].to_string()));
+            }
 
-                        if italic == Some(true) {
-                            text = text.italics();
-                        }
+            // Fields never contain newlines, so the message ends with the first one
+            let end = match rest[start..].find('\n') {
+                Some(end) => start + end,
+                None => {
+                    self.pending = rest[start..].to_string();
+                    return parsed;
+                }
+            };
 
-                        if underline == Some(true) {
-                            text = text.underline();
-                        }
+            let mut pieces: Vec<&str> = rest[start + MAGIC.len_utf8()..end].split(MAGIC).collect();
+            // The message ends with a separator before the newline
+            if pieces.last() == Some(&"") {
+                pieces.pop();
+            }
+            if let Some((id, fields)) = pieces.split_first() {
+                if let (Ok(id), Some(new)) = (id.parse(), OutputType::parse(fields)) {
+                    parsed.push(Parsed::Message(id, new));
+                }
+            }
+            rest = &rest[end + 1..];
+        }
 
-                        if strikethrough == Some(true) {
-                            text = text.strikethrough();
-                        }
+        if !rest.is_empty() {
+            parsed.push(Parsed::Text(rest.to_string()));
+        }
+        parsed
+    }
+}
+
+fn plain_text(output: &[(u64, OutputType)]) -> String {
+    output
+        .iter()
+        .map(|(_, o)| o.text())
+        .collect::<Vec<_>>()
+        .iter()
+        .flat_map(|text| cansi::v3::categorise_text(text))
+        .map(|slice| slice.text)
+        .collect()
+}
 
-                        text = match intensity {
-                            Some(Intensity::Bold) => text.strong(),
-                            Some(Intensity::Faint) => text.weak(),
-                            Some(Intensity::Normal) | None => text,
-                        };
+/// Last line of text with something in it
+fn status_line(output: &[(u64, OutputType)]) -> Option<&str> {
+    output.iter().rev().find_map(|(_, o)| match o {
+        OutputType::Lines(lines) => (lines.first()..lines.len())
+            .rev()
+            .map(|i| lines[i].text.as_str())
+            .find(|line| !line.trim().is_empty()),
+        OutputType::Text(text) => text.lines().rev().find(|line| !line.trim().is_empty()),
+        _ => None,
+    })
+}
 
-                        ui.add(Label::new(text))
-                    }
-                };
+fn hash(id: impl Hash) -> u64 {
+    let mut h = DefaultHasher::new();
+    id.hash(&mut h);
+    h.finish()
+}
+
+#[derive(Debug)]
+pub(crate) enum Output {
+    None,
+    Err(ExecutionError),
+    Child(Box<ChildApp>, Vec<(u64, OutputType)>, Box<View>, Parser),
+}
+
+/// Bookmarks, expanded lines and open folds of the output, kept with it so every run has its own
+#[derive(Debug, Default)]
+pub(crate) struct View {
+    /// Numbers of bookmarked lines of text, counted from 0
+    bookmarks: BTreeSet<usize>,
+    /// Scrolled to on the next frame
+    jump_to: Option<Jump>,
+    /// First lines of repeated lines that are shown expanded
+    expanded: BTreeSet<usize>,
+    /// Lines before indented blocks that are shown unfolded
+    open_folds: BTreeSet<usize>,
+    /// Of images whose paths are in the output
+    #[cfg(feature = "images")]
+    thumbnails: image::Thumbnails,
+    /// Relative paths of images start here, None for the current directory
+    #[cfg(feature = "images")]
+    working_dir: Option<PathBuf>,
+    /// Lines parsed as JSON, with the length of the text they were parsed from,
+    /// since the last line can still grow
+    json_lines: HashMap<usize, (usize, Option<Arc<Value>>)>,
+    /// Blocks of CSV or TSV shown as tables, by their first line
+    tables: HashMap<usize, table::TableView>,
+    /// Whether the end of the output was below the visible area in the last frame
+    end_hidden: bool,
+    /// Which log records and lines of text with a level are shown
+    level_filter: LevelFilter,
+    /// Counts changes to the output, so text built from it can be cached
+    generation: u64,
+    /// Plain text of the split view, with the generation it was built at
+    raw_text: (u64, String),
+}
+
+impl View {
+    /// The line parsed as JSON, if it's an object or array, see [`json`]
+    fn json_line(&mut self, line: usize, text: &str) -> Option<Arc<Value>> {
+        if !json::might_be_json(text) {
+            return None;
+        }
+        match self.json_lines.get(&line) {
+            Some((len, value)) if *len == text.len() => value.clone(),
+            _ => {
+                let value = json::parse_line(text).map(Arc::new);
+                self.json_lines.insert(line, (text.len(), value.clone()));
+                value
             }
         }
-    });
-    ui.style_mut().spacing.item_spacing = previous;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jump {
+    Line(usize),
+    /// Index of the entry
+    Mark(usize),
+    /// End of the output
+    End,
 }
 
-fn ansi_color_to_egui(color: Color) -> Color32 {
-    match color {
-        Color::Black => Color32::from_rgb(0, 0, 0),
-        Color::Red => Color32::from_rgb(205, 49, 49),
-        Color::Green => Color32::from_rgb(13, 188, 121),
-        Color::Yellow => Color32::from_rgb(229, 229, 16),
-        Color::Blue => Color32::from_rgb(36, 114, 200),
-        Color::Magenta => Color32::from_rgb(188, 63, 188),
-        Color::Cyan => Color32::from_rgb(17, 168, 205),
-        Color::White => Color32::from_rgb(229, 229, 229),
-        Color::BrightBlack => Color32::from_rgb(102, 102, 102),
-        Color::BrightRed => Color32::from_rgb(241, 76, 76),
-        Color::BrightGreen => Color32::from_rgb(35, 209, 139),
-        Color::BrightYellow => Color32::from_rgb(245, 245, 67),
-        Color::BrightBlue => Color32::from_rgb(59, 142, 234),
-        Color::BrightMagenta => Color32::from_rgb(214, 112, 214),
-        Color::BrightCyan => Color32::from_rgb(41, 184, 219),
-        Color::BrightWhite => Color32::from_rgb(229, 229, 229),
+impl Output {
+    pub fn new_with_child(child: ChildApp) -> Self {
+        let view = View {
+            #[cfg(feature = "images")]
+            working_dir: child.working_dir().map(Path::to_path_buf),
+            ..View::default()
+        };
+        Self::Child(Box::new(child), vec![], Box::new(view), Parser::default())
+    }
+
+    /// Reads new output from the child. Called once per frame, before anything is displayed.
+    /// Plain text over `byte_limit` bytes is dropped, starting with the oldest.
+    pub fn read_child(&mut self, byte_limit: Option<usize>) {
+        let (child, output, view, parser) = match self {
+            Output::Child(child, output, view, parser) => (child, output, view, parser),
+            _ => return,
+        };
+
+        let parsed: Vec<Parsed> = child
+            .read()
+            .iter()
+            .flat_map(|chunk| parser.feed(chunk))
+            .collect();
+        if parsed.is_empty() {
+            return;
+        }
+        view.generation += 1;
+        for parsed in parsed {
+            match parsed {
+                Parsed::Text(text) => {
+                    child.log(&text);
+                    push_text(output, &text, &mut parser.ansi);
+                }
+                Parsed::Message(id, new) => {
+                    // Messages with id 0 can't be updated, they are always added
+                    let exists = (id != 0)
+                        .then(|| output.iter().position(|(i, _)| *i == id))
+                        .flatten();
+                    if matches!(new, OutputType::Remove) {
+                        remove_entry(output, id);
+                    } else if let Some(index) = exists {
+                        let root = root(output, id);
+                        let was_active = is_root_active(output, root);
+                        match (&output[index].1, new) {
+                            (
+                                OutputType::ProgressGroup { .. },
+                                OutputType::ProgressState(state),
+                            ) => set_group_state(output, id, &state),
+                            (_, new) => output[index].1.update(new),
+                        }
+
+                        // Finished bars move from the pinned region to the end of the output
+                        if was_active && !is_root_active(output, root) {
+                            move_to_end(output, root);
+                        }
+                    } else if !matches!(new, OutputType::ProgressState(_)) {
+                        let logged = matches!(
+                            new,
+                            OutputType::Text(_)
+                                | OutputType::Log(_)
+                                | OutputType::Error(_)
+                                | OutputType::Json(_)
+                        );
+                        #[cfg(feature = "qr")]
+                        let logged = logged || matches!(new, OutputType::QrCode(_));
+                        if logged {
+                            child.log(&new.text());
+                        }
+                        output.push((id, new));
+                    }
+                }
+            }
+        }
+
+        if let Some(limit) = byte_limit {
+            truncate(output, limit);
+        }
+    }
+
+    /// Changes whenever the output changes, see [`Output::text`]
+    pub fn generation(&self) -> u64 {
+        match self {
+            Output::Child(_, _, view, _) => view.generation,
+            _ => 0,
+        }
+    }
+
+    /// Output without ANSI escape codes, as it's copied with the "Copy output" button
+    pub fn text(&self) -> String {
+        match self {
+            Output::None => String::new(),
+            Output::Err(err) => err.to_string(),
+            Output::Child(_, output, ..) => plain_text(output),
+        }
+    }
+
+    /// How much of the input passed when starting has been written to stdin,
+    /// like `42%`, while it's being written
+    pub fn input_progress(&self) -> Option<String> {
+        let (written, total) = match self {
+            Output::Child(child, ..) => child.feeding_input()?,
+            _ => return None,
+        };
+        Some(match total {
+            Some(total) => format!("{}%", written * 100 / total),
+            None => format_bytes(written),
+        })
+    }
+
+    /// Whether the running child has its stdin open for [`Output::send_input`]
+    pub fn accepts_input(&self) -> bool {
+        match self {
+            Output::Child(child, ..) => child.is_running() && child.has_stdin(),
+            _ => false,
+        }
+    }
+
+    /// Writes a line to the stdin of the child, and shows it like a terminal would echo it
+    pub fn send_input(&mut self, line: &str) {
+        if let Output::Child(child, output, view, parser) = self {
+            view.generation += 1;
+            let line = format!("{}\n", line);
+            child.write_stdin(&line);
+            child.log(&line);
+            push_text(output, &line, &mut parser.ansi);
+        }
+    }
+
+    /// Adds a section for the first panic in the output of a finished child, if there is one
+    pub fn detect_panic(&mut self) {
+        if let Output::Child(_, output, view, _) = self {
+            if output
+                .iter()
+                .any(|(_, o)| matches!(o, OutputType::Panic(_)))
+            {
+                return;
+            }
+            if let Some(panic) = panic::find(&plain_text(output)) {
+                output.push((0, OutputType::Panic(panic)));
+                view.generation += 1;
+            }
+        }
+    }
+
+    pub fn close_stdin(&mut self) {
+        if let Output::Child(child, ..) = self {
+            child.close_stdin();
+        }
+    }
+
+    /// Active progress is only pinned while the child is running
+    pub fn has_active_progress(&self) -> bool {
+        match self {
+            Output::Child(child, output, ..) => {
+                child.is_running() && output.iter().any(|(id, o)| is_active(output, *id, o))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the child is running while the end of the output is scrolled out of view
+    pub fn is_scrolled_up(&self) -> bool {
+        match self {
+            Output::Child(child, _, view, _) => child.is_running() && view.end_hidden,
+            _ => false,
+        }
+    }
+
+    /// Displays top-level progress bars and groups that are still running.
+    /// They are hidden from the rest of the output until they finish or the child exits.
+    /// While the output is scrolled up, the last line of text is shown below them,
+    /// with a button that scrolls to the end.
+    pub fn show_active_progress(&mut self, ui: &mut Ui, localization: &Localization) {
+        let scrolled_up = self.is_scrolled_up();
+        if let Output::Child(_, output, view, _) = self {
+            for (id, o) in output.iter() {
+                if is_active(output, *id, o) {
+                    show_progress(ui, output, *id, o);
+                }
+            }
+            if scrolled_up {
+                ui.horizontal(|ui| {
+                    if ui
+                        .small_button("⏬")
+                        .on_hover_text(&localization.scroll_to_end)
+                        .clicked()
+                    {
+                        view.jump_to = Some(Jump::End);
+                    }
+                    if let Some(line) = status_line(output) {
+                        ui.add(Label::new(RichText::new(line).monospace().weak()).wrap(false));
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// How the output is shown. Built every frame, since the preferences can change the palette.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OutputOptions<'a> {
+    /// See [`crate::Settings::output_renderers`]
+    pub renderers: &'a HashMap<String, Renderer>,
+    pub localization: &'a Localization,
+    /// See [`crate::Settings::ansi_palette`]
+    pub palette: AnsiPalette,
+}
+
+impl Output {
+    pub fn show(&mut self, ui: &mut Ui, options: &OutputOptions) -> Response {
+        match self {
+            Output::None => ui.vertical(|_| {}).response,
+            Output::Err(err) => ui.colored_label(Color32::RED, err.to_string()),
+            Output::Child(child, output, view, _) => {
+                let pinned = child.is_running();
+                let split_id = Id::new("klask_split_output");
+                let split = ui.data().get_temp(split_id).unwrap_or(false);
+                // View
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        let localization = options.localization;
+                        ui.menu_button(&localization.copy_output, |ui| {
+                            if ui.button(&localization.copy_plain_text).clicked() {
+                                ui.ctx().output().copied_text = plain_text(output);
+                                ui.close_menu();
+                            }
+                            if ui.button(&localization.copy_ansi_text).clicked() {
+                                ui.ctx().output().copied_text = copy::ansi_text(output);
+                                ui.close_menu();
+                            }
+                            if ui.button(&localization.copy_html).clicked() {
+                                let html = copy::html(output, &options.palette);
+                                // Plain text is the fallback for apps that can't paste HTML
+                                let copied = arboard::Clipboard::new().and_then(|mut clipboard| {
+                                    clipboard.set().html(html.clone(), Some(plain_text(output)))
+                                });
+                                if copied.is_err() {
+                                    ui.ctx().output().copied_text = html;
+                                }
+                                ui.close_menu();
+                            }
+                        });
+                        show_bookmarks_menu(ui, output, view, localization);
+                        if ui
+                            .selectable_label(split, &localization.split_view)
+                            .on_hover_text(&localization.split_view_hover)
+                            .clicked()
+                        {
+                            ui.data().insert_temp(split_id, !split);
+                        }
+                        if view.level_filter.only {
+                            let level = view.level_filter.level.as_str();
+                            let text = localization.level_only.format(&[("level", level)]);
+                            if ui.small_button(format!("{} ✖", text)).clicked() {
+                                view.level_filter = LevelFilter::default();
+                            }
+                        }
+                    });
+
+                    if split {
+                        ui.columns(2, |columns| {
+                            let (raw, structured) = columns.split_at_mut(1);
+                            show_raw(&mut raw[0], output, view);
+                            let structured = &mut structured[0];
+                            show_entries(structured, output, view, pinned, true, options);
+                        });
+                    } else {
+                        show_entries(ui, output, view, pinned, false, options);
+                    }
+
+                    view.end_hidden = ui.cursor().min.y > ui.clip_rect().max.y;
+                    if view.jump_to == Some(Jump::End) {
+                        ui.scroll_to_cursor(Some(Align::BOTTOM));
+                        view.jump_to = None;
+                    }
+                })
+                .response
+            }
+        }
+    }
+}
+
+/// Shows the text of every entry in order, as it's copied, for the split view
+fn show_raw(ui: &mut Ui, output: &[(u64, OutputType)], view: &mut View) {
+    if view.raw_text.0 != view.generation {
+        view.raw_text = (view.generation, plain_text(output));
+    }
+    ui.add(
+        TextEdit::multiline(&mut view.raw_text.1.as_str())
+            .font(TextStyle::Monospace)
+            .desired_width(f32::INFINITY),
+    );
+}
+
+/// Shows the tables, JSON and images found in plain text, for the split view
+fn show_structured_lines(
+    ui: &mut Ui,
+    lines: &Lines,
+    line: &mut usize,
+    view: &mut View,
+    localization: &Localization,
+) {
+    let mut i = lines.first();
+    *line += i;
+    while i < lines.len() {
+        let table = table::table_len(lines.iter_from(i).map(|l| l.text.as_str()));
+        if let Some((len, delimiter)) = table {
+            let first = *line;
+            let mut table = view.tables.get(&first).copied().unwrap_or_default();
+            let text = lines.iter_from(i).take(len).map(|l| l.text.as_str());
+            let id = Id::new(("klask_table", first));
+            table::show_table(ui, text, delimiter, &mut table, id);
+            // Sorted tables stay tables when the view is no longer split
+            if table.sort.is_some() || view.tables.contains_key(&first) {
+                view.tables.insert(first, table);
+            }
+            i += len;
+            *line += len;
+            continue;
+        }
+
+        let text = &lines[i].text;
+        if let Some(value) = view.json_line(*line, text) {
+            let id = Id::new(("klask_json_line", *line));
+            json::show_json(ui, &value, id, localization);
+        }
+        #[cfg(feature = "images")]
+        for path in image::image_paths(text) {
+            let working_dir = view.working_dir.as_deref();
+            view.thumbnails.show(ui, path, working_dir);
+        }
+        i += 1;
+        *line += 1;
+    }
+}
+
+/// Shows the results table, the log filter and the entries of the output.
+/// With `structured_only`, plain text is left out and only the tables, JSON
+/// and images found in it are shown, see [`show_structured_lines`].
+fn show_entries(
+    ui: &mut Ui,
+    output: &[(u64, OutputType)],
+    view: &mut View,
+    pinned: bool,
+    structured_only: bool,
+    options: &OutputOptions,
+) {
+    let results: Vec<(&str, &str)> = output
+        .iter()
+        .filter_map(|(_, o)| match o {
+            OutputType::Result { key, value } => Some((key.as_str(), value.as_str())),
+            _ => None,
+        })
+        .collect();
+    if !results.is_empty() {
+        ui.group(|ui| {
+            Grid::new("klask_results").striped(true).show(ui, |ui| {
+                for (key, value) in results {
+                    ui.label(RichText::new(key).strong());
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    let has_logs = output.iter().any(|(_, o)| matches!(o, OutputType::Log(_)));
+    if has_logs {
+        ui.horizontal(|ui| {
+            for level in LogLevel::ALL.iter().copied().rev() {
+                let text = RichText::new(level.as_str()).color(level.color(&options.palette));
+                let minimum = LevelFilter { level, only: false };
+                ui.selectable_value(&mut view.level_filter, minimum, text);
+            }
+        });
+    }
+
+    let mut line = 0;
+    let mut index = 0;
+    while index < output.len() {
+        let (id, o) = &output[index];
+        match o {
+            OutputType::Lines(lines) if structured_only => {
+                show_structured_lines(ui, lines, &mut line, view, options.localization)
+            }
+            OutputType::Lines(lines) => show_lines(ui, lines, &mut line, view, options),
+            OutputType::Text(_) if structured_only => {}
+            OutputType::Text(text) => format_output(ui, text, &options.palette),
+            OutputType::Custom { kind, data } => match options.renderers.get(kind) {
+                Some(renderer) => (renderer.0)(ui, data),
+                None => format_output(ui, &o.text(), &options.palette),
+            },
+            OutputType::Mark(label) => {
+                let response = ui
+                    .separator()
+                    .union(ui.label(RichText::new(format!("⚑ {}", label)).strong()));
+                if view.jump_to == Some(Jump::Mark(index)) {
+                    response.scroll_to_me(Some(Align::TOP));
+                    view.jump_to = None;
+                }
+            }
+            OutputType::Log(_) => {
+                let records: Vec<&LogRecord> = output[index..]
+                    .iter()
+                    .map_while(|(_, o)| match o {
+                        OutputType::Log(record) => Some(record),
+                        _ => None,
+                    })
+                    .collect();
+                let count = records.len();
+                let visible: Vec<&LogRecord> = records
+                    .into_iter()
+                    .filter(|r| view.level_filter.shows(r.level))
+                    .collect();
+                show_log_records(ui, &visible, 0, index, &options.palette);
+                index += count;
+                continue;
+            }
+            OutputType::Error(message) => {
+                ui.group(|ui| {
+                    ui.label(
+                        RichText::new(format!("✖ {}", message))
+                            .color(Color32::RED)
+                            .strong(),
+                    );
+                });
+            }
+            #[cfg(feature = "qr")]
+            OutputType::QrCode(qr) => qr::show_qr_code(ui, qr),
+            OutputType::Json(value) => {
+                let id = Id::new(("klask_json", index));
+                json::show_json(ui, value, id, options.localization)
+            }
+            OutputType::Panic(panic) => panic::show_panic(ui, panic, options.localization),
+            // Displayed in the results table
+            OutputType::Result { .. } => {}
+            // Displayed with the group
+            _ if o.parent().is_some() => {}
+            // Displayed in the pinned region
+            _ if pinned && is_active(output, *id, o) => {}
+            _ => show_progress(ui, output, *id, o),
+        }
+        index += 1;
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum OutputType {
+    /// Text that can be updated, plain output is kept as [`OutputType::Lines`]
+    Text(String),
+    Lines(Lines),
+    ProgressBar {
+        description: String,
+        value: f32,
+        parent: Option<u64>,
+        state: ProgressState,
+    },
+    ProgressGroup {
+        description: String,
+        parent: Option<u64>,
+    },
+    /// Updates the state of an existing progress bar
+    ProgressState(ProgressState),
+    /// Removes an existing entry
+    Remove,
+    Log(LogRecord),
+    Result {
+        key: String,
+        value: String,
+    },
+    /// Named anchor, see [`mark`]
+    Mark(String),
+    /// See [`custom`]
+    Custom {
+        kind: String,
+        data: Vec<String>,
+    },
+    /// Found in the output once the child exits, see [`Output::detect_panic`]
+    Panic(Panic),
+    /// Error returned by the closure, see [`crate::RunResult`]
+    Error(String),
+    /// See [`qr_code`]
+    #[cfg(feature = "qr")]
+    QrCode(Box<QrCode>),
+    /// See [`json`]
+    Json(Box<Value>),
+}
+
+/// A structured log record, sent by the `log` and `tracing` integrations.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LogRecord {
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+    /// Names and fields of spans the event happened in, outermost first
+    pub spans: Vec<String>,
+    pub fields: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub(crate) enum LogLevel {
+    #[default]
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|level| level.as_str() == s)
+    }
+
+    fn color(self, palette: &AnsiPalette) -> Color32 {
+        let color = match self {
+            LogLevel::Trace => Color::BrightBlack,
+            LogLevel::Debug => Color::Blue,
+            LogLevel::Info => Color::Green,
+            LogLevel::Warn => Color::Yellow,
+            LogLevel::Error => Color::Red,
+        };
+        palette.color(color)
+    }
+}
+
+impl LogRecord {
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    pub fn send(self) {
+        OutputType::Log(self).send(0);
+    }
+
+    fn text(&self) -> String {
+        let mut text = format!(
+            "{:5} {}: {}",
+            self.level.as_str(),
+            self.target,
+            self.message
+        );
+        for (key, value) in &self.fields {
+            text.push_str(&format!(" {}={}", key, value));
+        }
+        text.push('\n');
+        text
+    }
+}
+
+/// Displays records grouped by their spans. `index` is used to create unique ids.
+/// Which log records and lines of text are shown, by their level. The buttons above log
+/// records choose a minimum level, clicking a level badge in the text shows only that level.
+/// Lines of text without a level are hidden while anything is filtered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct LevelFilter {
+    level: LogLevel,
+    /// Only `level` is shown, instead of it and the levels above it
+    only: bool,
+}
+
+impl LevelFilter {
+    fn is_active(self) -> bool {
+        self != Self::default()
+    }
+
+    fn shows(self, level: LogLevel) -> bool {
+        if self.only {
+            level == self.level
+        } else {
+            level >= self.level
+        }
+    }
+}
+
+fn show_log_records(
+    ui: &mut Ui,
+    records: &[&LogRecord],
+    depth: usize,
+    index: usize,
+    palette: &AnsiPalette,
+) {
+    let mut i = 0;
+    while i < records.len() {
+        match records[i].spans.get(depth) {
+            None => {
+                show_log_record(ui, records[i], palette);
+                i += 1;
+            }
+            Some(span) => {
+                let count = records[i..]
+                    .iter()
+                    .take_while(|r| r.spans.get(depth) == Some(span))
+                    .count();
+                CollapsingHeader::new(RichText::new(span).monospace())
+                    .id_source(("klask_log_span", index + i, depth))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        let records = &records[i..i + count];
+                        show_log_records(ui, records, depth + 1, index + i, palette)
+                    });
+                i += count;
+            }
+        }
+    }
+}
+
+fn show_log_record(ui: &mut Ui, record: &LogRecord, palette: &AnsiPalette) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label(
+            RichText::new(format!("{:5}", record.level.as_str()))
+                .monospace()
+                .strong()
+                .color(record.level.color(palette)),
+        );
+        ui.label(RichText::new(&record.target).weak());
+        ui.label(&record.message);
+        for (key, value) in &record.fields {
+            ui.label(RichText::new(format!("{}={}", key, value)).weak());
+        }
+    });
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ProgressState {
+    Running,
+    Finished,
+    Failed(String),
+}
+
+fn show_progress(ui: &mut Ui, output: &[(u64, OutputType)], id: u64, o: &OutputType) {
+    match o {
+        OutputType::ProgressBar {
+            description,
+            value,
+            state,
+            ..
+        } => progress_bar_ui(ui, description, *value, state),
+        OutputType::ProgressGroup { description, .. } => {
+            progress_bar_ui(
+                ui,
+                description,
+                group_value(output, id),
+                &group_state(output, id),
+            );
+            ui.indent(id, |ui| {
+                for (child_id, child) in children(output, id) {
+                    show_progress(ui, output, *child_id, child);
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+fn progress_bar_ui(ui: &mut Ui, description: &str, value: f32, state: &ProgressState) {
+    // Get rid of the ending newline
+    let description = &description[..description.len() - 1];
+
+    ui.scope(|ui| {
+        let bar = match state {
+            // Animating would repaint every frame, even when nothing changes
+            ProgressState::Running => ProgressBar::new(value).text(description),
+            ProgressState::Finished => {
+                ui.visuals_mut().selection.bg_fill = Color32::from_rgb(13, 120, 80);
+                ProgressBar::new(1.0).text(format!("✔ {}", description))
+            }
+            ProgressState::Failed(message) => {
+                ui.visuals_mut().selection.bg_fill = Color32::from_rgb(160, 30, 30);
+                ProgressBar::new(value).text(if message.is_empty() {
+                    format!("✖ {}", description)
+                } else {
+                    format!("✖ {}: {}", description, message)
+                })
+            }
+        };
+        ui.add(bar);
+    });
+}
+
+/// Top-level progress bars and groups are active until they finish or fail
+fn is_active(output: &[(u64, OutputType)], id: u64, o: &OutputType) -> bool {
+    o.parent().is_none()
+        && match o {
+            OutputType::ProgressBar { state, .. } => *state == ProgressState::Running,
+            OutputType::ProgressGroup { .. } => group_state(output, id) == ProgressState::Running,
+            _ => false,
+        }
+}
+
+fn is_root_active(output: &[(u64, OutputType)], root: u64) -> bool {
+    output
+        .iter()
+        .any(|(i, o)| *i == root && is_active(output, root, o))
+}
+
+/// Id of the top-level group containing an entry, or the entry itself
+fn root(output: &[(u64, OutputType)], mut id: u64) -> u64 {
+    // Bounded in case of a cycle
+    for _ in 0..output.len() {
+        match output
+            .iter()
+            .find(|(i, _)| *i == id)
+            .and_then(|(_, o)| o.parent())
+        {
+            Some(parent) => id = parent,
+            None => break,
+        }
+    }
+    id
+}
+
+/// Moves an entry together with its children to the end of the output
+fn move_to_end(output: &mut Vec<(u64, OutputType)>, id: u64) {
+    let mut ids = vec![id];
+    let mut i = 0;
+    while i < ids.len() {
+        ids.extend(children(output, ids[i]).map(|(child, _)| *child));
+        i += 1;
+    }
+
+    let (moved, kept): (Vec<_>, Vec<_>) = std::mem::take(output)
+        .into_iter()
+        .partition(|(i, _)| *i != 0 && ids.contains(i));
+    *output = kept;
+    output.extend(moved);
+}
+
+/// Removes an entry together with its children
+fn remove_entry(output: &mut Vec<(u64, OutputType)>, id: u64) {
+    let children: Vec<u64> = children(output, id).map(|(child, _)| *child).collect();
+    for child in children {
+        remove_entry(output, child);
+    }
+    output.retain(|(i, _)| *i != id);
+}
+
+fn children(output: &[(u64, OutputType)], id: u64) -> impl Iterator<Item = &(u64, OutputType)> {
+    output.iter().filter(move |(_, o)| o.parent() == Some(id))
+}
+
+/// Average progress of all bars in a group, including nested groups.
+fn group_value(output: &[(u64, OutputType)], id: u64) -> f32 {
+    let (sum, count) =
+        children(output, id).fold((0.0, 0), |(sum, count), (child_id, child)| match child {
+            OutputType::ProgressBar { value, .. } => (sum + value, count + 1),
+            OutputType::ProgressGroup { .. } => (sum + group_value(output, *child_id), count + 1),
+            _ => (sum, count),
+        });
+
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f32
+    }
+}
+
+/// Finishes or fails the bars of a group that are still running, including nested groups
+fn set_group_state(output: &mut [(u64, OutputType)], id: u64, new: &ProgressState) {
+    let children: Vec<u64> = children(output, id).map(|(child, _)| *child).collect();
+    for child in children {
+        let index = match output.iter().position(|(i, _)| *i == child) {
+            Some(index) => index,
+            None => continue,
+        };
+        match &mut output[index].1 {
+            OutputType::ProgressBar { state, .. } if *state == ProgressState::Running => {
+                *state = new.clone();
+            }
+            OutputType::ProgressGroup { .. } => set_group_state(output, child, new),
+            _ => {}
+        }
+    }
+}
+
+/// A group failed if any bar failed, and finished if all bars finished.
+fn group_state(output: &[(u64, OutputType)], id: u64) -> ProgressState {
+    let states: Vec<ProgressState> = children(output, id)
+        .filter_map(|(child_id, child)| match child {
+            OutputType::ProgressBar { state, .. } => Some(state.clone()),
+            OutputType::ProgressGroup { .. } => Some(group_state(output, *child_id)),
+            _ => None,
+        })
+        .collect();
+
+    if states.iter().any(|s| matches!(s, ProgressState::Failed(_))) {
+        ProgressState::Failed(String::new())
+    } else if !states.is_empty() && states.iter().all(|s| *s == ProgressState::Finished) {
+        ProgressState::Finished
+    } else {
+        ProgressState::Running
+    }
+}
+
+/// Unicode non-character. Used for sending messages between GUI and user's program
+const MAGIC: char = '\u{5FFFE}';
+
+fn send_message(data: &[impl std::fmt::Display]) {
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+    for d in data {
+        write!(&mut lock, "{}{}", MAGIC, d).unwrap();
+    }
+    writeln!(&mut lock, "{}", MAGIC).unwrap();
+}
+
+/// Fields can't contain newlines, so they are replaced with a unicode line separator
+fn escape_newlines(s: &str) -> String {
+    s.replace('\n', "\u{2028}")
+}
+
+fn unescape_newlines(s: &str) -> String {
+    s.replace('\u{2028}', "\n")
+}
+
+/// Parent is an optional last field
+fn with_parent(mut data: Vec<String>, parent: Option<u64>) -> Vec<String> {
+    data.extend(parent.map(|p| p.to_string()));
+    data
+}
+
+impl OutputType {
+    const PROGRESS_BAR_STR: &'static str = "progress-bar";
+    const PROGRESS_GROUP_STR: &'static str = "progress-group";
+    const PROGRESS_FINISH_STR: &'static str = "progress-finish";
+    const PROGRESS_FAIL_STR: &'static str = "progress-fail";
+    const LOG_STR: &'static str = "log";
+    const TEXT_STR: &'static str = "text";
+    const RESULT_STR: &'static str = "result";
+    const REMOVE_STR: &'static str = "remove";
+    const MARK_STR: &'static str = "mark";
+    const CUSTOM_STR: &'static str = "custom";
+    const ERROR_STR: &'static str = "error";
+    #[cfg(feature = "qr")]
+    const QR_CODE_STR: &'static str = "qr-code";
+    const JSON_STR: &'static str = "json";
+
+    fn custom(kind: &str, data: &[&str]) -> Self {
+        Self::Custom {
+            kind: kind.to_string(),
+            data: data.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    pub fn send(self, id: u64) {
+        let id = id.to_string();
+        // Make sure to get rid of any newlines
+        match self {
+            // Text with an id can be updated, so it has to be sent as a message
+            Self::Text(s) if id == "0" => print!("{}", s),
+            Self::Text(s) => send_message(&[id, Self::TEXT_STR.into(), escape_newlines(&s)]),
+            // Only created from output that was already printed
+            Self::Lines(_) | Self::Panic(_) => {}
+            Self::ProgressBar {
+                description,
+                value,
+                parent,
+                ..
+            } => send_message(&with_parent(
+                vec![
+                    id,
+                    Self::PROGRESS_BAR_STR.into(),
+                    description.replace('\n', " "),
+                    value.to_string(),
+                ],
+                parent,
+            )),
+            Self::ProgressGroup {
+                description,
+                parent,
+            } => send_message(&with_parent(
+                vec![
+                    id,
+                    Self::PROGRESS_GROUP_STR.into(),
+                    description.replace('\n', " "),
+                ],
+                parent,
+            )),
+            Self::ProgressState(ProgressState::Running) => {}
+            Self::ProgressState(ProgressState::Finished) => {
+                send_message(&[id.as_str(), Self::PROGRESS_FINISH_STR])
+            }
+            Self::ProgressState(ProgressState::Failed(message)) => send_message(&[
+                id.as_str(),
+                Self::PROGRESS_FAIL_STR,
+                &message.replace('\n', " "),
+            ]),
+            Self::Remove => send_message(&[id.as_str(), Self::REMOVE_STR]),
+            Self::Mark(label) => {
+                send_message(&[id, Self::MARK_STR.into(), label.replace('\n', " ")])
+            }
+            Self::Result { key, value } => send_message(&[
+                id,
+                Self::RESULT_STR.into(),
+                escape_newlines(&key),
+                escape_newlines(&value),
+            ]),
+            Self::Error(message) => {
+                send_message(&[id, Self::ERROR_STR.into(), escape_newlines(&message)])
+            }
+            #[cfg(feature = "qr")]
+            Self::QrCode(qr) => {
+                send_message(&[id, Self::QR_CODE_STR.into(), escape_newlines(&qr.data)])
+            }
+            // Compact JSON has no newlines
+            Self::Json(value) => send_message(&[id, Self::JSON_STR.into(), value.to_string()]),
+            Self::Custom { kind, data } => {
+                let mut message = vec![id, Self::CUSTOM_STR.into(), escape_newlines(&kind)];
+                message.extend(data.iter().map(|d| escape_newlines(d)));
+                send_message(&message)
+            }
+            Self::Log(record) => {
+                let mut data = vec![
+                    id,
+                    Self::LOG_STR.into(),
+                    record.level.as_str().into(),
+                    record.target,
+                    escape_newlines(&record.message),
+                    record.spans.len().to_string(),
+                ];
+                data.extend(record.spans.iter().map(|s| escape_newlines(s)));
+                for (key, value) in record.fields {
+                    data.push(key);
+                    data.push(escape_newlines(&value));
+                }
+                send_message(&data)
+            }
+        }
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        match self {
+            Self::Text(text) => Cow::Borrowed(text),
+            Self::Lines(lines) => Cow::Owned(lines.text()),
+            Self::ProgressBar { description, .. } | Self::ProgressGroup { description, .. } => {
+                Cow::Borrowed(description)
+            }
+            // Its text is already in the output
+            Self::ProgressState(_) | Self::Remove | Self::Panic(_) => Cow::Borrowed(""),
+            Self::Log(record) => Cow::Owned(record.text()),
+            Self::Result { key, value } => Cow::Owned(format!("{}: {}\n", key, value)),
+            Self::Mark(label) | Self::Error(label) => Cow::Owned(format!("{}\n", label)),
+            #[cfg(feature = "qr")]
+            Self::QrCode(qr) => Cow::Owned(format!("{}\n", qr.data)),
+            Self::Json(value) => Cow::Owned(format!(
+                "{}\n",
+                serde_json::to_string_pretty(value).unwrap_or_default()
+            )),
+            Self::Custom { kind, data } => Cow::Owned(format!("{}: {}\n", kind, data.join(", "))),
+        }
+    }
+
+    /// Applies a new message with the same id
+    fn update(&mut self, new: OutputType) {
+        match (self, new) {
+            (Self::ProgressBar { state, .. }, Self::ProgressState(new)) => *state = new,
+            (_, Self::ProgressState(_)) => {}
+            (this, new) => *this = new,
+        }
+    }
+
+    pub fn parse(fields: &[&str]) -> Option<Self> {
+        let parent = |i: usize| fields.get(i).and_then(|s| s.parse().ok());
+
+        match fields.first() {
+            // Add a newline here for copying out text
+            Some(&Self::PROGRESS_BAR_STR) => Some(Self::ProgressBar {
+                description: format!("{}\n", fields.get(1).unwrap_or(&"")),
+                value: fields
+                    .get(2)
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or_default(),
+                parent: parent(3),
+                state: ProgressState::Running,
+            }),
+            Some(&Self::PROGRESS_GROUP_STR) => Some(Self::ProgressGroup {
+                description: format!("{}\n", fields.get(1).unwrap_or(&"")),
+                parent: parent(2),
+            }),
+            Some(&Self::PROGRESS_FINISH_STR) => Some(Self::ProgressState(ProgressState::Finished)),
+            Some(&Self::PROGRESS_FAIL_STR) => Some(Self::ProgressState(ProgressState::Failed(
+                fields.get(1).unwrap_or(&"").to_string(),
+            ))),
+            // Add a newline here for copying out text, unless the text was cleared
+            Some(&Self::TEXT_STR) => Some(Self::Text(match fields.get(1) {
+                Some(text) if !text.is_empty() => format!("{}\n", unescape_newlines(text)),
+                _ => String::new(),
+            })),
+            Some(&Self::REMOVE_STR) => Some(Self::Remove),
+            Some(&Self::MARK_STR) => Some(Self::Mark(fields.get(1)?.to_string())),
+            Some(&Self::RESULT_STR) => Some(Self::Result {
+                key: unescape_newlines(fields.get(1)?),
+                value: unescape_newlines(fields.get(2)?),
+            }),
+            Some(&Self::ERROR_STR) => Some(Self::Error(unescape_newlines(fields.get(1)?))),
+            // Only the text is sent, the modules are encoded again here
+            #[cfg(feature = "qr")]
+            Some(&Self::QR_CODE_STR) => QrCode::encode(&unescape_newlines(fields.get(1)?))
+                .map(|qr| Self::QrCode(Box::new(qr))),
+            Some(&Self::JSON_STR) => Some(Self::Json(Box::new(
+                serde_json::from_str(fields.get(1)?).ok()?,
+            ))),
+            Some(&Self::CUSTOM_STR) => Some(Self::Custom {
+                kind: unescape_newlines(fields.get(1)?),
+                data: fields[2..].iter().map(|d| unescape_newlines(d)).collect(),
+            }),
+            Some(&Self::LOG_STR) => {
+                let span_count: usize = fields.get(4)?.parse().ok()?;
+                let spans = fields.get(5..5 + span_count)?;
+                Some(Self::Log(LogRecord {
+                    level: LogLevel::parse(fields.get(1)?)?,
+                    target: fields.get(2)?.to_string(),
+                    message: unescape_newlines(fields.get(3)?),
+                    spans: spans.iter().map(|s| unescape_newlines(s)).collect(),
+                    fields: fields[5 + span_count..]
+                        .chunks_exact(2)
+                        .map(|kv| (kv[0].to_string(), unescape_newlines(kv[1])))
+                        .collect(),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn parent(&self) -> Option<u64> {
+        match self {
+            Self::Text(_)
+            | Self::Lines(_)
+            | Self::ProgressState(_)
+            | Self::Remove
+            | Self::Log(_)
+            | Self::Result { .. }
+            | Self::Mark(_)
+            | Self::Custom { .. }
+            | Self::Panic(_)
+            | Self::Error(_)
+            | Self::Json(_) => None,
+            #[cfg(feature = "qr")]
+            Self::QrCode(_) => None,
+            Self::ProgressBar { parent, .. } | Self::ProgressGroup { parent, .. } => *parent,
+        }
+    }
+}
+
+/// Lists marks and bookmarked lines, clicking one scrolls to it
+fn show_bookmarks_menu(
+    ui: &mut Ui,
+    output: &[(u64, OutputType)],
+    view: &mut View,
+    localization: &Localization,
+) {
+    let marks: Vec<(usize, &str)> = output
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, o))| match o {
+            OutputType::Mark(label) => Some((index, label.as_str())),
+            _ => None,
+        })
+        .collect();
+    if marks.is_empty() && view.bookmarks.is_empty() {
+        return;
+    }
+
+    ui.menu_button(format!("🔖 {}", localization.bookmarks), |ui| {
+        for (index, label) in marks {
+            if ui.button(format!("⚑ {}", label)).clicked() {
+                view.jump_to = Some(Jump::Mark(index));
+                ui.close_menu();
+            }
+        }
+
+        // Line numbers count dropped lines too
+        let mut first = 0;
+        let bookmarked: Vec<(usize, &str)> = output
+            .iter()
+            .filter_map(|(_, o)| match o {
+                OutputType::Lines(lines) => {
+                    let numbers = first + lines.first()..;
+                    first += lines.len();
+                    Some(numbers.zip(lines.iter_from(0)))
+                }
+                _ => None,
+            })
+            .flatten()
+            .filter(|(line, _)| view.bookmarks.contains(line))
+            .map(|(line, text)| (line, text.text.as_str()))
+            .collect();
+        for (line, text) in bookmarked {
+            if ui
+                .button(format!("{}: {}", line + 1, text.trim()))
+                .clicked()
+            {
+                view.jump_to = Some(Jump::Line(line));
+                ui.close_menu();
+            }
+        }
+    });
+}
+
+/// Shows each of `lines` with a gutter for bookmarking it.
+/// `line` is the number of the first line, and is advanced past the last one.
+fn show_lines(
+    ui: &mut Ui,
+    lines: &Lines,
+    line: &mut usize,
+    view: &mut View,
+    options: &OutputOptions,
+) {
+    if lines.dropped_bytes() > 0 {
+        show_truncation_banner(ui, lines.dropped_bytes());
+    }
+
+    let previous = ui.style().spacing.item_spacing;
+    ui.style_mut().spacing.item_spacing = vec2(0.0, 0.0);
+    let previous_text_style = ui
+        .style_mut()
+        .override_text_style
+        .replace(TextStyle::Monospace);
+    let mut i = lines.first();
+    *line += i;
+    while i < lines.len() {
+        if view.level_filter.is_active() {
+            let level = line_level(&lines[i].text).map(|(level, _)| level);
+            if level.is_some_and(|level| view.level_filter.shows(level)) {
+                show_line(ui, &lines[i], *line, None, None, view, options);
+            } else if view.jump_to == Some(Jump::Line(*line)) {
+                // Hidden lines can't be scrolled to
+                view.level_filter = LevelFilter::default();
+            }
+            i += 1;
+            *line += 1;
+            continue;
+        }
+
+        // Blocks of CSV or TSV can be shown as sortable tables
+        let table = table::table_len(lines.iter_from(i).map(|l| l.text.as_str()));
+        if let Some((len, delimiter)) = table {
+            let first = *line;
+            if let Some(Jump::Line(target)) = view.jump_to {
+                if (first..first + len).contains(&target) {
+                    view.tables.remove(&first);
+                }
+            }
+
+            let shown = view.tables.contains_key(&first);
+            let toggle = if shown {
+                &options.localization.view_as_text
+            } else {
+                &options.localization.view_as_table
+            };
+            if ui.small_button(format!("▦ {}", toggle)).clicked()
+                && view.tables.remove(&first).is_none()
+            {
+                view.tables.insert(first, table::TableView::default());
+            }
+            if let Some(table) = view.tables.get_mut(&first) {
+                let text = lines.iter_from(i).take(len).map(|l| l.text.as_str());
+                let id = Id::new(("klask_table", first));
+                table::show_table(ui, text, delimiter, table, id);
+            } else {
+                for offset in 0..len {
+                    show_line(
+                        ui,
+                        &lines[i + offset],
+                        first + offset,
+                        None,
+                        None,
+                        view,
+                        options,
+                    );
+                }
+            }
+            i += len;
+            *line += len;
+            continue;
+        }
+
+        // Indented blocks, like stack traces, are folded behind the line before them
+        let block = indented_block_len(lines.iter_from(i + 1).map(|l| l.text.as_str()));
+        if block >= MIN_FOLDED_LINES {
+            let first = *line;
+            if let Some(Jump::Line(target)) = view.jump_to {
+                if (first + 1..=first + block).contains(&target) {
+                    view.open_folds.insert(first);
+                }
+            }
+
+            let open = view.open_folds.contains(&first);
+            show_line(ui, &lines[i], first, None, Some(open), view, options);
+            i += 1;
+            *line += 1;
+            if !open {
+                i += block;
+                *line += block;
+            }
+            continue;
+        }
+
+        // Identical lines in a row are collapsed into the first one, unless expanded
+        let count = lines
+            .iter_from(i)
+            .take_while(|l| l.text == lines[i].text)
+            .count();
+        let first = *line;
+        if let Some(Jump::Line(target)) = view.jump_to {
+            if count > 1 && (first..first + count).contains(&target) {
+                view.expanded.insert(first);
+            }
+        }
+
+        let shown = if view.expanded.contains(&first) {
+            count
+        } else {
+            1
+        };
+        for offset in 0..shown {
+            let repeats = (offset == 0 && count > 1).then_some(count);
+            show_line(
+                ui,
+                &lines[i + offset],
+                first + offset,
+                repeats,
+                None,
+                view,
+                options,
+            );
+        }
+
+        i += count;
+        *line += count;
+    }
+    ui.style_mut().spacing.item_spacing = previous;
+    ui.style_mut().override_text_style = previous_text_style;
+}
+
+fn show_truncation_banner(ui: &mut Ui, dropped: usize) {
+    let id = Id::new(SAVE_LOG_ID);
+    ui.horizontal_wrapped(|ui| {
+        ui.label(
+            RichText::new(format!(
+                "… {} of earlier output truncated",
+                format_bytes(dropped as u64)
+            ))
+            .weak(),
+        );
+        if ui.data().get_temp::<bool>(id).is_some()
+            && ui.link("(save full log to keep everything)").clicked()
+        {
+            ui.data().insert_temp(id, true);
+        }
+    });
+}
+
+/// Folded blocks need at least this many lines, so short indented snippets stay visible
+const MIN_FOLDED_LINES: usize = 3;
+
+/// Number of indented, non-empty lines at the start of `lines`
+fn indented_block_len<'a>(lines: impl IntoIterator<Item = &'a str>) -> usize {
+    lines
+        .into_iter()
+        .take_while(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        .count()
+}
+
+/// Shows a line with a gutter for bookmarking it. Lines repeated more than once
+/// show the number of repeats, which can be clicked to expand or collapse them.
+/// Lines followed by a folded block show an arrow, `fold` is whether it's open.
+/// Paths to images are followed by thumbnails with the `images` feature, and JSON is shown as a tree.
+fn show_line(
+    ui: &mut Ui,
+    content: &Line,
+    line: usize,
+    repeats: Option<usize>,
+    fold: Option<bool>,
+    view: &mut View,
+    options: &OutputOptions,
+) {
+    let response = ui.horizontal(|ui| {
+        let gutter = if view.bookmarks.contains(&line) {
+            RichText::new("🔖 ")
+        } else {
+            RichText::new("·  ").weak()
+        };
+        if ui.add(Label::new(gutter).sense(Sense::click())).clicked()
+            && !view.bookmarks.remove(&line)
+        {
+            view.bookmarks.insert(line);
+        }
+
+        if let Some(open) = fold {
+            let arrow = if open { "⏷ " } else { "⏵ " };
+            if ui.add(Label::new(arrow).sense(Sense::click())).clicked()
+                && !view.open_folds.remove(&line)
+            {
+                view.open_folds.insert(line);
+            }
+        }
+
+        let json = view.json_line(line, &content.text);
+        ui.horizontal_wrapped(|ui| {
+            if let Some(value) = json {
+                let id = Id::new(("klask_json_line", line));
+                json::show_json(ui, &value, id, options.localization);
+            } else {
+                let level = line_level(&content.text);
+                show_spans(ui, &content.spans, level, view, options);
+                #[cfg(feature = "images")]
+                for path in image::image_paths(&content.text) {
+                    let working_dir = view.working_dir.as_deref();
+                    view.thumbnails.show(ui, path, working_dir);
+                }
+            }
+            if let Some(repeats) = repeats {
+                let label = RichText::new(format!("  ×{}", repeats)).weak();
+                if ui.add(Label::new(label).sense(Sense::click())).clicked()
+                    && !view.expanded.remove(&line)
+                {
+                    view.expanded.insert(line);
+                }
+            }
+        });
+    });
+
+    if view.jump_to == Some(Jump::Line(line)) {
+        response.response.scroll_to_me(Some(Align::Center));
+        view.jump_to = None;
+    }
+}
+
+pub(crate) fn format_output(ui: &mut Ui, text: &str, palette: &AnsiPalette) {
+    let output = cansi::v3::categorise_text(text);
+
+    let previous = ui.style().spacing.item_spacing;
+    ui.style_mut().spacing.item_spacing = vec2(0.0, 0.0);
+    let previous_text_style = ui
+        .style_mut()
+        .override_text_style
+        .replace(TextStyle::Monospace);
+
+    ui.horizontal_wrapped(|ui| {
+        for slice in output {
+            show_span(ui, &Span::from(slice), palette);
+        }
+    });
+    ui.style_mut().spacing.item_spacing = previous;
+    ui.style_mut().override_text_style = previous_text_style;
+}
+
+/// Sets the size of text in the output, see [`crate::Settings::output_font_size`]
+pub(crate) fn set_font_size(ui: &mut Ui, size: f32) {
+    for text_style in [TextStyle::Body, TextStyle::Monospace, TextStyle::Button] {
+        if let Some(font) = ui.style_mut().text_styles.get_mut(&text_style) {
+            font.size = size;
+        }
+    }
+}
+
+/// Shows the spans of a line, with its level as a badge that filters the output by it
+fn show_spans(
+    ui: &mut Ui,
+    spans: &[Span],
+    level: Option<(LogLevel, Range<usize>)>,
+    view: &mut View,
+    options: &OutputOptions,
+) {
+    let palette = &options.palette;
+    let (level, range) = match level {
+        Some(level) => level,
+        None => {
+            for span in spans {
+                show_span(ui, span, palette);
+            }
+            return;
+        }
+    };
+
+    let mut start = 0;
+    for span in spans {
+        let end = start + span.text.len();
+        if range.start >= end || range.end <= start {
+            show_span(ui, span, palette);
+        } else {
+            // The badge is cut out of the span it's in, the rest keeps its style
+            let before = &span.text[..range.start.max(start) - start];
+            let after = &span.text[range.end.min(end) - start..];
+            show_span(ui, &Span::new(before.to_string(), span.style), palette);
+            if range.start >= start {
+                show_level_badge(ui, level, view, options);
+            }
+            show_span(ui, &Span::new(after.to_string(), span.style), palette);
+        }
+        start = end;
+    }
+}
+
+fn show_level_badge(ui: &mut Ui, level: LogLevel, view: &mut View, options: &OutputOptions) {
+    let text = RichText::new(level.as_str())
+        .strong()
+        .color(Color32::BLACK)
+        .background_color(level.color(&options.palette));
+    let only = LevelFilter { level, only: true };
+    let localization = options.localization;
+    let hover = if view.level_filter == only {
+        localization.show_all_lines.clone()
+    } else {
+        localization
+            .show_only_level
+            .format(&[("level", level.as_str())])
+    };
+    if ui
+        .add(Label::new(text).sense(Sense::click()))
+        .on_hover_text(hover)
+        .clicked()
+    {
+        view.level_filter = if view.level_filter == only {
+            LevelFilter::default()
+        } else {
+            only
+        };
+    }
+}
+
+/// Level of a line of plain text, from a token like `ERROR` or `[warn]` near its start,
+/// with the byte range of the token
+fn line_level(text: &str) -> Option<(LogLevel, Range<usize>)> {
+    // Levels after this are more likely part of the message
+    const MAX_START: usize = 64;
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    while start < text.len().min(MAX_START) {
+        if !bytes[start].is_ascii_alphabetic() {
+            start += 1;
+            continue;
+        }
+        let len = bytes[start..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphabetic())
+            .count();
+        let word = &text[start..start + len];
+        let level = match word {
+            "TRACE" | "trace" => Some(LogLevel::Trace),
+            "DEBUG" | "debug" => Some(LogLevel::Debug),
+            "INFO" | "info" => Some(LogLevel::Info),
+            "WARN" | "warn" | "WARNING" | "warning" => Some(LogLevel::Warn),
+            "ERROR" | "error" | "FATAL" | "fatal" => Some(LogLevel::Error),
+            _ => None,
+        };
+        // Lowercase levels are only taken in brackets, to not match words of the message
+        let bracketed = text[..start].ends_with('[') && text[start + len..].starts_with(']');
+        if let Some(level) = level {
+            if word.chars().all(|c| c.is_ascii_uppercase()) || bracketed {
+                return Some((level, start..start + len));
+            }
+        }
+        start += len;
+    }
+    None
+}
+
+fn show_span(ui: &mut Ui, span: &Span, palette: &AnsiPalette) {
+    let mut end = 0;
+    for Link { range, email, uri } in &span.links {
+        if range.start > end {
+            let text = &span.text[end..range.start];
+            ui.add(Label::new(styled(text, &span.style, palette)));
+        }
+        let link = &span.text[range.clone()];
+        if let Some(uri) = uri {
+            ui.hyperlink_to(link, uri);
+        } else if *email {
+            ui.hyperlink_to(link, format!("mailto:{}", link));
+        } else {
+            ui.hyperlink(link);
+        }
+        end = range.end;
+    }
+    if end < span.text.len() {
+        ui.add(Label::new(styled(&span.text[end..], &span.style, palette)));
+    }
+}
+
+fn styled(text: &str, style: &lines::Style, palette: &AnsiPalette) -> RichText {
+    let lines::Style {
+        fg,
+        bg,
+        intensity,
+        italic,
+        underline,
+        strikethrough,
+        inverse,
+    } = style;
+    let mut text = RichText::new(text);
+
+    let (fg, bg) = if *inverse == Some(true) {
+        (
+            Some(bg.unwrap_or(Color::Black)),
+            Some(fg.unwrap_or(Color::White)),
+        )
+    } else {
+        (*fg, *bg)
+    };
+
+    if let Some(fg) = fg {
+        text = text.color(palette.color(fg));
+    }
+
+    if let Some(bg) = bg {
+        if bg != Color::Black {
+            text = text.background_color(palette.color(bg));
+        }
+    }
+
+    if *italic == Some(true) {
+        text = text.italics();
+    }
+
+    if *underline == Some(true) {
+        text = text.underline();
+    }
+
+    if *strikethrough == Some(true) {
+        text = text.strikethrough();
+    }
+
+    match intensity {
+        Some(Intensity::Bold) => text.strong(),
+        Some(Intensity::Faint) => text.weak(),
+        Some(Intensity::Normal) | None => text,
+    }
+}
+
+/// Colors of the output for ANSI colors, also used for success and failure,
+/// see [`crate::Settings::ansi_palette`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnsiPalette {
+    /// Black, red, green, yellow, blue, magenta, cyan and white, then their bright versions
+    pub colors: [Color32; 16],
+}
+
+impl Default for AnsiPalette {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl AnsiPalette {
+    /// Colors of most terminals
+    pub fn standard() -> Self {
+        Self {
+            colors: [
+                Color32::from_rgb(0, 0, 0),
+                Color32::from_rgb(205, 49, 49),
+                Color32::from_rgb(13, 188, 121),
+                Color32::from_rgb(229, 229, 16),
+                Color32::from_rgb(36, 114, 200),
+                Color32::from_rgb(188, 63, 188),
+                Color32::from_rgb(17, 168, 205),
+                Color32::from_rgb(229, 229, 229),
+                Color32::from_rgb(102, 102, 102),
+                Color32::from_rgb(241, 76, 76),
+                Color32::from_rgb(35, 209, 139),
+                Color32::from_rgb(245, 245, 67),
+                Color32::from_rgb(59, 142, 234),
+                Color32::from_rgb(214, 112, 214),
+                Color32::from_rgb(41, 184, 219),
+                Color32::from_rgb(229, 229, 229),
+            ],
+        }
+    }
+
+    /// Colors that can be told apart with deuteranopia and protanopia, based on the
+    /// Okabe-Ito palette. Red is orange and green is blue, so failures and successes differ.
+    pub fn deuteranopia() -> Self {
+        Self {
+            colors: [
+                Color32::from_rgb(0, 0, 0),
+                Color32::from_rgb(213, 94, 0),
+                Color32::from_rgb(86, 180, 233),
+                Color32::from_rgb(240, 228, 66),
+                Color32::from_rgb(0, 114, 178),
+                Color32::from_rgb(204, 121, 167),
+                Color32::from_rgb(0, 158, 115),
+                Color32::from_rgb(229, 229, 229),
+                Color32::from_rgb(102, 102, 102),
+                Color32::from_rgb(230, 159, 0),
+                Color32::from_rgb(140, 205, 245),
+                Color32::from_rgb(248, 240, 130),
+                Color32::from_rgb(60, 150, 220),
+                Color32::from_rgb(225, 165, 200),
+                Color32::from_rgb(60, 200, 160),
+                Color32::from_rgb(229, 229, 229),
+            ],
+        }
+    }
+
+    pub(crate) fn color(&self, color: Color) -> Color32 {
+        let index = match color {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+        };
+        self.colors[index]
+    }
+
+    /// Color of finished runs and added lines
+    pub(crate) fn success(&self) -> Color32 {
+        self.colors[2]
+    }
+
+    /// Color of failed runs and removed lines
+    pub(crate) fn failure(&self) -> Color32 {
+        self.colors[1]
     }
 }
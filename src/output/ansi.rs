@@ -0,0 +1,216 @@
+use super::lines::Style;
+use cansi::{Color, Intensity};
+use std::borrow::Cow;
+
+const ESC: char = '\x1b';
+
+/// Escape sequences longer than this are never completed by a later read
+const MAX_SEQUENCE_LEN: usize = 64;
+/// Like [`MAX_SEQUENCE_LEN`] for operating system commands, which can contain long URIs
+const MAX_OSC_LEN: usize = 4096;
+
+const COLORS: [Color; 8] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+const BRIGHT_COLORS: [Color; 8] = [
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// Streaming parser for ANSI escape codes. The current style, hyperlink and an escape
+/// sequence cut off at the end of a read are carried over to the next one.
+#[derive(Debug, Default)]
+pub(crate) struct Ansi {
+    style: Style,
+    /// URI of the OSC 8 hyperlink the text is in
+    link: Option<String>,
+    partial: String,
+}
+
+enum Escape<'a> {
+    /// Parameters of a Select Graphic Rendition sequence and its length
+    Sgr(&'a str, usize),
+    /// Parameters of an Operating System Command and its length
+    Osc(&'a str, usize),
+    /// Any other sequence, which is skipped
+    Other(usize),
+    Incomplete,
+}
+
+impl Ansi {
+    /// Splits `text` into slices with the same style and hyperlink, without the escape codes
+    pub fn categorise(&mut self, text: &str) -> Vec<(String, Style, Option<String>)> {
+        let data = if self.partial.is_empty() {
+            Cow::Borrowed(text)
+        } else {
+            let mut data = std::mem::take(&mut self.partial);
+            data.push_str(text);
+            Cow::Owned(data)
+        };
+
+        let mut slices = vec![];
+        let mut rest = &*data;
+        while let Some(start) = rest.find(ESC) {
+            self.push(&mut slices, &rest[..start]);
+            rest = &rest[start..];
+
+            match parse_escape(rest) {
+                Escape::Sgr(params, len) => {
+                    apply_sgr(&mut self.style, params);
+                    rest = &rest[len..];
+                }
+                Escape::Osc(params, len) => {
+                    // Hyperlinks are `8;params;URI`, and an empty URI ends them
+                    if let Some(link) = params.strip_prefix("8;") {
+                        let uri = link.split_once(';').map_or("", |(_, uri)| uri);
+                        self.link = (!uri.is_empty()).then(|| uri.to_string());
+                    }
+                    rest = &rest[len..];
+                }
+                Escape::Other(len) => rest = &rest[len..],
+                Escape::Incomplete => {
+                    self.partial = rest.to_string();
+                    return slices;
+                }
+            }
+        }
+        self.push(&mut slices, rest);
+        slices
+    }
+
+    fn push(&self, slices: &mut Vec<(String, Style, Option<String>)>, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match slices.last_mut() {
+            Some((last, style, link)) if *style == self.style && *link == self.link => {
+                last.push_str(text)
+            }
+            _ => slices.push((text.to_string(), self.style, self.link.clone())),
+        }
+    }
+}
+
+/// Applies the parameters of a Select Graphic Rendition sequence on top of `style`
+pub(crate) fn apply_sgr(style: &mut Style, params: &str) {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        match param {
+            "" | "0" => *style = Style::default(),
+            "1" => style.intensity = Some(Intensity::Bold),
+            "2" => style.intensity = Some(Intensity::Faint),
+            "3" => style.italic = Some(true),
+            "4" => style.underline = Some(true),
+            "7" => style.inverse = Some(true),
+            "9" => style.strikethrough = Some(true),
+            "22" => style.intensity = Some(Intensity::Normal),
+            "23" => style.italic = Some(false),
+            "24" => style.underline = Some(false),
+            "27" => style.inverse = Some(false),
+            "29" => style.strikethrough = Some(false),
+            "39" => style.fg = None,
+            "49" => style.bg = None,
+            // 256 and true colors can't be shown, but their values shouldn't be read as codes
+            "38" | "48" => match params.next() {
+                Some("5") => {
+                    params.next();
+                }
+                Some("2") => {
+                    params.nth(2);
+                }
+                _ => {}
+            },
+            _ => match param.parse::<usize>() {
+                Ok(n @ 30..=37) => style.fg = Some(COLORS[n - 30]),
+                Ok(n @ 40..=47) => style.bg = Some(COLORS[n - 40]),
+                Ok(n @ 90..=97) => style.fg = Some(BRIGHT_COLORS[n - 90]),
+                Ok(n @ 100..=107) => style.bg = Some(BRIGHT_COLORS[n - 100]),
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Select Graphic Rendition sequence that sets `style`, starting from the default style.
+/// Empty for the default style.
+pub(crate) fn sgr(style: &Style) -> String {
+    let mut params = vec![];
+    match style.intensity {
+        Some(Intensity::Bold) => params.push(1),
+        Some(Intensity::Faint) => params.push(2),
+        Some(Intensity::Normal) | None => {}
+    }
+    let flags = [
+        (style.italic, 3),
+        (style.underline, 4),
+        (style.inverse, 7),
+        (style.strikethrough, 9),
+    ];
+    for (flag, param) in flags {
+        if flag == Some(true) {
+            params.push(param);
+        }
+    }
+    let color = |color: Color, normal: usize, bright: usize| {
+        COLORS
+            .iter()
+            .position(|c| *c == color)
+            .map(|index| normal + index)
+            .or_else(|| {
+                BRIGHT_COLORS
+                    .iter()
+                    .position(|c| *c == color)
+                    .map(|index| bright + index)
+            })
+    };
+    params.extend(style.fg.and_then(|fg| color(fg, 30, 90)));
+    params.extend(style.bg.and_then(|bg| color(bg, 40, 100)));
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        let params: Vec<String> = params.iter().map(ToString::to_string).collect();
+        format!("{}[{}m", ESC, params.join(";"))
+    }
+}
+
+/// Parses the escape sequence at the start of `text`
+fn parse_escape(text: &str) -> Escape<'_> {
+    let bytes = text.as_bytes();
+    match bytes.get(1) {
+        None => Escape::Incomplete,
+        Some(b'[') => match bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) {
+            Some(end) if bytes[2 + end] == b'm' => Escape::Sgr(&text[2..2 + end], 3 + end),
+            Some(end) => Escape::Other(3 + end),
+            None if text.len() < MAX_SEQUENCE_LEN => Escape::Incomplete,
+            // Not a real sequence, so only skip the escape character
+            None => Escape::Other(1),
+        },
+        // Ended by BEL or by ST, which is ESC \
+        Some(b']') => match bytes[2..].iter().position(|&b| b == 0x07 || b == 0x1b) {
+            Some(end) if bytes[2 + end] == 0x07 => Escape::Osc(&text[2..2 + end], 3 + end),
+            Some(end) => match bytes.get(3 + end) {
+                Some(b'\\') => Escape::Osc(&text[2..2 + end], 4 + end),
+                Some(_) => Escape::Other(2 + end),
+                None => Escape::Incomplete,
+            },
+            None if text.len() < MAX_OSC_LEN => Escape::Incomplete,
+            None => Escape::Other(1),
+        },
+        Some(_) => Escape::Other(1),
+    }
+}
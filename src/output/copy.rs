@@ -0,0 +1,110 @@
+use super::{
+    ansi::{self, Ansi},
+    lines::Style,
+    AnsiPalette, OutputType,
+};
+use cansi::{Color, Intensity};
+use std::fmt::Write;
+
+/// Output split into slices with the same style, as it's displayed
+fn styled_text(output: &[(u64, OutputType)]) -> Vec<(String, Style)> {
+    output
+        .iter()
+        .flat_map(|(_, o)| match o {
+            OutputType::Lines(lines) => lines.styled_text(),
+            // Other text can still contain escape codes
+            _ => Ansi::default()
+                .categorise(&o.text())
+                .into_iter()
+                .map(|(text, style, _)| (text, style))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Output with styles as ANSI escape codes, for pasting into a terminal
+pub(crate) fn ansi_text(output: &[(u64, OutputType)]) -> String {
+    let mut text = String::new();
+    for (slice, style) in styled_text(output) {
+        let sgr = ansi::sgr(&style);
+        text.push_str(&sgr);
+        text.push_str(&slice);
+        if !sgr.is_empty() {
+            text.push_str("\x1b[0m");
+        }
+    }
+    text
+}
+
+/// Output as HTML with inline styles matching how it's displayed, for pasting into
+/// chat tools and wikis
+pub(crate) fn html(output: &[(u64, OutputType)], palette: &AnsiPalette) -> String {
+    let mut html = String::from("<pre style=\"font-family: monospace\">");
+    for (slice, style) in styled_text(output) {
+        let css = css(&style, palette);
+        if css.is_empty() {
+            html.push_str(&escape_html(&slice));
+        } else {
+            write!(
+                html,
+                "<span style=\"{}\">{}</span>",
+                css,
+                escape_html(&slice)
+            )
+            .unwrap();
+        }
+    }
+    html.push_str("</pre>");
+    html
+}
+
+/// Same colors and decorations as [`super::styled`]
+fn css(style: &Style, palette: &AnsiPalette) -> String {
+    let (fg, bg) = if style.inverse == Some(true) {
+        (
+            Some(style.bg.unwrap_or(Color::Black)),
+            Some(style.fg.unwrap_or(Color::White)),
+        )
+    } else {
+        (style.fg, style.bg)
+    };
+    let hex = |color| {
+        let color = palette.color(color);
+        format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+    };
+
+    let mut css = vec![];
+    if let Some(fg) = fg {
+        css.push(format!("color: {}", hex(fg)));
+    }
+    if let Some(bg) = bg.filter(|bg| *bg != Color::Black) {
+        css.push(format!("background-color: {}", hex(bg)));
+    }
+    match style.intensity {
+        Some(Intensity::Bold) => css.push("font-weight: bold".into()),
+        Some(Intensity::Faint) => css.push("opacity: 0.6".into()),
+        Some(Intensity::Normal) | None => {}
+    }
+    if style.italic == Some(true) {
+        css.push("font-style: italic".into());
+    }
+    let decorations: Vec<&str> = [
+        (style.underline, "underline"),
+        (style.strikethrough, "line-through"),
+    ]
+    .iter()
+    .filter(|(flag, _)| *flag == Some(true))
+    .map(|(_, decoration)| *decoration)
+    .collect();
+    if !decorations.is_empty() {
+        css.push(format!("text-decoration: {}", decorations.join(" ")));
+    }
+    css.join("; ")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,153 @@
+//! Thumbnails of images whose paths are printed in the output
+
+use ::image::{io::Limits, io::Reader, RgbaImage};
+use eframe::egui::{ColorImage, Image as ImageWidget, Sense, TextureHandle, Ui};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests;
+
+/// Extensions of the images that can be decoded
+const EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "bmp"];
+/// Larger images aren't decoded, so a huge file can't use up the memory
+const MAX_BYTES: u64 = 100_000_000;
+/// Longest side of an expanded thumbnail
+const MAX_SIZE: u32 = 256;
+/// Height of a thumbnail that isn't expanded
+const SMALL_HEIGHT: f32 = 48.0;
+/// How long a missing image waits before it's loaded again,
+/// since its path can be printed before the file is written
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Decodes a PNG, JPEG or BMP image, shrunk so its longest side is at most `max_size`
+pub(crate) fn thumbnail(data: &[u8], max_size: u32) -> Option<RgbaImage> {
+    let mut reader = Reader::new(Cursor::new(data)).with_guessed_format().ok()?;
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_BYTES);
+    reader.limits(limits);
+    let image = reader.decode().ok()?;
+    let image = if image.width().max(image.height()) > max_size {
+        image.thumbnail(max_size, max_size)
+    } else {
+        image
+    };
+    Some(image.into_rgba8())
+}
+
+/// Words of the text that look like paths to images, without surrounding quotes or brackets
+pub(crate) fn image_paths(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .map(|word| {
+            let word = word.trim_matches(|c| "\"'`()[]{}<>,;".contains(c));
+            let word = word.trim_end_matches([':', '.']);
+            word.strip_prefix("file://").unwrap_or(word)
+        })
+        .filter(|word| {
+            Path::new(word)
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    EXTENSIONS
+                        .iter()
+                        .any(|known| extension.eq_ignore_ascii_case(known))
+                })
+        })
+}
+
+/// Thumbnails of the output, loaded on a worker thread the first time a path is shown
+#[derive(Default)]
+pub(crate) struct Thumbnails {
+    /// Keyed by the path as printed
+    thumbnails: HashMap<String, Thumbnail>,
+    /// Paths of thumbnails shown at full size
+    expanded: HashSet<String>,
+}
+
+enum Thumbnail {
+    Loading(Receiver<Option<ColorImage>>),
+    Loaded(TextureHandle),
+    /// The file doesn't exist or isn't an image that can be decoded, since the instant
+    Missing(Instant),
+}
+
+impl std::fmt::Debug for Thumbnails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Thumbnails")
+            .field("count", &self.thumbnails.len())
+            .field("expanded", &self.expanded)
+            .finish()
+    }
+}
+
+impl Thumbnails {
+    /// Shows the thumbnail of the image at `path`, once it's loaded.
+    /// Relative paths start in `working_dir`, which is the current directory if None.
+    /// Clicking the thumbnail expands or shrinks it.
+    pub fn show(&mut self, ui: &mut Ui, path: &str, working_dir: Option<&Path>) {
+        let load_path = || {
+            let full_path = working_dir
+                .map(|dir| dir.join(path))
+                .unwrap_or_else(|| PathBuf::from(path));
+            Thumbnail::Loading(load(full_path, ui.ctx().clone()))
+        };
+        let thumbnail = self
+            .thumbnails
+            .entry(path.to_string())
+            .or_insert_with(load_path);
+        if matches!(thumbnail, Thumbnail::Missing(since) if since.elapsed() >= RETRY_INTERVAL) {
+            *thumbnail = load_path();
+        }
+
+        if let Thumbnail::Loading(receiver) = thumbnail {
+            match receiver.try_recv() {
+                Ok(Some(image)) => {
+                    *thumbnail = Thumbnail::Loaded(ui.ctx().load_texture(path, image));
+                }
+                Ok(None) | Err(mpsc::TryRecvError::Disconnected) => {
+                    *thumbnail = Thumbnail::Missing(Instant::now());
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+
+        if let Thumbnail::Loaded(texture) = thumbnail {
+            let expanded = self.expanded.contains(path);
+            let size = texture.size_vec2();
+            let size = if expanded || size.y <= SMALL_HEIGHT {
+                size
+            } else {
+                size * (SMALL_HEIGHT / size.y)
+            };
+            let response = ui
+                .add(ImageWidget::new(&*texture, size).sense(Sense::click()))
+                .on_hover_text(path);
+            if response.clicked() && !self.expanded.remove(path) {
+                self.expanded.insert(path.to_string());
+            }
+        }
+    }
+}
+
+/// Reads, decodes and shrinks the image on a worker thread
+fn load(path: PathBuf, ctx: eframe::egui::Context) -> Receiver<Option<ColorImage>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let image = path
+            .is_file()
+            .then(|| std::fs::read(&path).ok())
+            .flatten()
+            .and_then(|data| thumbnail(&data, MAX_SIZE))
+            .map(|image| {
+                let size = [image.width() as usize, image.height() as usize];
+                ColorImage::from_rgba_unmultiplied(size, image.as_raw())
+            });
+        drop(tx.send(image));
+        ctx.request_repaint();
+    });
+    rx
+}
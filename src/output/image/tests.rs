@@ -0,0 +1,95 @@
+use super::{image_paths, thumbnail};
+use ::image::{codecs::jpeg::JpegEncoder, ColorType, Rgba, RgbaImage};
+
+#[test]
+fn decode_png() {
+    // 2x2 RGB, with the sub filter on the first row and the up filter on the second
+    let rgb = [
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 2, 0, 0, 0, 2, 8, 2,
+        0, 0, 0, 253, 212, 154, 115, 0, 0, 0, 19, 73, 68, 65, 84, 120, 218, 99, 228, 18, 145, 3,
+        34, 38, 70, 38, 102, 32, 2, 0, 5, 20, 0, 136, 200, 21, 164, 13, 0, 0, 0, 0, 73, 69, 78, 68,
+        174, 66, 96, 130,
+    ];
+    assert_eq!(
+        thumbnail(&rgb, 256).map(RgbaImage::into_raw),
+        Some(vec![
+            10, 20, 30, 255, 20, 40, 60, 255, 11, 22, 33, 255, 21, 42, 63, 255
+        ])
+    );
+
+    // 3x1 with a 2 bit palette, where the first color is transparent
+    let palette = [
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 3, 0, 0, 0, 1, 2, 3,
+        0, 0, 0, 102, 142, 252, 39, 0, 0, 0, 9, 80, 76, 84, 69, 255, 0, 0, 0, 255, 0, 0, 0, 255,
+        45, 74, 205, 138, 0, 0, 0, 1, 116, 82, 78, 83, 0, 64, 230, 216, 102, 0, 0, 0, 10, 73, 68,
+        65, 84, 120, 218, 99, 144, 0, 0, 0, 26, 0, 25, 128, 0, 142, 187, 0, 0, 0, 0, 73, 69, 78,
+        68, 174, 66, 96, 130,
+    ];
+    assert_eq!(
+        thumbnail(&palette, 256).map(RgbaImage::into_raw),
+        Some(vec![255, 0, 0, 0, 0, 255, 0, 255, 0, 0, 255, 255])
+    );
+
+    // Cut off while it's still being written
+    assert_eq!(thumbnail(&rgb[..40], 256), None);
+}
+
+#[test]
+fn decode_jpeg() {
+    let pixels = [200u8; 16 * 8 * 3];
+    let mut jpeg = vec![];
+    JpegEncoder::new(&mut jpeg)
+        .encode(&pixels, 16, 8, ColorType::Rgb8)
+        .unwrap();
+
+    let image = thumbnail(&jpeg, 256).unwrap();
+    assert_eq!(image.dimensions(), (16, 8));
+    let Rgba([r, g, b, a]) = *image.get_pixel(3, 3);
+    assert!([r, g, b].iter().all(|c| c.abs_diff(200) <= 2));
+    assert_eq!(a, 255);
+}
+
+#[test]
+fn decode_bmp() {
+    let mut bmp = b"BM".to_vec();
+    bmp.extend_from_slice(&[0; 8]);
+    bmp.extend_from_slice(&54u32.to_le_bytes());
+    bmp.extend_from_slice(&40u32.to_le_bytes());
+    bmp.extend_from_slice(&1i32.to_le_bytes());
+    bmp.extend_from_slice(&2i32.to_le_bytes());
+    bmp.extend_from_slice(&1u16.to_le_bytes());
+    bmp.extend_from_slice(&24u16.to_le_bytes());
+    bmp.extend_from_slice(&[0; 24]);
+    // Bottom row first, each padded to 4 bytes, in BGR
+    bmp.extend_from_slice(&[3, 2, 1, 0, 6, 5, 4, 0]);
+
+    assert_eq!(
+        thumbnail(&bmp, 256).map(RgbaImage::into_raw),
+        Some(vec![4, 5, 6, 255, 1, 2, 3, 255])
+    );
+}
+
+#[test]
+fn shrink_to_thumbnail() {
+    let image = RgbaImage::from_pixel(40, 20, Rgba([100, 100, 100, 255]));
+    let mut png = vec![];
+    image
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            ::image::ImageFormat::Png,
+        )
+        .unwrap();
+    assert_eq!(thumbnail(&png, 10).unwrap().dimensions(), (10, 5));
+}
+
+#[test]
+fn find_image_paths() {
+    let paths: Vec<_> = image_paths(
+        "Saved plot.PNG and \"out/chart.bmp\", see file:///tmp/a.png. Photo image.jpg, not notes.txt or png",
+    )
+    .collect();
+    assert_eq!(
+        paths,
+        ["plot.PNG", "out/chart.bmp", "/tmp/a.png", "image.jpg"]
+    );
+}
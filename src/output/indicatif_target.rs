@@ -0,0 +1,276 @@
+use super::{hash, OutputType, ProgressState};
+use indicatif::{ProgressDrawTarget, TermLike};
+use std::io;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Returns an [`indicatif`] draw target that displays bars as native progress bars
+/// in the output. Requires the `indicatif` feature.
+///
+/// Lines with a progress (a bar, `pos/len` or a percentage) become progress bars,
+/// other lines like spinners and messages are displayed as text updated in place.
+/// ```no_run
+/// # use clap::App;
+/// # use klask::Settings;
+/// # use indicatif::ProgressBar;
+/// klask::run_app(App::new("Example"), Settings::default(), |_| {
+///     let bar = ProgressBar::with_draw_target(Some(100), klask::output::indicatif_draw_target());
+///     for _ in 0..100 {
+///         bar.inc(1);
+///     }
+///     bar.finish();
+/// });
+/// ```
+pub fn indicatif_draw_target() -> ProgressDrawTarget {
+    ProgressDrawTarget::term_like(Box::new(KlaskTerm::default()))
+}
+
+/// A virtual terminal. Lines are sent to the GUI on every flush.
+#[derive(Debug)]
+struct KlaskTerm {
+    /// Makes ids unique between draw targets
+    id: Uuid,
+    screen: Mutex<Screen>,
+}
+
+#[derive(Debug, Default)]
+struct Screen {
+    lines: Vec<String>,
+    /// Last text sent for each line
+    sent: Vec<String>,
+    row: usize,
+    column: usize,
+}
+
+impl Default for KlaskTerm {
+    fn default() -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            screen: Mutex::default(),
+        }
+    }
+}
+
+impl KlaskTerm {
+    fn with_screen(&self, f: impl FnOnce(&mut Screen)) -> io::Result<()> {
+        let mut screen = self.screen.lock().unwrap();
+        if screen.lines.len() <= screen.row {
+            let row = screen.row;
+            screen.lines.resize(row + 1, String::new());
+        }
+        f(&mut screen);
+        Ok(())
+    }
+}
+
+impl Screen {
+    fn line(&mut self) -> &mut String {
+        &mut self.lines[self.row]
+    }
+
+    fn write(&mut self, s: &str) {
+        for (i, part) in s.split('\r').enumerate() {
+            if i > 0 {
+                self.column = 0;
+            }
+            if part.is_empty() {
+                continue;
+            }
+
+            // Lines are always redrawn whole, so writing from the start replaces the line
+            if self.column == 0 {
+                self.line().clear();
+            }
+            let text: String = cansi::v3::categorise_text(part)
+                .into_iter()
+                .map(|slice| slice.text)
+                .collect();
+            self.column += text.chars().count();
+            self.line().push_str(&text);
+        }
+    }
+}
+
+impl TermLike for KlaskTerm {
+    fn width(&self) -> u16 {
+        80
+    }
+
+    fn height(&self) -> u16 {
+        u16::MAX
+    }
+
+    fn move_cursor_up(&self, n: usize) -> io::Result<()> {
+        self.with_screen(|s| s.row = s.row.saturating_sub(n))
+    }
+
+    fn move_cursor_down(&self, n: usize) -> io::Result<()> {
+        self.with_screen(|s| s.row += n)
+    }
+
+    fn move_cursor_right(&self, n: usize) -> io::Result<()> {
+        self.with_screen(|s| s.column += n)
+    }
+
+    fn move_cursor_left(&self, n: usize) -> io::Result<()> {
+        self.with_screen(|s| s.column = s.column.saturating_sub(n))
+    }
+
+    fn write_line(&self, s: &str) -> io::Result<()> {
+        self.with_screen(|screen| {
+            screen.write(s);
+            screen.row += 1;
+            screen.column = 0;
+        })
+    }
+
+    fn write_str(&self, s: &str) -> io::Result<()> {
+        self.with_screen(|screen| screen.write(s))
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        self.with_screen(|screen| {
+            screen.line().clear();
+            screen.column = 0;
+        })
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        let mut screen = self.screen.lock().unwrap();
+        let Screen { lines, sent, .. } = &mut *screen;
+        sent.resize(lines.len(), String::new());
+
+        for (index, (line, sent)) in lines.iter().zip(sent.iter_mut()).enumerate() {
+            let line = line.trim_end();
+            if line == sent {
+                continue;
+            }
+            *sent = line.to_string();
+
+            let id = hash((self.id, index));
+            match parse_line(line) {
+                (description, Some(value)) => OutputType::ProgressBar {
+                    description,
+                    value,
+                    parent: None,
+                    state: ProgressState::Running,
+                }
+                .send(id),
+                (_, None) => OutputType::Text(line.to_string()).send(id),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Characters of a bar, with how full they are
+const BAR_CHARS: [(char, f32); 24] = [
+    ('█', 1.0),
+    ('#', 1.0),
+    ('=', 1.0),
+    ('■', 1.0),
+    ('━', 1.0),
+    ('▇', 1.0),
+    ('▉', 0.875),
+    ('▊', 0.75),
+    ('▋', 0.625),
+    ('▌', 0.5),
+    ('▍', 0.375),
+    ('▎', 0.25),
+    ('▏', 0.125),
+    ('▓', 0.75),
+    ('▒', 0.5),
+    ('>', 0.0),
+    ('╸', 0.0),
+    ('░', 0.0),
+    ('-', 0.0),
+    ('·', 0.0),
+    ('─', 0.0),
+    ('╌', 0.0),
+    ('□', 0.0),
+    ('▁', 0.0),
+];
+
+fn bar_char(c: char) -> Option<f32> {
+    BAR_CHARS.iter().find(|(b, _)| *b == c).map(|(_, v)| *v)
+}
+
+/// Splits a line drawn by indicatif into a description and progress.
+/// The progress is taken from `pos/len`, a percentage or the bar itself.
+fn parse_line(line: &str) -> (String, Option<f32>) {
+    let chars: Vec<char> = line.chars().collect();
+
+    // Find the longest run of bar characters, which is the bar
+    let mut bar = 0..0;
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start;
+        while end < chars.len() && bar_char(chars[end]).is_some() {
+            end += 1;
+        }
+        // Bars like `[==>   ]` use spaces as the empty part
+        let mut padded = end;
+        while padded < chars.len() && chars[padded] == ' ' {
+            padded += 1;
+        }
+        if end > start && padded > end && chars.get(padded) == Some(&']') {
+            end = padded;
+        }
+        if end - start > bar.len() {
+            bar = start..end;
+        }
+        start = end.max(start + 1);
+    }
+    if bar.len() < 5 {
+        bar = 0..0;
+    }
+
+    // Don't leave empty brackets around the bar in the description
+    let mut removed = bar.clone();
+    if removed.start > 0 && chars[removed.start - 1] == '[' && chars.get(removed.end) == Some(&']')
+    {
+        removed = removed.start - 1..removed.end + 1;
+    }
+
+    let description: String = chars[..removed.start]
+        .iter()
+        .chain(&chars[removed.end..])
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let from_text = description.split_whitespace().find_map(|word| {
+        let word = word.trim_matches(|c: char| !c.is_ascii_digit() && c != '.');
+        if let Some((pos, len)) = word.split_once('/') {
+            let (pos, len) = (pos.parse::<f32>().ok()?, len.parse::<f32>().ok()?);
+            (len > 0.0).then(|| pos / len)
+        } else {
+            None
+        }
+    });
+    let from_percent = || {
+        description.split_whitespace().find_map(|word| {
+            let percent = word.trim_start_matches(['(', '[']).strip_suffix('%')?;
+            percent.parse::<f32>().ok().map(|p| p / 100.0)
+        })
+    };
+    let from_bar = || {
+        (!bar.is_empty()).then(|| {
+            let filled: f32 = chars[bar.clone()]
+                .iter()
+                .map(|c| bar_char(*c).unwrap_or(0.0))
+                .sum();
+            filled / bar.len() as f32
+        })
+    };
+
+    let value = from_text
+        .or_else(from_percent)
+        .or_else(from_bar)
+        .map(|v| v.clamp(0.0, 1.0));
+    (description, value)
+}
+
+#[cfg(test)]
+mod tests;
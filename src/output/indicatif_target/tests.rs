@@ -0,0 +1,28 @@
+use super::parse_line;
+
+#[test]
+fn parse_default_bar() {
+    let (description, value) = parse_line("██████████░░░░░░░░░░ 50/200");
+    assert_eq!(description, "50/200");
+    assert_eq!(value, Some(0.25));
+}
+
+#[test]
+fn parse_bar_without_count() {
+    let (description, value) = parse_line("[00:00:01] [=====>    ] Copying");
+    assert_eq!(description, "[00:00:01] Copying");
+    assert_eq!(value, Some(0.5));
+}
+
+#[test]
+fn parse_percent() {
+    let (description, value) = parse_line("Downloading 42%");
+    assert_eq!(description, "Downloading 42%");
+    assert_eq!(value, Some(0.42));
+}
+
+#[test]
+fn parse_spinner() {
+    assert_eq!(parse_line("⠁ Waiting for server").1, None);
+    assert_eq!(parse_line("Status: up-to-date").1, None);
+}
@@ -0,0 +1,147 @@
+//! Collapsible trees of JSON in the output
+
+use crate::localization::Localization;
+use eframe::egui::{CollapsingHeader, Color32, Id, Label, Response, RichText, Sense, Ui};
+use serde_json::Value;
+
+/// Longer lines aren't parsed, so huge outputs stay fast
+const MAX_LINE_LEN: usize = 1024 * 1024;
+/// Length of the JSON text shown in a collapsed header
+const PREVIEW_LEN: usize = 80;
+
+/// Quick check before parsing, whether the line is in braces or brackets
+pub(crate) fn might_be_json(text: &str) -> bool {
+    let text = text.trim();
+    let bracketed = (text.starts_with('{') && text.ends_with('}'))
+        || (text.starts_with('[') && text.ends_with(']'));
+    bracketed && text.len() <= MAX_LINE_LEN
+}
+
+/// Parses the line if it's a JSON object or array. Other values, like numbers,
+/// are more readable as text.
+pub(crate) fn parse_line(text: &str) -> Option<Value> {
+    if !might_be_json(text) {
+        return None;
+    }
+    serde_json::from_str(text.trim()).ok()
+}
+
+/// Path of a child in jq syntax, like `.items[2].name`
+pub(crate) fn child_path(parent: &str, key: PathKey) -> String {
+    // Brackets right after the root need the dot of the root, like `.[0]`
+    let bracket_parent = if parent.is_empty() { "." } else { parent };
+    match key {
+        PathKey::Index(index) => format!("{}[{}]", bracket_parent, index),
+        PathKey::Key(key) => {
+            let identifier = key.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if identifier {
+                format!("{}.{}", parent, key)
+            } else {
+                format!("{}[{}]", bracket_parent, Value::from(key))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PathKey<'a> {
+    Index(usize),
+    Key(&'a str),
+}
+
+/// Shows the value as a tree, collapsed behind a preview of its text
+pub(crate) fn show_json(ui: &mut Ui, value: &Value, id: Id, localization: &Localization) {
+    let mut preview = value.to_string();
+    if preview.chars().count() > PREVIEW_LEN {
+        preview = preview.chars().take(PREVIEW_LEN).collect::<String>() + "…";
+    }
+    let response = CollapsingHeader::new(RichText::new(preview).monospace())
+        .id_source(id)
+        .show(ui, |ui| show_children(ui, value, "", id, localization));
+    context_menu(response.header_response, ".", value, localization);
+}
+
+fn show_children(ui: &mut Ui, value: &Value, path: &str, id: Id, localization: &Localization) {
+    let children: Vec<(PathKey, &Value)> = match value {
+        Value::Object(map) => map.iter().map(|(k, v)| (PathKey::Key(k), v)).collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (PathKey::Index(i), v))
+            .collect(),
+        _ => return,
+    };
+    for (key, child) in children {
+        let label = match key {
+            PathKey::Index(index) => RichText::new(index.to_string()).weak(),
+            PathKey::Key(key) => RichText::new(key).strong(),
+        };
+        let path = child_path(path, key);
+        let response = match child {
+            Value::Object(map) => {
+                let header = format!("{}  {{{}}}", label.text(), map.len());
+                nested(ui, header, &path, child, id, localization)
+            }
+            Value::Array(array) => {
+                let header = format!("{}  [{}]", label.text(), array.len());
+                nested(ui, header, &path, child, id, localization)
+            }
+            scalar => {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.label(": ");
+                    ui.add(Label::new(scalar_text(scalar)).sense(Sense::click()))
+                })
+                .inner
+            }
+        };
+        context_menu(response, &path, child, localization);
+    }
+}
+
+fn nested(
+    ui: &mut Ui,
+    header: String,
+    path: &str,
+    value: &Value,
+    id: Id,
+    localization: &Localization,
+) -> Response {
+    let id = id.with(path);
+    CollapsingHeader::new(header)
+        .id_source(id)
+        .show(ui, |ui| show_children(ui, value, path, id, localization))
+        .header_response
+}
+
+fn scalar_text(value: &Value) -> RichText {
+    match value {
+        Value::String(string) => RichText::new(Value::from(string.as_str()).to_string())
+            .color(Color32::from_rgb(0x6a, 0xb0, 0x4c)),
+        Value::Number(number) => {
+            RichText::new(number.to_string()).color(Color32::from_rgb(0x4c, 0x9a, 0xd6))
+        }
+        other => RichText::new(other.to_string()).color(Color32::from_rgb(0xd6, 0x8a, 0x3c)),
+    }
+}
+
+/// Right-clicking an entry copies its path or its value
+fn context_menu(response: Response, path: &str, value: &Value, localization: &Localization) {
+    response.context_menu(|ui| {
+        if ui.button(&localization.copy_json_path).clicked() {
+            ui.ctx().output().copied_text = path.to_string();
+            ui.close_menu();
+        }
+        if ui.button(&localization.copy_json_value).clicked() {
+            ui.ctx().output().copied_text = match value {
+                Value::String(string) => string.clone(),
+                other => serde_json::to_string_pretty(other).unwrap_or_default(),
+            };
+            ui.close_menu();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests;
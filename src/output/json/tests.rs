@@ -0,0 +1,33 @@
+use super::{child_path, parse_line, PathKey};
+use serde_json::json;
+
+#[test]
+fn parse_json_lines() {
+    assert_eq!(
+        parse_line(r#"  {"name": "klask", "tags": [1, 2]}  "#),
+        Some(json!({"name": "klask", "tags": [1, 2]}))
+    );
+    assert_eq!(parse_line("[]"), Some(json!([])));
+    assert_eq!(parse_line("42"), None);
+    assert_eq!(parse_line(r#""text""#), None);
+    assert_eq!(parse_line("[INFO] Started {server}"), None);
+    assert_eq!(parse_line(r#"{"unfinished": "#), None);
+}
+
+#[test]
+fn jq_paths() {
+    let path = child_path("", PathKey::Key("items"));
+    assert_eq!(path, ".items");
+    let path = child_path(&path, PathKey::Index(2));
+    assert_eq!(path, ".items[2]");
+    assert_eq!(
+        child_path(&path, PathKey::Key("name_1")),
+        ".items[2].name_1"
+    );
+    assert_eq!(
+        child_path(&path, PathKey::Key("a \"b\"")),
+        r#".items[2]["a \"b\""]"#
+    );
+    assert_eq!(child_path("", PathKey::Key("1st")), r#".["1st"]"#);
+    assert_eq!(child_path("", PathKey::Index(0)), ".[0]");
+}
@@ -0,0 +1,288 @@
+use super::ansi::Ansi;
+use cansi::{v3::CategorisedSlice, Color, Intensity};
+use linkify::{LinkFinder, LinkKind};
+use std::ops::{Index, Range};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub intensity: Option<Intensity>,
+    pub italic: Option<bool>,
+    pub underline: Option<bool>,
+    pub strikethrough: Option<bool>,
+    /// Swapped foreground and background
+    pub inverse: Option<bool>,
+}
+
+/// Part of a line with a single style
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub text: String,
+    pub style: Style,
+    /// Found when the text is added, so it isn't scanned every frame
+    pub links: Vec<Link>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Link {
+    /// Byte range in the text of the span
+    pub range: Range<usize>,
+    pub email: bool,
+    /// Target of an OSC 8 hyperlink, None if the text is the target
+    pub uri: Option<String>,
+}
+
+impl Span {
+    pub fn new(text: String, style: Style) -> Self {
+        let links = find_links(&text);
+        Self { text, style, links }
+    }
+
+    /// Appends text with the same style, which is the label of a hyperlink to `uri` if any.
+    /// Links don't contain whitespace, so only the text after the last whitespace
+    /// is searched for links again.
+    fn push_str(&mut self, text: &str, uri: Option<&str>) {
+        let start = self
+            .text
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        self.links
+            .retain(|link| link.uri.is_some() || link.range.end <= start);
+
+        let end = self.text.len();
+        self.text.push_str(text);
+        if let Some(uri) = uri {
+            // A label split between reads continues the same link
+            match self.links.iter_mut().find(|link| link.range.end == end) {
+                Some(link) if link.uri.as_deref() == Some(uri) => link.range.end = self.text.len(),
+                _ => self.links.push(Link {
+                    range: end..self.text.len(),
+                    email: false,
+                    uri: Some(uri.to_string()),
+                }),
+            }
+        }
+
+        let found = find_links(&self.text[start..]);
+        let found: Vec<Link> = found
+            .into_iter()
+            .map(|link| Link {
+                range: link.range.start + start..link.range.end + start,
+                ..link
+            })
+            .filter(|found| {
+                // Labels of hyperlinks are often URLs themselves
+                !self.links.iter().any(|link| {
+                    link.uri.is_some()
+                        && found.range.start < link.range.end
+                        && link.range.start < found.range.end
+                })
+            })
+            .collect();
+        self.links.extend(found);
+        self.links.sort_by_key(|link| link.range.start);
+    }
+}
+
+fn find_links(text: &str) -> Vec<Link> {
+    LinkFinder::new()
+        .links(text)
+        .filter_map(|link| match link.kind() {
+            LinkKind::Url => Some((link, false)),
+            LinkKind::Email => Some((link, true)),
+            _ => None,
+        })
+        .map(|(link, email)| Link {
+            range: link.start()..link.end(),
+            email,
+            uri: None,
+        })
+        .collect()
+}
+
+impl From<CategorisedSlice<'_>> for Span {
+    fn from(slice: CategorisedSlice<'_>) -> Self {
+        let style = Style {
+            fg: slice.fg,
+            bg: slice.bg,
+            intensity: slice.intensity,
+            italic: slice.italic,
+            underline: slice.underline,
+            strikethrough: slice.strikethrough,
+            inverse: None,
+        };
+        Self::new(slice.text.to_string(), style)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Line {
+    pub spans: Vec<Span>,
+    /// Text of the spans, without ANSI escape codes
+    pub text: String,
+}
+
+/// Lines are stored in chunks of at most this many, so appending never moves the whole output
+const CHUNK_LINES: usize = 1024;
+
+/// Plain output of the child, split into styled lines once when it arrives,
+/// so nothing has to be parsed again when it's displayed
+#[derive(Debug, Default)]
+pub(crate) struct Lines {
+    /// Only the last chunk is appended to
+    chunks: Vec<Vec<Line>>,
+    /// Number of the first line of each chunk
+    starts: Vec<usize>,
+    /// Size of the text of each chunk in bytes
+    sizes: Vec<usize>,
+    /// Including lines in dropped chunks, so line numbers don't change when they're dropped
+    len: usize,
+    /// Size of the dropped chunks in bytes
+    dropped: usize,
+    /// Whether the last line hasn't ended with a newline yet
+    open: bool,
+}
+
+impl Lines {
+    /// Parses `text` and appends it, continuing the last line if it was open.
+    /// `ansi` carries styles and escape codes over from the previous output.
+    pub fn push_str(&mut self, text: &str, ansi: &mut Ansi) {
+        for (slice, style, link) in ansi.categorise(text) {
+            for (i, part) in slice.split('\n').enumerate() {
+                if i > 0 {
+                    // The newline ends the open line, or is an empty line itself
+                    if !self.open {
+                        self.push_line();
+                    }
+                    self.open = false;
+                }
+                if part.is_empty() {
+                    continue;
+                }
+
+                if !self.open {
+                    self.push_line();
+                    self.open = true;
+                }
+                let line = self.chunks.last_mut().unwrap().last_mut().unwrap();
+                line.text.push_str(part);
+                *self.sizes.last_mut().unwrap() += part.len();
+                match line.spans.last_mut() {
+                    Some(span) if span.style == style => span.push_str(part, link.as_deref()),
+                    _ => {
+                        let mut span = Span::new(String::new(), style);
+                        span.push_str(part, link.as_deref());
+                        line.spans.push(span);
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_line(&mut self) {
+        if self.chunks.last().is_none_or(|c| c.len() >= CHUNK_LINES) {
+            self.starts.push(self.len);
+            self.sizes.push(0);
+            self.chunks.push(Vec::with_capacity(CHUNK_LINES));
+        }
+        self.chunks.last_mut().unwrap().push(Line::default());
+        self.len += 1;
+    }
+
+    /// Number of lines, including dropped ones
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of the first line that wasn't dropped
+    pub fn first(&self) -> usize {
+        self.starts.first().copied().unwrap_or(self.len)
+    }
+
+    /// Size of the kept text in bytes
+    pub fn bytes(&self) -> usize {
+        self.sizes.iter().sum()
+    }
+
+    /// Size of the dropped text in bytes
+    pub fn dropped_bytes(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Drops the oldest chunk of lines and returns its size in bytes
+    pub fn drop_oldest_chunk(&mut self) -> usize {
+        if self.chunks.is_empty() {
+            return 0;
+        }
+        self.chunks.remove(0);
+        self.starts.remove(0);
+        let size = self.sizes.remove(0);
+        self.dropped += size;
+        if self.chunks.is_empty() {
+            self.open = false;
+        }
+        size
+    }
+
+    pub fn get(&self, line: usize) -> Option<&Line> {
+        let chunk = self.starts.partition_point(|&start| start <= line);
+        let chunk = chunk.checked_sub(1)?;
+        self.chunks[chunk].get(line - self.starts[chunk])
+    }
+
+    /// Lines starting with line number `line`
+    pub fn iter_from(&self, line: usize) -> impl Iterator<Item = &Line> {
+        let chunk = self
+            .starts
+            .partition_point(|&start| start <= line)
+            .saturating_sub(1);
+        let skip = line.saturating_sub(self.starts.get(chunk).copied().unwrap_or(0));
+        self.chunks[chunk.min(self.chunks.len())..]
+            .iter()
+            .flatten()
+            .skip(skip)
+    }
+
+    /// Kept text split into slices with the same style, with newlines in the default style
+    pub fn styled_text(&self) -> Vec<(String, Style)> {
+        let mut slices = vec![];
+        for (i, line) in (self.first()..).zip(self.iter_from(0)) {
+            slices.extend(
+                line.spans
+                    .iter()
+                    .map(|span| (span.text.clone(), span.style)),
+            );
+            if i + 1 < self.len || !self.open {
+                slices.push(("\n".to_string(), Style::default()));
+            }
+        }
+        slices
+    }
+
+    /// Kept text without ANSI escape codes
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for (i, line) in (self.first()..).zip(self.iter_from(0)) {
+            text.push_str(&line.text);
+            if i + 1 < self.len || !self.open {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+impl Index<usize> for Lines {
+    type Output = Line;
+
+    fn index(&self, line: usize) -> &Line {
+        self.get(line).expect("Line number out of range")
+    }
+}
@@ -0,0 +1,55 @@
+use super::{LogLevel, LogRecord};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// A [`log`] logger that displays records in the output with level colors
+/// and a level filter. Requires the `log` feature.
+/// ```no_run
+/// # use clap::App;
+/// # use klask::Settings;
+/// klask::run_app(App::new("Example"), Settings::default(), |_| {
+///     klask::output::Logger::init(log::LevelFilter::Debug).unwrap();
+///     log::info!(target: "example", "Started");
+/// });
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Logger {
+    max_level: LevelFilter,
+}
+
+impl Logger {
+    /// Sets the logger as the global logger for the [`log`] crate.
+    pub fn init(max_level: LevelFilter) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(Logger { max_level }))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.max_level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        LogRecord {
+            level: match record.level() {
+                Level::Error => LogLevel::Error,
+                Level::Warn => LogLevel::Warn,
+                Level::Info => LogLevel::Info,
+                Level::Debug => LogLevel::Debug,
+                Level::Trace => LogLevel::Trace,
+            },
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            spans: vec![],
+            fields: vec![],
+        }
+        .send();
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,101 @@
+use crate::localization::Localization;
+use eframe::egui::{CollapsingHeader, Color32, RichText, Ui};
+
+/// A panic found in the output of the binary, see [`crate::Settings::capture_panics`]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Panic {
+    pub thread: String,
+    pub message: String,
+    /// File, line and column of the panic
+    pub location: String,
+    /// Frames after `stack backtrace:`, if the backtrace was printed
+    pub backtrace: Option<String>,
+}
+
+/// Finds the first panic printed by the default panic hook, in the format
+/// of any Rust version. Text must not contain ANSI escape codes.
+pub(crate) fn find(text: &str) -> Option<Panic> {
+    let lines: Vec<&str> = text.lines().collect();
+    let (start, thread, rest) = lines.iter().enumerate().find_map(|(index, line)| {
+        let (thread, rest) = line
+            .strip_prefix("thread '")?
+            .split_once("' panicked at ")?;
+        Some((index, thread, rest))
+    })?;
+
+    let mut next = start + 1;
+    let (message, location) = match rest.strip_prefix('\'') {
+        // Before Rust 1.73: thread 'main' panicked at 'message', src/main.rs:2:5
+        Some(rest) => {
+            let mut message = rest.to_string();
+            loop {
+                if let Some((text, location)) = message.rsplit_once("', ") {
+                    if is_location(location) {
+                        break (text.to_string(), location.to_string());
+                    }
+                }
+                // The message continues on the next line
+                message.push('\n');
+                message.push_str(lines.get(next)?);
+                next += 1;
+            }
+        }
+        // Since Rust 1.73 the message is on the lines after the location
+        None => {
+            let location = rest.strip_suffix(':').unwrap_or(rest);
+            let end = lines[next..]
+                .iter()
+                .position(|line| line.starts_with("note: ") || *line == "stack backtrace:")
+                .map_or(lines.len(), |end| next + end);
+            let message = lines[next..end].join("\n");
+            next = end;
+            (message, location.to_string())
+        }
+    };
+
+    let backtrace = (lines.get(next) == Some(&"stack backtrace:")).then(|| {
+        lines[next + 1..]
+            .iter()
+            .take_while(|line| line.starts_with(' '))
+            .copied()
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+
+    Some(Panic {
+        thread: thread.to_string(),
+        message,
+        location,
+        backtrace,
+    })
+}
+
+/// Whether the text looks like `src/main.rs:2:5`
+fn is_location(text: &str) -> bool {
+    let mut parts = text.rsplitn(3, ':');
+    let numbers = parts
+        .by_ref()
+        .take(2)
+        .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    numbers && parts.next().is_some_and(|file| !file.is_empty())
+}
+
+pub(crate) fn show_panic(ui: &mut Ui, panic: &Panic, localization: &Localization) {
+    ui.group(|ui| {
+        let title = &localization.program_crashed;
+        CollapsingHeader::new(RichText::new(title).color(Color32::RED).strong())
+            .id_source("klask_panic")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new(&panic.message).color(Color32::RED));
+                ui.label(RichText::new(format!("{} ({})", panic.location, panic.thread)).weak());
+                if let Some(frames) = &panic.backtrace {
+                    CollapsingHeader::new(&localization.backtrace)
+                        .id_source("klask_panic_backtrace")
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(frames).monospace());
+                        });
+                }
+            });
+    });
+}
@@ -0,0 +1,76 @@
+//! QR codes, with the medium error correction level
+
+use eframe::egui::{vec2, Color32, Rect, Sense, Ui};
+use qrcode::{Color, EcLevel};
+
+/// Width of the light border around the code, in modules
+const QUIET_ZONE: usize = 4;
+/// Size of a module in points
+const MODULE_SIZE: f32 = 4.0;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QrCode {
+    /// The encoded text
+    pub data: String,
+    /// Width and height in modules
+    size: usize,
+    /// Rows of modules, true if dark
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes the text in the smallest version that fits.
+    /// Returns None if it's too long for a QR code.
+    pub fn encode(data: &str) -> Option<Self> {
+        let code = qrcode::QrCode::with_error_correction_level(data, EcLevel::M).ok()?;
+        Some(Self {
+            data: data.to_string(),
+            size: code.width(),
+            modules: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+}
+
+/// Draws the code in black on white, whatever the theme, so phones can scan it.
+/// Hovering shows the text and clicking copies it.
+pub(crate) fn show_qr_code(ui: &mut Ui, qr: &QrCode) {
+    let modules = qr.size() + QUIET_ZONE * 2;
+    let side = modules as f32 * MODULE_SIZE;
+    let (rect, response) = ui.allocate_exact_size(vec2(side, side), Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, Color32::WHITE);
+    for y in 0..qr.size() {
+        for x in 0..qr.size() {
+            if qr.is_dark(x, y) {
+                let min = rect.min
+                    + vec2(
+                        (x + QUIET_ZONE) as f32 * MODULE_SIZE,
+                        (y + QUIET_ZONE) as f32 * MODULE_SIZE,
+                    );
+                painter.rect_filled(
+                    Rect::from_min_size(min, vec2(MODULE_SIZE, MODULE_SIZE)),
+                    0.0,
+                    Color32::BLACK,
+                );
+            }
+        }
+    }
+    if response.on_hover_text(&qr.data).clicked() {
+        ui.ctx().output().copied_text = qr.data.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,25 @@
+use super::QrCode;
+
+#[test]
+fn smallest_version_that_fits() {
+    let size = |len| QrCode::encode(&"a".repeat(len)).map(|qr| qr.size());
+    assert_eq!(size(0), Some(21));
+    assert_eq!(size(14), Some(21));
+    assert_eq!(size(15), Some(25));
+    assert_eq!(size(2331), Some(177));
+    assert_eq!(size(2332), None);
+}
+
+#[test]
+fn finder_patterns() {
+    let qr = QrCode::encode("https://example.com/pair?code=0123456789abcdef").unwrap();
+    let size = qr.size();
+    for &(left, top) in &[(0, 0), (size - 7, 0), (0, size - 7)] {
+        for y in 0..7 {
+            for x in 0..7 {
+                let ring = x.min(y).min(6 - x).min(6 - y);
+                assert_eq!(qr.is_dark(left + x, top + y), ring != 1, "{} {}", x, y);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Blocks of CSV or TSV in the output, which can be shown as sortable tables
+
+use eframe::egui::{Grid, Id, Label, RichText, Sense, Ui};
+use std::cmp::Ordering;
+
+/// Tables need a header and at least this many rows, so a few lines with commas
+/// in the same places aren't taken for one
+const MIN_ROWS: usize = 2;
+
+/// Sorting of a table shown from a block of output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct TableView {
+    /// Column and whether it's descending
+    pub sort: Option<(usize, bool)>,
+}
+
+/// Splits a line into cells. Quoted cells can contain the delimiter and doubled quotes.
+/// Returns None if a quote isn't closed.
+pub(crate) fn split_record(line: &str, delimiter: char) -> Option<Vec<String>> {
+    let mut cells = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    cells.push(cell);
+    Some(cells)
+}
+
+/// Number of lines at the start that look like CSV or TSV, with their delimiter.
+/// Every line must have the same number of cells, at least two.
+pub(crate) fn table_len<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<(usize, char)> {
+    let mut lines = lines.into_iter();
+    let header = lines.next()?;
+    // Lines of JSON have commas too
+    if header.trim_start().starts_with(['{', '[']) {
+        return None;
+    }
+    let delimiter = if header.contains('\t') {
+        '\t'
+    } else if header.contains(',') {
+        ','
+    } else {
+        return None;
+    };
+    let columns = split_record(header, delimiter)?.len();
+    let rows = lines
+        .take_while(|line| {
+            line.contains(delimiter)
+                && split_record(line, delimiter).is_some_and(|cells| cells.len() == columns)
+        })
+        .count();
+    (rows >= MIN_ROWS).then_some((rows + 1, delimiter))
+}
+
+/// Compares numbers by value and everything else as text
+pub(crate) fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+/// Shows the lines as a table with the first one as its header.
+/// Clicking a header sorts by its column, clicking it again reverses the order.
+pub(crate) fn show_table<'a>(
+    ui: &mut Ui,
+    lines: impl IntoIterator<Item = &'a str>,
+    delimiter: char,
+    view: &mut TableView,
+    id: Id,
+) {
+    let mut records = lines
+        .into_iter()
+        .filter_map(|line| split_record(line, delimiter));
+    let header = match records.next() {
+        Some(header) => header,
+        None => return,
+    };
+    let mut rows: Vec<Vec<String>> = records.collect();
+    if let Some((column, descending)) = view.sort {
+        // Stable, so equal cells keep the order of the output
+        rows.sort_by(|a, b| {
+            let ordering = compare_cells(&a[column], &b[column]);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    Grid::new(id).striped(true).show(ui, |ui| {
+        for (column, name) in header.iter().enumerate() {
+            let arrow = match view.sort {
+                Some((sorted, false)) if sorted == column => " ⏶",
+                Some((sorted, true)) if sorted == column => " ⏷",
+                _ => "",
+            };
+            let label = RichText::new(format!("{}{}", name.trim(), arrow)).strong();
+            if ui.add(Label::new(label).sense(Sense::click())).clicked() {
+                view.sort = match view.sort {
+                    Some((sorted, false)) if sorted == column => Some((column, true)),
+                    Some((sorted, true)) if sorted == column => None,
+                    _ => Some((column, false)),
+                };
+            }
+        }
+        ui.end_row();
+        for row in &rows {
+            for cell in row {
+                ui.label(cell.trim());
+            }
+            ui.end_row();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests;
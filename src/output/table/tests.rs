@@ -0,0 +1,32 @@
+use super::{compare_cells, split_record, table_len};
+use std::cmp::Ordering;
+
+#[test]
+fn split_quoted_cells() {
+    assert_eq!(
+        split_record(r#"a, "b, ""c""",d"#, ',').unwrap(),
+        ["a", "b, \"c\"", "d"]
+    );
+    assert_eq!(split_record("x\t\ty\r", '\t').unwrap(), ["x", "", "y"]);
+    assert_eq!(split_record(r#"a,"b"#, ','), None);
+}
+
+#[test]
+fn detect_tables() {
+    let csv = ["name,size", "a.txt,10", "\"b,c.txt\",2", "Done"];
+    assert_eq!(table_len(csv), Some((3, ',')));
+    assert_eq!(table_len(["Copying", "a,b", "c,d"]), None);
+    // A header and one row aren't enough
+    assert_eq!(table_len(["name,size", "a.txt,10", "Done"]), None);
+    let tsv = ["name\tsize", "a\t1", "b\t2", "c,d"];
+    assert_eq!(table_len(tsv), Some((3, '\t')));
+    assert_eq!(table_len(["a,b,c", "1,2", "3,4"]), None);
+}
+
+#[test]
+fn compare_numbers_and_text() {
+    assert_eq!(compare_cells("9", "10"), Ordering::Less);
+    assert_eq!(compare_cells(" 2.5", "-1"), Ordering::Greater);
+    assert_eq!(compare_cells("10", "abc"), Ordering::Less);
+    assert_eq!(compare_cells("b", "a"), Ordering::Greater);
+}
@@ -0,0 +1,508 @@
+use super::{
+    ansi::Ansi, copy, format_bytes, group_state, indented_block_len, is_root_active, line_level,
+    lines::Style, move_to_end, panic, push_text, root, set_group_state, status_line, truncate,
+    AnsiPalette, LevelFilter, Lines, LogLevel, Output, OutputType, Parsed, Parser, ProgressState,
+    MAGIC,
+};
+use crate::child_app::ChildApp;
+use cansi::{Color, Intensity};
+
+#[test]
+fn format_bytes_units() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1024), "1.0 KiB");
+    assert_eq!(format_bytes(1536 * 1024), "1.5 MiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    assert_eq!(format_bytes(u64::MAX), "16.0 EiB");
+}
+
+#[test]
+fn finished_group_moves_to_end() {
+    let bar = |parent| OutputType::ProgressBar {
+        description: "bar\n".into(),
+        value: 0.5,
+        parent,
+        state: ProgressState::Running,
+    };
+    let mut output = vec![
+        (
+            1,
+            OutputType::ProgressGroup {
+                description: "group\n".into(),
+                parent: None,
+            },
+        ),
+        (2, bar(Some(1))),
+        (0, OutputType::Text("text\n".into())),
+        (3, bar(None)),
+    ];
+
+    assert_eq!(root(&output, 2), 1);
+    assert!(is_root_active(&output, 1));
+
+    output[1]
+        .1
+        .update(OutputType::ProgressState(ProgressState::Finished));
+    assert!(!is_root_active(&output, 1));
+
+    move_to_end(&mut output, 1);
+    let ids: Vec<u64> = output.iter().map(|(id, _)| *id).collect();
+    assert_eq!(ids, [0, 3, 1, 2]);
+}
+
+#[test]
+fn group_state_applies_to_bars() {
+    let bar = |parent, state| OutputType::ProgressBar {
+        description: "bar\n".into(),
+        value: 0.5,
+        parent: Some(parent),
+        state,
+    };
+    let group = |parent| OutputType::ProgressGroup {
+        description: "group\n".into(),
+        parent,
+    };
+    let failed = ProgressState::Failed("Disk full".into());
+    let mut output = vec![
+        (1, group(None)),
+        (2, bar(1, ProgressState::Running)),
+        (3, group(Some(1))),
+        (4, bar(3, ProgressState::Running)),
+        (5, bar(3, ProgressState::Finished)),
+    ];
+    assert_eq!(group_state(&output, 1), ProgressState::Running);
+
+    set_group_state(&mut output, 1, &failed);
+    let states: Vec<_> = output
+        .iter()
+        .filter_map(|(_, o)| match o {
+            OutputType::ProgressBar { state, .. } => Some(state.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(states, [failed.clone(), failed, ProgressState::Finished]);
+    assert!(matches!(group_state(&output, 1), ProgressState::Failed(_)));
+    assert!(!is_root_active(&output, 1));
+
+    let mut output = vec![(1, group(None)), (2, bar(1, ProgressState::Running))];
+    set_group_state(&mut output, 1, &ProgressState::Finished);
+    assert_eq!(group_state(&output, 1), ProgressState::Finished);
+}
+
+#[test]
+fn parse_mark() {
+    assert!(matches!(
+        OutputType::parse(&["mark", "Phase 2 start"]),
+        Some(OutputType::Mark(label)) if label == "Phase 2 start"
+    ));
+    assert!(OutputType::parse(&["mark"]).is_none());
+}
+
+#[test]
+fn parse_custom() {
+    let parsed = OutputType::parse(&["custom", "gauge", "Pressure", "0.7\u{2028}bar"]).unwrap();
+    assert!(matches!(
+        &parsed,
+        OutputType::Custom { kind, data } if kind == "gauge" && data == &["Pressure", "0.7\nbar"]
+    ));
+    assert_eq!(parsed.text(), "gauge: Pressure, 0.7\nbar\n");
+    assert!(OutputType::parse(&["custom"]).is_none());
+}
+
+#[test]
+fn parse_error() {
+    let parsed = OutputType::parse(&["error", "No such file\u{2028}(os error 2)"]).unwrap();
+    assert!(
+        matches!(&parsed, OutputType::Error(message) if message == "No such file\n(os error 2)")
+    );
+    assert_eq!(parsed.text(), "No such file\n(os error 2)\n");
+    assert!(OutputType::parse(&["error"]).is_none());
+}
+
+#[test]
+fn parse_json() {
+    let parsed = OutputType::parse(&["json", r#"{"a":[1,"x\ny"]}"#]).unwrap();
+    assert!(matches!(&parsed, OutputType::Json(value) if value["a"][1] == "x\ny"));
+    assert_eq!(
+        parsed.text(),
+        "{\n  \"a\": [\n    1,\n    \"x\\ny\"\n  ]\n}\n"
+    );
+    assert!(OutputType::parse(&["json", "{"]).is_none());
+}
+
+#[test]
+fn push_text_joins_plain_text() {
+    let mut output = vec![];
+    let mut ansi = Ansi::default();
+    push_text(&mut output, "partial ", &mut ansi);
+    push_text(&mut output, "line\n", &mut ansi);
+    output.push((1, OutputType::Text("updatable\n".into())));
+    push_text(&mut output, "after\n", &mut ansi);
+
+    let texts: Vec<String> = output.iter().map(|(_, o)| o.text().into_owned()).collect();
+    assert_eq!(texts, ["partial line\n", "updatable\n", "after\n"]);
+}
+
+#[test]
+fn push_text_splits_lines() {
+    let mut output = vec![];
+    let mut ansi = Ansi::default();
+    push_text(&mut output, "\x1b[31mred\x1b[0m\n\nopen", &mut ansi);
+    push_text(&mut output, " line", &mut ansi);
+
+    match &output[..] {
+        [(0, OutputType::Lines(lines))] => {
+            let texts: Vec<&str> = lines.iter_from(0).map(|l| l.text.as_str()).collect();
+            assert_eq!(texts, ["red", "", "open line"]);
+            assert_eq!(lines.text(), "red\n\nopen line");
+        }
+        _ => panic!("Expected one entry of lines"),
+    }
+}
+
+#[test]
+fn ansi_style_spans_reads() {
+    let mut lines = Lines::default();
+    let mut ansi = Ansi::default();
+    lines.push_str("\x1b[1mbold \x1b[3", &mut ansi);
+    lines.push_str("1mred\n", &mut ansi);
+    lines.push_str("still red\x1b[0m plain\n", &mut ansi);
+
+    let texts: Vec<&str> = lines.iter_from(0).map(|l| l.text.as_str()).collect();
+    assert_eq!(texts, ["bold red", "still red plain"]);
+
+    let red = &lines[0].spans[1].style;
+    assert_eq!(red.fg, Some(Color::Red));
+    assert_eq!(red.intensity, Some(Intensity::Bold));
+    assert_eq!(lines[1].spans[0].style, *red);
+    assert_eq!(lines[1].spans[1].style, Style::default());
+}
+
+#[test]
+fn lines_index_across_chunks() {
+    let mut lines = Lines::default();
+    let mut ansi = Ansi::default();
+    for i in 0..2500 {
+        lines.push_str(&format!("{}\n", i), &mut ansi);
+    }
+
+    assert_eq!(lines.len(), 2500);
+    assert_eq!(lines[1500].text, "1500");
+    assert!(lines.get(2500).is_none());
+    let rest: Vec<&str> = lines.iter_from(2047).map(|l| l.text.as_str()).collect();
+    assert_eq!(rest.len(), 453);
+    assert_eq!(rest[..2], ["2047", "2048"]);
+}
+
+#[test]
+fn links_found_across_reads() {
+    let mut lines = Lines::default();
+    let mut ansi = Ansi::default();
+    lines.push_str("see https://exa", &mut ansi);
+    lines.push_str("mple.com and a@b.org\n", &mut ansi);
+
+    let spans = &lines[0].spans;
+    assert_eq!(spans.len(), 1);
+    let links: Vec<(&str, bool)> = spans[0]
+        .links
+        .iter()
+        .map(|link| (&spans[0].text[link.range.clone()], link.email))
+        .collect();
+    assert_eq!(links, [("https://example.com", false), ("a@b.org", true)]);
+}
+
+#[test]
+fn truncate_drops_oldest_chunks() {
+    let mut output = vec![];
+    let mut ansi = Ansi::default();
+    // Every line is 4 bytes, so a chunk is 4096
+    for i in 0..2500 {
+        push_text(&mut output, &format!("{:04}\n", i), &mut ansi);
+    }
+
+    truncate(&mut output, 5000);
+    let lines = match &output[..] {
+        [(0, OutputType::Lines(lines))] => lines,
+        _ => panic!("Expected lines"),
+    };
+    assert_eq!(lines.dropped_bytes(), 2 * 4096);
+    assert_eq!(lines.first(), 2048);
+    assert_eq!(lines.len(), 2500);
+    assert!(lines.get(100).is_none());
+    assert_eq!(lines[2048].text, "2048");
+    assert!(lines.text().starts_with("2048\n"));
+
+    // The chunk being appended to is kept
+    truncate(&mut output, 0);
+    assert!(matches!(&output[..], [(0, OutputType::Lines(lines))] if lines.first() == 2048));
+}
+
+#[test]
+fn parser_keeps_split_message() {
+    let message = format!("{m}7{m}mark{m}Label{m}\n", m = MAGIC);
+    let (first, second) = message.split_at(message.len() / 2);
+    let mut parser = Parser::default();
+
+    let parsed = parser.feed(&format!("before\n{}", first));
+    assert!(matches!(&parsed[..], [Parsed::Text(text)] if text == "before\n"));
+
+    let parsed = parser.feed(&format!("{}after", second));
+    assert!(matches!(
+        &parsed[..],
+        [Parsed::Message(7, OutputType::Mark(label)), Parsed::Text(text)]
+            if label == "Label" && text == "after"
+    ));
+}
+
+#[test]
+fn indented_block_len_stops_at_unindented() {
+    let lines = [
+        "  File \"main.py\", line 3, in <module>",
+        "    main()",
+        "\tat Main.java:5",
+        "",
+        "    after empty line",
+    ];
+    assert_eq!(indented_block_len(lines.iter().copied()), 3);
+    assert_eq!(indented_block_len(lines[3..].iter().copied()), 0);
+}
+
+#[test]
+fn copy_with_colors() {
+    let mut output = vec![];
+    let mut ansi = Ansi::default();
+    push_text(&mut output, "plain \x1b[1;31mred<b>\x1b[0m\n", &mut ansi);
+    output.push((0, OutputType::Mark("Done".into())));
+
+    assert_eq!(
+        copy::ansi_text(&output),
+        "plain \x1b[1;31mred<b>\x1b[0m\nDone\n"
+    );
+    assert_eq!(
+        copy::html(&output, &AnsiPalette::default()),
+        "<pre style=\"font-family: monospace\">plain \
+         <span style=\"color: #cd3131; font-weight: bold\">red&lt;b&gt;</span>\nDone\n</pre>"
+    );
+    assert!(copy::html(&output, &AnsiPalette::deuteranopia()).contains("color: #d55e00"));
+}
+
+#[test]
+fn find_panic_since_1_73() {
+    let text = "working\nthread 'main' panicked at src/main.rs:4:5:\nfile not found\nat all\n\
+                stack backtrace:\n   0: rust_begin_unwind\n             at src/panicking.rs:1:1\n\
+                note: Some details are omitted\n";
+    let panic = panic::find(text).unwrap();
+    assert_eq!(panic.thread, "main");
+    assert_eq!(panic.message, "file not found\nat all");
+    assert_eq!(panic.location, "src/main.rs:4:5");
+    assert_eq!(
+        panic.backtrace.as_deref(),
+        Some("   0: rust_begin_unwind\n             at src/panicking.rs:1:1")
+    );
+}
+
+#[test]
+fn find_panic_before_1_73() {
+    let text = "thread '<unnamed>' panicked at 'bad', value', src/lib.rs:10:9\n\
+                note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+    let panic = panic::find(text).unwrap();
+    assert_eq!(panic.thread, "<unnamed>");
+    assert_eq!(panic.message, "bad', value");
+    assert_eq!(panic.location, "src/lib.rs:10:9");
+    assert_eq!(panic.backtrace, None);
+
+    let multiline = panic::find("thread 'main' panicked at 'a\nb', src/main.rs:1:1\n").unwrap();
+    assert_eq!(multiline.message, "a\nb");
+    assert!(panic::find("no panic here\n").is_none());
+}
+
+#[test]
+#[cfg(unix)]
+fn input_sent_to_running_child() {
+    let child = ChildApp::run(
+        Some(std::path::Path::new("sh")),
+        vec!["-c".into(), "read name; echo \"Hi $name\"".into()],
+        None,
+        None,
+        None,
+        &[],
+        eframe::egui::Context::default(),
+    )
+    .unwrap();
+    let mut output = Output::new_with_child(child);
+    assert!(output.accepts_input());
+    let generation = output.generation();
+    output.send_input("klask");
+    assert_ne!(output.generation(), generation);
+
+    for _ in 0..500 {
+        output.read_child(None);
+        if !output.accepts_input() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(output.text(), "klask\nHi klask\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn line_before_prompt_is_shown() {
+    let child = ChildApp::run(
+        Some(std::path::Path::new("sh")),
+        vec!["-c".into(), "printf 'done\\nName: '; sleep 5".into()],
+        None,
+        None,
+        None,
+        &[],
+        eframe::egui::Context::default(),
+    )
+    .unwrap();
+    let mut output = Output::new_with_child(child);
+
+    for _ in 0..200 {
+        output.read_child(None);
+        if output.text().starts_with("done\n") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(output.text().starts_with("done\n"));
+}
+
+#[test]
+#[cfg(unix)]
+fn prompt_is_shown_before_input() {
+    let child = ChildApp::run(
+        Some(std::path::Path::new("sh")),
+        vec![
+            "-c".into(),
+            "printf 'Name: '; read name; echo \"Hi $name\"".into(),
+        ],
+        None,
+        None,
+        None,
+        &[],
+        eframe::egui::Context::default(),
+    )
+    .unwrap();
+    let mut output = Output::new_with_child(child);
+
+    for _ in 0..200 {
+        output.read_child(None);
+        if !output.text().is_empty() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(output.text(), "Name: ");
+
+    output.send_input("klask");
+    for _ in 0..500 {
+        output.read_child(None);
+        if !output.accepts_input() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(output.text(), "Name: klask\nHi klask\n");
+}
+
+#[test]
+fn status_line_is_last_text() {
+    let mut output = vec![];
+    let mut ansi = Ansi::default();
+    push_text(&mut output, "first\nsecond\n\n", &mut ansi);
+    assert_eq!(status_line(&output), Some("second"));
+
+    output.push((1, OutputType::Text("updated\n".into())));
+    output.push((0, OutputType::Mark("Phase 2".into())));
+    assert_eq!(status_line(&output), Some("updated"));
+    assert_eq!(status_line(&[]), None);
+}
+
+#[test]
+fn osc_8_hyperlinks() {
+    let mut lines = Lines::default();
+    let mut ansi = Ansi::default();
+    lines.push_str(
+        "see \x1b]8;;https://example.com/docs\x1b\\the do",
+        &mut ansi,
+    );
+    lines.push_str(
+        "cs\x1b]8;;\x07 or https://other.org\x1b]0;title\x07\n",
+        &mut ansi,
+    );
+    lines.push_str(
+        "\x1b]8;id=1;https://a.org\x07https://a.org\x1b]8;;\x1b\\\n",
+        &mut ansi,
+    );
+
+    assert_eq!(lines[0].text, "see the docs or https://other.org");
+    let links: Vec<(&str, Option<&str>)> = lines[0].spans[0]
+        .links
+        .iter()
+        .map(|link| (&lines[0].text[link.range.clone()], link.uri.as_deref()))
+        .collect();
+    assert_eq!(
+        links,
+        [
+            ("the docs", Some("https://example.com/docs")),
+            ("https://other.org", None)
+        ]
+    );
+    // A label that is a URL isn't found again
+    assert_eq!(lines[1].spans[0].links.len(), 1);
+    assert_eq!(
+        lines[1].spans[0].links[0].uri.as_deref(),
+        Some("https://a.org")
+    );
+}
+
+#[test]
+fn level_filter() {
+    assert!(!LevelFilter::default().is_active());
+    assert!(LevelFilter::default().shows(LogLevel::Trace));
+
+    let minimum = LevelFilter {
+        level: LogLevel::Warn,
+        only: false,
+    };
+    assert!(minimum.is_active());
+    assert!(!minimum.shows(LogLevel::Info));
+    assert!(minimum.shows(LogLevel::Error));
+
+    let only = LevelFilter {
+        only: true,
+        ..minimum
+    };
+    assert!(only.shows(LogLevel::Warn));
+    assert!(!only.shows(LogLevel::Error));
+
+    // Showing only trace is still a filter
+    let trace = LevelFilter {
+        level: LogLevel::Trace,
+        only: true,
+    };
+    assert!(trace.is_active());
+    assert!(!trace.shows(LogLevel::Debug));
+}
+
+#[test]
+fn level_of_lines() {
+    assert_eq!(
+        line_level("2024-01-01T10:00:00Z ERROR server: failed"),
+        Some((LogLevel::Error, 21..26))
+    );
+    assert_eq!(
+        line_level("[warn] disk almost full"),
+        Some((LogLevel::Warn, 1..5))
+    );
+    assert_eq!(line_level("│ INFO │ ok"), Some((LogLevel::Info, 4..8)));
+    // Lowercase words of a message aren't levels
+    assert_eq!(line_level("no error found"), None);
+    assert_eq!(line_level("ERRORS: 0"), None);
+    let late = format!("{} ERROR", "x".repeat(80));
+    assert_eq!(line_level(&late), None);
+}
@@ -0,0 +1,130 @@
+use super::{LogLevel, LogRecord};
+use std::fmt::Debug;
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    Layer,
+};
+
+/// Returns a [`tracing`] subscriber that displays events in the output with
+/// level colors, a level filter and collapsible spans. Requires the `tracing` feature.
+/// ```no_run
+/// # use clap::App;
+/// # use klask::Settings;
+/// klask::run_app(App::new("Example"), Settings::default(), |_| {
+///     tracing::subscriber::set_global_default(klask::output::tracing_subscriber()).unwrap();
+///     let _span = tracing::info_span!("download", file = "a.txt").entered();
+///     tracing::info!(bytes = 1024, "Finished");
+/// });
+/// ```
+pub fn tracing_subscriber() -> impl Subscriber + Send + Sync {
+    tracing_subscriber::registry().with(KlaskLayer)
+}
+
+struct KlaskLayer;
+
+/// Formatted span name and fields, stored in span extensions
+struct SpanText(String);
+
+impl<S> Layer<S> for KlaskLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = FieldVisitor::default();
+            attrs.record(&mut visitor);
+            let text = visitor.span_text(span.name());
+            span.extensions_mut().insert(SpanText(text));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = FieldVisitor::default();
+            values.record(&mut visitor);
+            if let Some(SpanText(text)) = span.extensions_mut().get_mut::<SpanText>() {
+                for (key, value) in visitor.fields {
+                    text.push_str(&format!(" {}={}", key, value));
+                }
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| {
+                        span.extensions()
+                            .get::<SpanText>()
+                            .map(|SpanText(text)| text.clone())
+                            .unwrap_or_else(|| span.name().to_string())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let metadata = event.metadata();
+        LogRecord {
+            level: match *metadata.level() {
+                Level::ERROR => LogLevel::Error,
+                Level::WARN => LogLevel::Warn,
+                Level::INFO => LogLevel::Info,
+                Level::DEBUG => LogLevel::Debug,
+                Level::TRACE => LogLevel::Trace,
+            },
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            spans,
+            fields: visitor.fields,
+        }
+        .send();
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl FieldVisitor {
+    fn span_text(self, name: &str) -> String {
+        let mut text = name.to_string();
+        for (key, value) in self.fields {
+            text.push_str(&format!(" {}={}", key, value));
+        }
+        text
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{app_state::AppState, localization::Localization};
+use eframe::egui::{self, Align, Align2, Context, Key, Modifiers, TextEdit};
+
+/// Something the user can jump to from the command palette.
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    /// Subcommand names leading to the entry
+    pub path: Vec<String>,
+    /// Index of the argument in its subcommand, `None` for subcommands themselves
+    pub arg: Option<usize>,
+    pub label: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    const MAX_RESULTS: usize = 10;
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    /// Returns true if the user jumped somewhere.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        state: &mut AppState,
+        localization: &Localization,
+    ) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let mut entries = vec![];
+        state.palette_entries(&mut vec![], &mut entries);
+
+        let mut results: Vec<(i32, PaletteEntry)> = entries
+            .into_iter()
+            .filter_map(|e| Some((e.score(&self.query)?, e)))
+            .collect();
+        results.sort_by(|(a, _), (b, _)| b.cmp(a));
+        results.truncate(Self::MAX_RESULTS);
+
+        let (up, down, enter, escape) = {
+            let mut input = ctx.input_mut();
+            (
+                input.consume_key(Modifiers::NONE, Key::ArrowUp),
+                input.consume_key(Modifiers::NONE, Key::ArrowDown),
+                input.consume_key(Modifiers::NONE, Key::Enter),
+                input.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        };
+
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let mut chosen = enter.then_some(self.selected);
+
+        egui::Window::new("command_palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, [0.0, 40.0])
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    TextEdit::singleline(&mut self.query).hint_text(&localization.palette_hint),
+                );
+                response.request_focus();
+                if response.changed() {
+                    self.selected = 0;
+                }
+
+                for (index, (_, entry)) in results.iter().enumerate() {
+                    // Subcommand entries contain their own name in the path
+                    let parents = match entry.arg {
+                        Some(_) => &entry.path[..],
+                        None => &entry.path[..entry.path.len() - 1],
+                    };
+                    let text = if parents.is_empty() {
+                        entry.label.clone()
+                    } else {
+                        format!("{} › {}", parents.join(" › "), entry.label)
+                    };
+
+                    let response = ui.selectable_label(index == self.selected, text);
+                    if index == self.selected && (up || down) {
+                        response.scroll_to_me(Some(Align::Center));
+                    }
+                    let response = if entry.detail.is_empty() {
+                        response
+                    } else {
+                        response.on_hover_text(&entry.detail)
+                    };
+                    if response.clicked() {
+                        chosen = Some(index);
+                    }
+                }
+            });
+
+        if escape {
+            self.toggle();
+        }
+
+        match chosen.and_then(|i| results.get(i)) {
+            Some((_, entry)) => {
+                state.jump_to(&entry.path, entry.arg);
+                self.toggle();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl PaletteEntry {
+    fn score(&self, query: &str) -> Option<i32> {
+        if query.trim().is_empty() {
+            return Some(0);
+        }
+
+        // Prefer matches in the name over matches in the help text
+        fuzzy_score(query, &self.label)
+            .map(|s| s * 2)
+            .or_else(|| fuzzy_score(query, &self.path.join(" ")))
+            .or_else(|| fuzzy_score(query, &self.detail))
+    }
+}
+
+/// Scores how well `query` matches `text` as a case-insensitive subsequence.
+/// Consecutive characters and matches at the start score higher.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut text = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut score = 0;
+    let mut last = None;
+
+    for q in query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+    {
+        let index = loop {
+            let (i, c) = text.next()?;
+            if c == q {
+                break i;
+            }
+        };
+
+        score += match last {
+            _ if index == 0 => 4,
+            Some(last) if last + 1 == index => 3,
+            _ => 1,
+        };
+        last = Some(index);
+    }
+
+    Some(score)
+}
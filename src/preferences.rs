@@ -0,0 +1,265 @@
+use crate::{output::AnsiPalette, Localization};
+use eframe::egui::{
+    Color32, ComboBox, DragValue, Grid, Id, Slider, Stroke, Style, TextStyle, Ui, Visuals,
+};
+use serde::{Deserialize, Serialize};
+
+/// Key of the preferences in eframe storage
+pub const STORAGE_KEY: &str = "klask_preferences";
+
+/// Choices of the end user in the preferences tab, see [`crate::Settings::enable_preferences`].
+/// Everything left at its default keeps what the author set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub theme: Theme,
+    /// Multiplies the scale of the screen
+    pub scale: Option<f32>,
+    /// Only applied when klask starts, since the form is built with the language
+    pub language: Language,
+    pub output_font_size: Option<f32>,
+    pub notify_on_finish: Option<bool>,
+    pub palette: Palette,
+    /// Language klask was started with
+    #[serde(skip)]
+    started_with: Language,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    #[serde(alias = "default")]
+    Default,
+    #[serde(alias = "light")]
+    Light,
+    #[serde(alias = "dark")]
+    Dark,
+    #[serde(alias = "high_contrast", alias = "high-contrast")]
+    HighContrast,
+}
+
+impl Theme {
+    /// None keeps the visuals of the author
+    pub fn visuals(self) -> Option<Visuals> {
+        match self {
+            Theme::Default => None,
+            Theme::Light => Some(Visuals::light()),
+            Theme::Dark => Some(Visuals::dark()),
+            Theme::HighContrast => Some(high_contrast()),
+        }
+    }
+}
+
+/// Colors of the output, see [`crate::Settings::ansi_palette`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Default,
+    Standard,
+    Deuteranopia,
+}
+
+/// White text and outlines on black, see [`crate::Settings::high_contrast`]
+pub fn high_contrast() -> Visuals {
+    let mut visuals = Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.hyperlink_color = Color32::from_rgb(255, 255, 0);
+    visuals.faint_bg_color = Color32::from_gray(24);
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.code_bg_color = Color32::from_gray(32);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 90, 200);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::WHITE);
+    let widgets = &mut visuals.widgets;
+    for (widget, fill) in [
+        (&mut widgets.noninteractive, Color32::BLACK),
+        (&mut widgets.inactive, Color32::from_gray(20)),
+        (&mut widgets.hovered, Color32::from_gray(60)),
+        (&mut widgets.active, Color32::from_gray(90)),
+        (&mut widgets.open, Color32::from_gray(40)),
+    ] {
+        widget.bg_fill = fill;
+        widget.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+        widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    widgets.hovered.bg_stroke = Stroke::new(2.0, Color32::from_rgb(255, 255, 0));
+    visuals
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(alias = "default")]
+    Default,
+    #[serde(alias = "english")]
+    English,
+    #[serde(alias = "polish")]
+    Polish,
+}
+
+impl Preferences {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let mut preferences: Self = storage
+            .and_then(|storage| eframe::get_value(storage, STORAGE_KEY))
+            .unwrap_or_default();
+        preferences.started_with = preferences.language;
+        preferences
+    }
+
+    /// The style of the author with the chosen theme
+    pub fn style(&self, style: &Style) -> Style {
+        let mut style = style.clone();
+        if let Some(visuals) = self.theme.visuals() {
+            style.visuals = visuals;
+        }
+        style
+    }
+
+    /// The colors of the output chosen, or `palette` of the author
+    pub fn ansi_palette(&self, palette: AnsiPalette) -> AnsiPalette {
+        match self.palette {
+            Palette::Default => palette,
+            Palette::Standard => AnsiPalette::standard(),
+            Palette::Deuteranopia => AnsiPalette::deuteranopia(),
+        }
+    }
+
+    /// None keeps the localization of the author
+    pub fn localization(&self) -> Option<Localization> {
+        match self.language {
+            Language::Default => None,
+            Language::English => Some(Localization::default()),
+            Language::Polish => Some(Localization::polish()),
+        }
+    }
+
+    /// Returns true if anything changed. `notify_on_finish` and `output_font_size`
+    /// are the choices of the author, notifications aren't offered if it's None.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        localization: &Localization,
+        notify_on_finish: Option<bool>,
+        output_font_size: Option<f32>,
+    ) -> bool {
+        let previous = self.clone();
+
+        Grid::new("klask_preferences").show(ui, |ui| {
+            ui.label(&localization.theme);
+            let theme_name = |theme| match theme {
+                Theme::Default => &localization.default,
+                Theme::Light => &localization.theme_light,
+                Theme::Dark => &localization.theme_dark,
+                Theme::HighContrast => &localization.theme_high_contrast,
+            };
+            ComboBox::from_id_source("klask_theme")
+                .selected_text(theme_name(self.theme))
+                .show_ui(ui, |ui| {
+                    for theme in [
+                        Theme::Default,
+                        Theme::Light,
+                        Theme::Dark,
+                        Theme::HighContrast,
+                    ] {
+                        ui.selectable_value(&mut self.theme, theme, theme_name(theme));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(&localization.ansi_palette);
+            let palette_name = |palette| match palette {
+                Palette::Default => &localization.default,
+                Palette::Standard => &localization.palette_standard,
+                Palette::Deuteranopia => &localization.palette_deuteranopia,
+            };
+            ComboBox::from_id_source("klask_ansi_palette")
+                .selected_text(palette_name(self.palette))
+                .show_ui(ui, |ui| {
+                    for palette in [Palette::Default, Palette::Standard, Palette::Deuteranopia] {
+                        ui.selectable_value(&mut self.palette, palette, palette_name(palette));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(&localization.ui_scale);
+            // Only applied once the slider is released, since it moves while scaling
+            let scale_id = Id::new("klask_scale");
+            let mut scale = ui
+                .data()
+                .get_temp(scale_id)
+                .unwrap_or_else(|| self.scale.unwrap_or(1.0));
+            if ui.add(Slider::new(&mut scale, 0.5..=3.0)).dragged() {
+                ui.data().insert_temp(scale_id, scale);
+            } else {
+                ui.data().remove::<f32>(scale_id);
+                if scale != self.scale.unwrap_or(1.0) {
+                    self.scale = Some(scale);
+                }
+            }
+            ui.end_row();
+
+            ui.label(&localization.language);
+            let language_name = |language| match language {
+                Language::Default => localization.default.as_str(),
+                Language::English => "English",
+                Language::Polish => "Polski",
+            };
+            ui.horizontal(|ui| {
+                ComboBox::from_id_source("klask_language")
+                    .selected_text(language_name(self.language))
+                    .show_ui(ui, |ui| {
+                        for language in [Language::Default, Language::English, Language::Polish] {
+                            ui.selectable_value(
+                                &mut self.language,
+                                language,
+                                language_name(language),
+                            );
+                        }
+                    });
+                if self.language != self.started_with {
+                    ui.label(&localization.restart_to_apply);
+                }
+            });
+            ui.end_row();
+
+            ui.label(&localization.output_font_size);
+            let mut size = self
+                .output_font_size
+                .or(output_font_size)
+                .or_else(|| {
+                    ui.style()
+                        .text_styles
+                        .get(&TextStyle::Monospace)
+                        .map(|font| font.size)
+                })
+                .unwrap_or(14.0);
+            if ui
+                .add(DragValue::new(&mut size).clamp_range(6.0..=48.0))
+                .changed()
+            {
+                self.output_font_size = Some(size);
+            }
+            ui.end_row();
+
+            if let Some(notify_on_finish) = notify_on_finish {
+                ui.label(&localization.notify_on_finish);
+                let mut notify = self.notify_on_finish.unwrap_or(notify_on_finish);
+                if ui.checkbox(&mut notify, "").changed() {
+                    self.notify_on_finish = Some(notify);
+                }
+                ui.end_row();
+            }
+        });
+
+        if ui.button(&localization.reset_preferences).clicked() {
+            *self = Self {
+                started_with: self.started_with,
+                ..Self::default()
+            };
+        }
+
+        *self != previous
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,65 @@
+use super::{Language, Palette, Preferences, Theme};
+use crate::{output::AnsiPalette, Localization};
+use eframe::egui::{Color32, Style, Visuals};
+
+#[test]
+fn defaults_keep_settings() {
+    let preferences = Preferences::default();
+    let mut style = Style::default();
+    style.visuals.hyperlink_color = eframe::egui::Color32::RED;
+    assert_eq!(preferences.style(&style).visuals, style.visuals);
+    assert_eq!(preferences.localization(), None);
+}
+
+#[test]
+fn chosen_theme_and_language() {
+    let preferences = Preferences {
+        theme: Theme::Light,
+        language: Language::Polish,
+        ..Preferences::default()
+    };
+    assert_eq!(
+        preferences.style(&Style::default()).visuals,
+        Visuals::light()
+    );
+    assert_eq!(preferences.localization(), Some(Localization::polish()));
+}
+
+#[test]
+fn high_contrast_and_palette() {
+    let preferences = Preferences {
+        theme: Theme::HighContrast,
+        palette: Palette::Deuteranopia,
+        ..Preferences::default()
+    };
+    let visuals = preferences.style(&Style::default()).visuals;
+    assert_eq!(visuals.override_text_color, Some(Color32::WHITE));
+    assert_eq!(visuals.extreme_bg_color, Color32::BLACK);
+    assert_eq!(
+        preferences.ansi_palette(AnsiPalette::standard()),
+        AnsiPalette::deuteranopia()
+    );
+    assert_eq!(
+        Preferences::default().ansi_palette(AnsiPalette::deuteranopia()),
+        AnsiPalette::deuteranopia()
+    );
+
+    // Red and green are told apart by more than their red and green channels
+    let palette = AnsiPalette::deuteranopia();
+    let (failure, success) = (palette.failure(), palette.success());
+    assert!(failure.b().abs_diff(success.b()) > 128);
+}
+
+#[test]
+fn missing_fields_are_default() {
+    let preferences: Preferences =
+        serde_json::from_str(r#"{"theme": "Dark", "scale": 1.5}"#).unwrap();
+    assert_eq!(
+        preferences,
+        Preferences {
+            theme: Theme::Dark,
+            scale: Some(1.5),
+            ..Preferences::default()
+        }
+    );
+}
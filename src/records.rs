@@ -0,0 +1,293 @@
+use crate::{
+    format_duration,
+    output::{self, AnsiPalette},
+    Localization,
+};
+use eframe::egui::{CollapsingHeader, Color32, Grid, RichText, Ui};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// A finished run, saved to disk when [`crate::Settings::run_records`] is enabled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// RFC 3339 timestamp of when the run started
+    pub started: String,
+    pub args: Vec<String>,
+    /// Names of the selected subcommands
+    #[serde(default)]
+    pub subcommands: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub working_dir: Option<String>,
+    pub success: bool,
+    /// None for in-process runs and processes killed by a signal
+    pub exit_code: Option<i32>,
+    pub duration_secs: f64,
+    /// Only saved with [`crate::Settings::run_record_output`]
+    pub output: Option<String>,
+}
+
+/// Aggregated over the saved runs of one subcommand
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statistics {
+    /// Names of the subcommands joined with spaces, empty for the binary itself
+    pub subcommand: String,
+    pub runs: usize,
+    pub successes: usize,
+    pub average: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+/// Groups `records` by subcommand, sorted by name
+pub fn statistics(records: &[RunRecord]) -> Vec<Statistics> {
+    let mut groups: BTreeMap<String, Vec<&RunRecord>> = BTreeMap::new();
+    for record in records {
+        groups
+            .entry(record.subcommands.join(" "))
+            .or_default()
+            .push(record);
+    }
+
+    groups
+        .into_iter()
+        .map(|(subcommand, records)| {
+            let mut durations: Vec<f64> = records.iter().map(|r| r.duration_secs).collect();
+            durations.sort_by(f64::total_cmp);
+            let secs = |secs: f64| Duration::try_from_secs_f64(secs).unwrap_or_default();
+
+            Statistics {
+                subcommand,
+                runs: records.len(),
+                successes: records.iter().filter(|r| r.success).count(),
+                average: secs(durations.iter().sum::<f64>() / durations.len() as f64),
+                median: secs(percentile(&durations, 0.5)),
+                p95: secs(percentile(&durations, 0.95)),
+            }
+        })
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let rank = (p * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+/// Records saved in the platform data directory, one JSON file per run
+#[derive(Debug)]
+pub struct Records {
+    app_name: String,
+    /// None if the platform has no data directory
+    dir: Option<PathBuf>,
+    limit: usize,
+    pub save_output: bool,
+    /// Newest first
+    loaded: Vec<RunRecord>,
+    /// Error from the last save or load
+    error: Option<String>,
+}
+
+impl Records {
+    pub fn new(app_name: &str, limit: usize, save_output: bool) -> Self {
+        let mut records = Self {
+            app_name: app_name.to_string(),
+            dir: dirs::data_dir().map(|dir| dir.join(app_name).join("runs")),
+            limit,
+            save_output,
+            loaded: vec![],
+            error: None,
+        };
+        records.load();
+        records
+    }
+
+    /// Saves `record`, then deletes the oldest records over the limit
+    pub fn save(&mut self, record: RunRecord) {
+        self.error = self.try_save(&record).err().map(|e| e.to_string());
+        if self.error.is_none() {
+            self.load();
+        }
+    }
+
+    fn try_save(&self, record: &RunRecord) -> io::Result<()> {
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return Ok(()),
+        };
+        fs::create_dir_all(dir)?;
+
+        // Timestamps sort chronologically, colons aren't allowed in file names on Windows
+        let name = format!("{}.json", record.started.replace(':', "-"));
+        fs::write(dir.join(name), serde_json::to_vec_pretty(record)?)?;
+
+        let files = Self::files(dir)?;
+        for file in &files[..files.len().saturating_sub(self.limit)] {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    fn load(&mut self) {
+        let dir = match &self.dir {
+            Some(dir) if dir.exists() => dir,
+            _ => return,
+        };
+
+        match Self::files(dir) {
+            Ok(files) => {
+                // Skip files that can't be read, instead of hiding every record
+                self.loaded = files
+                    .iter()
+                    .rev()
+                    .filter_map(|file| fs::read(file).ok())
+                    .filter_map(|json| serde_json::from_slice(&json).ok())
+                    .collect();
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Record files, oldest first
+    fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let mut files = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect::<Vec<_>>();
+        files.sort();
+        Ok(files)
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, localization: &Localization, palette: &AnsiPalette) {
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        if !self.loaded.is_empty() {
+            CollapsingHeader::new(&localization.statistics)
+                .id_source("klask_run_statistics")
+                .show(ui, |ui| show_statistics(ui, self, localization));
+        }
+
+        for (index, record) in self.loaded.iter().enumerate() {
+            let status = if record.success {
+                RichText::new("✔").color(palette.success())
+            } else {
+                RichText::new("✖").color(palette.failure())
+            };
+
+            ui.horizontal(|ui| {
+                ui.label(status);
+                CollapsingHeader::new(format!("{}  {}", record.started, record.args.join(" ")))
+                    .id_source(("klask_run_record", index))
+                    .show(ui, |ui| show_record(ui, record, localization, palette));
+            });
+        }
+    }
+}
+
+fn show_statistics(ui: &mut Ui, records: &Records, localization: &Localization) {
+    Grid::new("klask_run_statistics_grid")
+        .striped(true)
+        .num_columns(6)
+        .show(ui, |ui| {
+            for header in [
+                &localization.subcommand,
+                &localization.runs,
+                &localization.success_rate,
+                &localization.average,
+                &localization.median,
+                &localization.percentile_95,
+            ] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+
+            for stats in statistics(&records.loaded) {
+                if stats.subcommand.is_empty() {
+                    ui.label(&records.app_name);
+                } else {
+                    ui.label(&stats.subcommand);
+                }
+                ui.label(stats.runs.to_string());
+                ui.label(format!(
+                    "{:.0}%",
+                    100.0 * stats.successes as f64 / stats.runs as f64
+                ));
+                for duration in [stats.average, stats.median, stats.p95] {
+                    ui.label(format_short_duration(duration));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Like [`format_duration`], but with tenths of a second for short runs
+fn format_short_duration(duration: Duration) -> String {
+    if duration.as_secs() < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format_duration(duration)
+    }
+}
+
+fn show_record(
+    ui: &mut Ui,
+    record: &RunRecord,
+    localization: &Localization,
+    palette: &AnsiPalette,
+) {
+    Grid::new("klask_run_record_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label(&localization.arguments);
+            ui.label(record.args.join(" "));
+            ui.end_row();
+
+            if !record.env.is_empty() {
+                ui.label(&localization.env_variables);
+                let env: Vec<String> = record
+                    .env
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect();
+                ui.label(env.join(" "));
+                ui.end_row();
+            }
+
+            if let Some(working_dir) = &record.working_dir {
+                ui.label(&localization.working_directory);
+                ui.label(working_dir);
+                ui.end_row();
+            }
+
+            if let Some(exit_code) = record.exit_code {
+                ui.label(&localization.exit_code);
+                ui.label(exit_code.to_string());
+                ui.end_row();
+            }
+
+            ui.label(&localization.duration);
+            let duration = Duration::try_from_secs_f64(record.duration_secs).unwrap_or_default();
+            ui.label(format_duration(duration));
+            ui.end_row();
+        });
+
+    if let Some(text) = &record.output {
+        CollapsingHeader::new(&localization.output)
+            .id_source(("klask_run_record_output", &record.started))
+            .show(ui, |ui| {
+                ui.scope(|ui| {
+                    ui.style_mut().override_text_style = Some(eframe::egui::TextStyle::Monospace);
+                    output::format_output(ui, text, palette);
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests;
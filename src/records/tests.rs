@@ -0,0 +1,35 @@
+use super::{statistics, RunRecord};
+use std::time::Duration;
+
+fn record(subcommands: &[&str], success: bool, duration_secs: f64) -> RunRecord {
+    RunRecord {
+        started: String::new(),
+        args: vec![],
+        subcommands: subcommands.iter().map(ToString::to_string).collect(),
+        env: vec![],
+        working_dir: None,
+        success,
+        exit_code: None,
+        duration_secs,
+        output: None,
+    }
+}
+
+#[test]
+fn statistics_per_subcommand() {
+    let mut records: Vec<RunRecord> = (1..=20)
+        .map(|secs| record(&["build"], secs % 4 != 0, secs as f64))
+        .collect();
+    records.push(record(&[], true, 1.0));
+
+    let stats = statistics(&records);
+    assert_eq!(stats.len(), 2);
+    assert_eq!((stats[0].subcommand.as_str(), stats[0].runs), ("", 1));
+
+    let build = &stats[1];
+    assert_eq!(build.subcommand, "build");
+    assert_eq!((build.runs, build.successes), (20, 15));
+    assert_eq!(build.average, Duration::from_secs_f64(10.5));
+    assert_eq!(build.median, Duration::from_secs(10));
+    assert_eq!(build.p95, Duration::from_secs(19));
+}
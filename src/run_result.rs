@@ -0,0 +1,39 @@
+use crate::output::OutputType;
+use std::fmt::Display;
+
+/// What the closure passed to [`crate::run_app`] or [`crate::run_derived`] can return.
+/// A returned error is shown in a banner at the end of the output, and the binary
+/// exits with code 1.
+/// ```no_run
+/// # use clap::{App, Arg};
+/// # use klask::Settings;
+/// let app = App::new("Example").arg(Arg::new("file").required(true));
+///
+/// klask::run_app(app, Settings::default(), |matches| {
+///     let text = std::fs::read_to_string(matches.value_of("file").unwrap())?;
+///     println!("{}", text);
+///     Ok::<_, std::io::Error>(())
+/// });
+/// ```
+pub trait RunResult {
+    /// Sends the error to the GUI, if there is one, and returns the exit code
+    fn report(self) -> i32;
+}
+
+impl RunResult for () {
+    fn report(self) -> i32 {
+        0
+    }
+}
+
+impl<E: Display> RunResult for Result<(), E> {
+    fn report(self) -> i32 {
+        match self {
+            Ok(()) => 0,
+            Err(err) => {
+                OutputType::Error(err.to_string()).send(0);
+                1
+            }
+        }
+    }
+}
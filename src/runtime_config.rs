@@ -0,0 +1,96 @@
+use crate::{
+    preferences::{Language, Theme},
+    Localization, Settings,
+};
+use eframe::egui::Vec2;
+use serde::Deserialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable with the path of the runtime configuration
+pub const ENV_VAR: &str = "KLASK_CONFIG";
+/// Name of the runtime configuration next to the binary
+pub const FILE_NAME: &str = "klask.toml";
+
+/// Overrides of [`Settings`] read when the GUI starts, see the crate documentation.
+/// Everything left out keeps what the author set.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeConfig {
+    pub theme: Theme,
+    pub language: Language,
+    pub tabs: Tabs,
+    /// Initial width and height of the window
+    pub window_size: Option<[f32; 2]>,
+}
+
+/// Tabs to show or hide. Tabs shown here that the author disabled have no description.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tabs {
+    pub env: Option<bool>,
+    pub stdin: Option<bool>,
+    pub working_dir: Option<bool>,
+    pub help: Option<bool>,
+    pub preferences: Option<bool>,
+}
+
+impl RuntimeConfig {
+    /// Reads the file from [`ENV_VAR`], or [`FILE_NAME`] next to the binary if it exists.
+    /// Errors are printed, since the window isn't open yet, and the settings are kept.
+    pub fn load() -> Self {
+        let path = match std::env::var_os(ENV_VAR) {
+            Some(path) => PathBuf::from(path),
+            None => match std::env::current_exe() {
+                Ok(exe) if exe.with_file_name(FILE_NAME).is_file() => exe.with_file_name(FILE_NAME),
+                _ => return Self::default(),
+            },
+        };
+
+        Self::read(&path).unwrap_or_else(|err| {
+            eprintln!("klask: can't read {}: {}", path.display(), err);
+            Self::default()
+        })
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Overrides the settings, returns the initial size of the window
+    pub fn apply(self, settings: &mut Settings) -> Option<Vec2> {
+        if let Some(visuals) = self.theme.visuals() {
+            settings.style.visuals = visuals;
+        }
+        match self.language {
+            Language::Default => {}
+            Language::English => settings.localization = Localization::default(),
+            Language::Polish => settings.localization = Localization::polish(),
+        }
+
+        let toggle = |tab: &mut Option<String>, enabled: Option<bool>| match enabled {
+            Some(true) => {
+                tab.get_or_insert_with(String::new);
+            }
+            Some(false) => *tab = None,
+            None => {}
+        };
+        toggle(&mut settings.enable_env, self.tabs.env);
+        toggle(&mut settings.enable_stdin, self.tabs.stdin);
+        toggle(&mut settings.enable_working_dir, self.tabs.working_dir);
+        if let Some(help) = self.tabs.help {
+            settings.enable_help_tab = help;
+        }
+        if let Some(preferences) = self.tabs.preferences {
+            settings.enable_preferences = preferences;
+        }
+
+        self.window_size.map(Vec2::from)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,56 @@
+use super::{RuntimeConfig, Tabs};
+use crate::{preferences::Theme, Localization, Settings};
+use eframe::egui::{Vec2, Visuals};
+
+#[test]
+fn overrides_settings() {
+    let config: RuntimeConfig = toml::from_str(
+        r#"
+        theme = "dark"
+        language = "polish"
+        window_size = [800, 600]
+
+        [tabs]
+        env = false
+        stdin = true
+        help = true
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.theme, Theme::Dark);
+
+    let mut settings = Settings {
+        enable_env: Some("Description".into()),
+        enable_working_dir: Some("Working dir".into()),
+        ..Settings::default()
+    };
+    assert_eq!(config.apply(&mut settings), Some(Vec2::new(800.0, 600.0)));
+    assert_eq!(settings.style.visuals, Visuals::dark());
+    assert_eq!(settings.localization, Localization::polish());
+    assert_eq!(settings.enable_env, None);
+    assert_eq!(settings.enable_stdin, Some(String::new()));
+    assert_eq!(settings.enable_working_dir, Some("Working dir".into()));
+    assert!(settings.enable_help_tab);
+    assert!(!settings.enable_preferences);
+}
+
+#[test]
+fn empty_keeps_settings() {
+    let config: RuntimeConfig = toml::from_str("").unwrap();
+    assert_eq!(config, RuntimeConfig::default());
+    assert_eq!(config.tabs, Tabs::default());
+
+    let expected = Settings {
+        enable_stdin: Some("Input".into()),
+        ..Settings::default()
+    };
+    let mut settings = expected.clone();
+    assert_eq!(config.apply(&mut settings), None);
+    assert_eq!(settings, expected);
+}
+
+#[test]
+fn unknown_fields_are_errors() {
+    assert!(toml::from_str::<RuntimeConfig>("colour = \"dark\"").is_err());
+    assert!(toml::from_str::<RuntimeConfig>("[tabs]\nenvironment = true").is_err());
+}
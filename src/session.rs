@@ -0,0 +1,167 @@
+use crate::Localization;
+use eframe::egui::{Button, Checkbox, Color32, ComboBox, Ui};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Input and output of a run with their timestamps, see [`crate::Settings::session_dir`].
+/// Replaying it shows the output like the run did, at the original or a faster speed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub args: Vec<String>,
+    pub success: bool,
+    /// None for processes killed by a signal
+    pub exit_code: Option<i32>,
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    /// Seconds since the run started
+    pub at: f64,
+    pub stream: Stream,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stream {
+    /// Lines sent to the running binary, input passed when starting isn't recorded
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+/// Speeds of replaying, with their names. Infinity shows everything at once.
+pub const SPEEDS: [(f32, &str); 4] = [
+    (1.0, "1×"),
+    (2.0, "2×"),
+    (10.0, "10×"),
+    (f32::INFINITY, "∞"),
+];
+
+impl Session {
+    pub fn new(args: Vec<String>) -> Self {
+        Self {
+            args,
+            ..Self::default()
+        }
+    }
+
+    pub fn push(&mut self, at: Duration, stream: Stream, text: &str) {
+        self.events.push(Event {
+            at: at.as_secs_f64(),
+            stream,
+            text: text.to_string(),
+        });
+    }
+
+    /// Saves the session as JSON in `dir`, in a file named after the binary and the time
+    pub fn save(&self, dir: &Path, name: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        // Colons aren't allowed in file names on Windows
+        let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now())
+            .to_string()
+            .replace(':', "-");
+        let path = dir.join(format!("{}-{}.session.json", name, timestamp));
+        fs::write(&path, serde_json::to_vec(self)?)?;
+        Ok(path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+/// Recording and replaying sessions in the GUI, see [`crate::Settings::session_dir`]
+#[derive(Debug)]
+pub struct Sessions {
+    pub dir: PathBuf,
+    /// Whether runs are recorded
+    pub record: bool,
+    /// One of [`SPEEDS`]
+    speed: f32,
+    /// Error from the last save or load
+    error: Option<String>,
+}
+
+impl Sessions {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            record: false,
+            speed: SPEEDS[0].0,
+            error: None,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Saves a recorded session of the binary called `name`
+    pub fn save(&mut self, session: &Session, name: &str) {
+        self.error = session.save(&self.dir, name).err().map(|e| e.to_string());
+    }
+
+    /// Checkbox for recording and button for replaying, returns the session to replay
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        running: bool,
+        localization: &Localization,
+    ) -> Option<Session> {
+        ui.add_enabled(
+            !running,
+            Checkbox::new(&mut self.record, &localization.record_session),
+        );
+
+        let mut replayed = None;
+        if ui
+            .add_enabled(!running, Button::new(&localization.replay_session))
+            .clicked()
+        {
+            let file = FileDialog::new()
+                .set_directory(&self.dir)
+                .add_filter("JSON", &["json"])
+                .pick_file();
+            if let Some(file) = file {
+                match Session::load(&file) {
+                    Ok(session) => {
+                        self.error = None;
+                        replayed = Some(session);
+                    }
+                    Err(err) => self.error = Some(format!("{}: {}", file.display(), err)),
+                }
+            }
+        }
+
+        let name = |speed| {
+            SPEEDS
+                .iter()
+                .find(|(s, _)| *s == speed)
+                .map_or("", |(_, n)| n)
+        };
+        ComboBox::from_id_source("klask_replay_speed")
+            .width(0.0)
+            .selected_text(name(self.speed))
+            .show_ui(ui, |ui| {
+                for (speed, name) in SPEEDS {
+                    ui.selectable_value(&mut self.speed, speed, name);
+                }
+            })
+            .response
+            .on_hover_text(&localization.replay_speed);
+
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        replayed
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,29 @@
+use super::{Session, Stream};
+use crate::child_app::ChildApp;
+use eframe::egui;
+use std::time::{Duration, Instant};
+
+#[test]
+fn replay_output() {
+    let mut session = Session::new(vec!["--verbose".into()]);
+    session.push(Duration::ZERO, Stream::Stdout, "first\n");
+    session.push(Duration::from_millis(50), Stream::Stdin, "typed\n");
+    session.push(Duration::from_millis(100), Stream::Stdout, "last\n");
+    session.exit_code = Some(3);
+
+    let json = serde_json::to_string(&session).unwrap();
+    assert_eq!(serde_json::from_str::<Session>(&json).unwrap(), session);
+
+    let started = Instant::now();
+    let mut child = ChildApp::replay(session, 2.0, egui::Context::default());
+    let mut output = String::new();
+    while child.is_running() {
+        output.extend(child.read());
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(output, "first\ntyped\nlast\n");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(child.is_replay());
+    assert_eq!(child.try_exit_status(), Some(false));
+    assert_eq!(child.exit_code(), Some(3));
+}
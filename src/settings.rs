@@ -1,8 +1,12 @@
 // Structs are marked as `#[non_exhaustive]` to allow
 // to add other optionas alter withour breaking compatibility.
 
+use crate::{
+    output::{AnsiPalette, Renderer},
+    Keymap, Localization,
+};
 use eframe::egui::{self, style::Spacing, Style};
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, path::PathBuf, time::Duration};
 
 /// Settings for klask.
 /// Is marked with `#[non_exhaustive]` so you must construct it like this
@@ -30,11 +34,303 @@ pub struct Settings {
     /// ```
     pub custom_font: Option<Cow<'static, [u8]>>,
 
+    /// Pass a monospace font for the output of the binary, so tables and diffs line up.
+    /// Pass None to use the default monospace font. Default is None.
+    pub output_font: Option<Cow<'static, [u8]>>,
+
+    /// Size of the output font, which users can change in the preferences tab, see
+    /// [`Settings::enable_preferences`]. Pass None to use the size of monospace text
+    /// from [`Settings::style`]. Default is None.
+    pub output_font_size: Option<f32>,
+
     /// Override builtin strings. By default everything is in english.
     pub localization: Localization,
 
     /// Egui style used in GUI.
     pub style: Style,
+
+    /// Replace the visuals of [`Settings::style`] with a high-contrast theme of white text
+    /// and outlines on black. Users can also choose it in the preferences tab, see
+    /// [`Settings::enable_preferences`]. Default is false.
+    pub high_contrast: bool,
+
+    /// Colors of the output for ANSI colors and log levels, also used for the status of runs
+    /// and for diffs. [`AnsiPalette::deuteranopia`] can be told apart with red-green color
+    /// blindness, and users can also choose it in the preferences tab.
+    /// Default is [`AnsiPalette::standard`].
+    pub ansi_palette: AnsiPalette,
+
+    /// Show a desktop notification when the binary exits after running for at least
+    /// 10 seconds. Eframe doesn't tell whether the window has focus, so shorter runs are
+    /// taken as watched instead, and notifications for long runs show even when it has.
+    /// Requires the `notify` feature. Default is false.
+    #[cfg(feature = "notify")]
+    pub notify_on_finish: bool,
+
+    /// Ask for confirmation before closing the window while the binary is running,
+    /// since closing kills it. Default is true.
+    pub confirm_on_close: bool,
+
+    /// Ask for confirmation before killing the binary, showing how long it has been
+    /// running, so a misclick doesn't end a long run. Default is false.
+    pub confirm_kill: bool,
+
+    /// Disable the Run button until every required argument has a value and no argument
+    /// has an error, with the missing values listed when hovering over it. Default is false.
+    pub disable_run_until_valid: bool,
+
+    /// Show a menu bar with actions for running the binary, exporting shell completions
+    /// and a man page with the `export` feature, changing the theme and zoom, and an about
+    /// window. Default is false.
+    pub enable_menu_bar: bool,
+
+    /// Show a tab with the full `--help` output of the selected subcommand. Default is false.
+    pub enable_help_tab: bool,
+
+    /// Commands the output of the binary is piped through, like a shell pipeline.
+    /// Each command is a program followed by its arguments, for example
+    /// `vec!["grep".into(), "error".into()]`. Only the output of the last command is
+    /// displayed, along with errors from every stage. Not supported by
+    /// [`crate::run_app_in_process`]. Default is empty.
+    pub post_process: Vec<Vec<String>>,
+
+    /// Commands run one after another before the binary starts, for example to mount a share
+    /// or check a connection. Each command is a program followed by its arguments, and runs with
+    /// the environment variables and working directory of the run. If a command fails, the binary
+    /// isn't started. Their output is shown in a collapsed section above the output.
+    /// Not used for runs in the terminal or batch tabs. Default is empty.
+    pub pre_run: Vec<Vec<String>>,
+
+    /// Commands run one after another after the binary exits, for example to open the folder
+    /// with the results, like [`Settings::pre_run`]. The exit code of the binary is in the
+    /// `KLASK_EXIT_CODE` environment variable. Default is empty.
+    pub post_run: Vec<Vec<String>>,
+
+    /// Directory to write the output of every run to, in a log file named after the binary
+    /// and the time the run started. It can be turned off in the GUI. Pass None to disable.
+    /// Default is None.
+    pub log_dir: Option<PathBuf>,
+
+    /// Keep ANSI escape codes in log files. Default is false.
+    pub log_keep_ansi: bool,
+
+    /// Directory to record sessions to, which have the output and the lines sent to stdin
+    /// of a run with their timestamps. Recording is turned on in the GUI, next to a button
+    /// for replaying a session at the original or a faster speed, which shows the output
+    /// like the run did. Pass None to disable. Default is None.
+    pub session_dir: Option<PathBuf>,
+
+    /// Pass Some with the id of an argument that takes a config file to show a tab
+    /// for viewing and editing that file. `.toml` and `.json` files have their syntax checked.
+    /// Default is None.
+    pub config_arg: Option<String>,
+
+    /// Number of previous runs whose output is kept, so it can be viewed after starting
+    /// a new run. Pass 0 to disable. Default is 10.
+    pub output_history: usize,
+
+    /// Size in bytes that the plain text output of a run can take. When it's exceeded
+    /// the oldest output is dropped, and a banner shows how much. Use [`Settings::log_dir`]
+    /// to keep everything. Pass None to disable. Default is 100 MiB.
+    pub output_byte_limit: Option<usize>,
+
+    /// Save a record of every run (arguments, environment variables, working directory,
+    /// exit code and duration) in the platform data directory, and show a tab for browsing
+    /// them along with statistics for each subcommand. Default is false.
+    pub run_records: bool,
+
+    /// Number of run records to keep, older ones are deleted. Default is 100.
+    pub run_record_limit: usize,
+
+    /// Also save the output in run records. Default is false.
+    pub run_record_output: bool,
+
+    /// Allow pinning runs to a bar at the top of the window, for starting them
+    /// with one click. Pinned runs are saved in the platform data directory. Default is false.
+    pub enable_favourites: bool,
+
+    /// Number of values to remember for each text argument. Values of earlier runs are
+    /// suggested below the text field while typing, most recent first, and are saved in
+    /// the platform data directory. Pass 0 to disable. Default is 0.
+    pub value_history: usize,
+
+    /// Names for the counts of arguments that can be passed multiple times, like `-v`,
+    /// by argument id. The first name is for passing it 0 times. They are chosen from
+    /// a drop-down instead of a counter, which can't go past the last name.
+    /// ```
+    /// # use klask::Settings;
+    /// let mut settings = Settings::default();
+    /// settings.occurrence_labels.insert(
+    ///     "verbose".into(),
+    ///     vec!["Normal".into(), "Verbose".into(), "Debug".into(), "Trace".into()],
+    /// );
+    /// ```
+    /// Default is empty.
+    pub occurrence_labels: HashMap<String, Vec<String>>,
+
+    /// Start arguments with their default values filled in, instead of showing them as hints.
+    /// They can be edited or reset to the default. Clap then sees them as passed on the command
+    /// line, which matters for `ArgMatches::value_source`. Default is false.
+    pub prefill_defaults: bool,
+
+    /// Directories offered next to the buttons for selecting files and directories,
+    /// for opening the dialog in one of them. Without choosing one, the dialog of an
+    /// argument opens where its last file was picked. Default is empty.
+    pub dialog_bookmarks: Vec<PathBuf>,
+
+    /// Extensions of the files shown in file dialogs and path suggestions of arguments,
+    /// by argument id. Arguments without filters get them from globs like `*.png` in their
+    /// help, and show every file if there are none.
+    /// ```
+    /// # use klask::Settings;
+    /// let mut settings = Settings::default();
+    /// settings
+    ///     .file_filters
+    ///     .insert("input".into(), vec!["png".into(), "jpg".into()]);
+    /// ```
+    /// Default is empty.
+    pub file_filters: HashMap<String, Vec<String>>,
+
+    /// Ids of arguments with paths the binary creates, like output files. With a working
+    /// directory set, they still show what they resolve to, but without a warning that it
+    /// doesn't exist. Default is empty.
+    pub output_paths: Vec<String>,
+
+    /// Labels, hints and tooltips of arguments, by argument id, for friendlier wording
+    /// than the help shown in the terminal.
+    /// ```
+    /// # use klask::{ArgText, Settings};
+    /// let mut text = ArgText::default();
+    /// text.label = Some("Output folder".into());
+    /// text.hint = Some("Next to the input".into());
+    /// text.tooltip = Some("Where the converted images are saved".into());
+    ///
+    /// let mut settings = Settings::default();
+    /// settings.arg_texts.insert("out-dir".into(), text);
+    /// ```
+    /// Default is empty.
+    pub arg_texts: HashMap<String, ArgText>,
+
+    /// Ids of arguments left out of the GUI, like internal or dangerous options.
+    /// They can still be passed on the command line, and required ones should have
+    /// a default value, since they can't be entered. Default is empty.
+    pub hide_args: Vec<String>,
+
+    /// Names of subcommands left out of the GUI, at any depth, like commands for
+    /// development. They can still be used on the command line. Default is empty.
+    pub hide_subcommands: Vec<String>,
+
+    /// Order and grouping of the arguments, instead of one grid in the order of clap.
+    /// It's used for every subcommand, skipping ids of arguments they don't have, and
+    /// arguments left out are shown after it. The wizard isn't affected, see
+    /// [`Settings::enable_wizard`].
+    /// ```
+    /// # use klask::{LayoutItem, Settings};
+    /// let mut settings = Settings::default();
+    /// settings.layout = vec![
+    ///     LayoutItem::Arg("input".into()),
+    ///     LayoutItem::Group(
+    ///         "Output".into(),
+    ///         vec![LayoutItem::Arg("out-dir".into()), LayoutItem::Arg("format".into())],
+    ///     ),
+    ///     LayoutItem::Separator,
+    ///     LayoutItem::Columns(vec![
+    ///         vec![LayoutItem::Arg("verbose".into())],
+    ///         vec![LayoutItem::Arg("quiet".into())],
+    ///     ]),
+    /// ];
+    /// ```
+    /// Default is empty.
+    pub layout: Vec<LayoutItem>,
+
+    /// How the flags of arguments, like `-v` and `--verbose`, are shown next to their
+    /// sentence-cased names. Showing them can also be toggled in the GUI. Aliases are
+    /// listed when hovering over the name either way. Default is [`FlagNames::Hidden`].
+    pub flag_names: FlagNames,
+
+    /// Show a tab with a terminal the binary can be run in, with keyboard input going to it,
+    /// for interactive binaries like REPLs, TUIs and password prompts. Only works on Unix and
+    /// isn't supported by [`crate::run_app_in_process`]. Default is false.
+    #[cfg(feature = "terminal")]
+    pub enable_terminal: bool,
+
+    /// Show a tab for running the binary once for every row of a CSV or JSON file, with
+    /// the values of arguments from the row on top of the form. The first line of a CSV
+    /// file has argument ids, a JSON file has an array of objects with argument ids as keys.
+    /// Not supported by [`crate::run_app_in_process`]. Default is false.
+    pub enable_batch: bool,
+
+    /// Renderers of custom output messages by their kind, for displaying widgets
+    /// sent with [`crate::output::custom`]. Default is empty.
+    pub output_renderers: HashMap<String, Renderer>,
+
+    /// Expand placeholders in argument and environment variable values when running:
+    /// `{date}` or `{date:%Y-%m-%d}` with a `strftime` format, `{uuid}`, `{home}` and `{n}`,
+    /// the number of the run. `{{` and `}}` are literal braces. The expanded command
+    /// is shown below the Run button. Default is false.
+    pub enable_substitution: bool,
+
+    /// Show a tab where users can change the theme, UI scale, language, output font size and
+    /// notifications. Their choices are saved between runs of the app, and choices they
+    /// don't change keep the values from these settings. Default is false.
+    pub enable_preferences: bool,
+
+    /// Show the arguments tab as a wizard, with a step for every help heading of arguments
+    /// and for choosing a subcommand, followed by a step showing the command that will run.
+    /// Default is false.
+    pub enable_wizard: bool,
+
+    /// Find panics in the output of the binary once it exits, and show the first one in
+    /// a red section at the end of the output, with its backtrace collapsed. `RUST_BACKTRACE=1`
+    /// is set for the binary, unless it's already set. Default is false.
+    pub capture_panics: bool,
+
+    /// Close the window once the binary exits with code 0, after counting down for the
+    /// given time. The countdown is shown above the output with a button for cancelling it,
+    /// and failed runs keep the window open. Pass None to disable. Default is None.
+    pub close_on_success: Option<Duration>,
+
+    /// Keyboard shortcuts for running, killing, clearing the output, searching
+    /// and switching tabs. They're shown when hovering over buttons.
+    /// Default is [`Keymap::default`].
+    pub keymap: Keymap,
+}
+
+/// How the flags of arguments are shown, see [`Settings::flag_names`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagNames {
+    /// Only the names are shown
+    Hidden,
+    /// Flags are shown after the names
+    Beside,
+    /// Flags are shown instead of the names, which are shown when hovering over them
+    Instead,
+}
+
+/// Text shown for an argument instead of what's taken from clap, see [`Settings::arg_texts`].
+/// Everything left as None keeps the text from clap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ArgText {
+    /// Shown instead of the sentence-cased id
+    pub label: Option<String>,
+    /// Shown in empty fields instead of the default value or "(Optional)"
+    pub hint: Option<String>,
+    /// Shown when hovering over the label instead of the help, as markdown
+    pub tooltip: Option<String>,
+}
+
+/// A part of [`Settings::layout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutItem {
+    /// The argument with this id
+    Arg(String),
+    /// A box with a title around the items
+    Group(String, Vec<LayoutItem>),
+    /// Columns side by side, each with its own items
+    Columns(Vec<Vec<LayoutItem>>),
+    /// A horizontal line
+    Separator,
 }
 
 impl Default for Settings {
@@ -44,6 +340,8 @@ impl Default for Settings {
             enable_stdin: Option::default(),
             enable_working_dir: Option::default(),
             custom_font: Option::default(),
+            output_font: None,
+            output_font_size: None,
             localization: Default::default(),
             style: Style {
                 spacing: Spacing {
@@ -53,73 +351,49 @@ impl Default for Settings {
                 },
                 ..Default::default()
             },
-        }
-    }
-}
-
-/// Localization for builtin strings.
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[non_exhaustive]
-pub struct Localization {
-    /// Displays when the value is optional. Default is "(Optional)".
-    pub optional: String,
-    /// Button text for opening a dialog for file selection. Default is "Select file...".
-    pub select_file: String,
-    /// Button text for opening a dialog for directory selection. Default is "Select directory...".
-    pub select_directory: String,
-    /// Button text for creating a new field for multi-value arguments and environment variables. Default is "New value".
-    pub new_value: String,
-    /// Button text for resetting multi-value arguments. Default is "Reset".
-    pub reset: String,
-    /// Button text for resetting multi-value arguments to default. Default is "Reset to default".
-    pub reset_to_default: String,
-    /// Error text when an argument is requires. The argument name will be displayed between the two strings.
-    /// Default is ("Argument '", "' is required").
-    pub error_is_required: (String, String),
-    /// Text for the arguments tab. Default is "Arguments".
-    pub arguments: String,
-    /// Text for the environment variables tab. Default is "Environment variables".
-    pub env_variables: String,
-    /// Error displayed when user tries to pass an environment variable with no name.
-    /// Default is "Environment variable can't be empty".
-    pub error_env_var_cant_be_empty: String,
-    /// Text for the input tab. Default is "Input".
-    pub input: String,
-    /// Text for the button when user wants to write text for input in the input tab. Default is "Text".
-    pub text: String,
-    /// Text for the button when user wants to select file for input in the input tab. Default is "File".
-    pub file: String,
-    /// Text displayed as a hint for the working directory field. Default is "Working directory".
-    pub working_directory: String,
-    /// Button text for running the binary. Default is "Run".
-    pub run: String,
-    /// Button text for killing the binary. Default is "Kill".
-    pub kill: String,
-    /// Text that shows when the binary is running. There will be animated dots ("...") displayed after it.
-    /// Default is "Running".
-    pub running: String,
-}
-
-impl Default for Localization {
-    fn default() -> Self {
-        Self {
-            optional: "(Optional)".into(),
-            select_file: "Select file...".into(),
-            select_directory: "Select directory...".into(),
-            new_value: "New value".into(),
-            reset: "Reset".into(),
-            reset_to_default: "Reset to default".into(),
-            error_is_required: ("Argument '".into(), "' is required".into()),
-            arguments: "Arguments".into(),
-            env_variables: "Environment variables".into(),
-            error_env_var_cant_be_empty: "Environment variable can't be empty".into(),
-            input: "Input".into(),
-            text: "Text".into(),
-            file: "File".into(),
-            working_directory: "Working directory".into(),
-            run: "Run".into(),
-            kill: "Kill".into(),
-            running: "Running".into(),
+            high_contrast: false,
+            ansi_palette: AnsiPalette::standard(),
+            #[cfg(feature = "notify")]
+            notify_on_finish: false,
+            confirm_on_close: true,
+            confirm_kill: false,
+            disable_run_until_valid: false,
+            enable_menu_bar: false,
+            enable_help_tab: false,
+            post_process: vec![],
+            pre_run: vec![],
+            post_run: vec![],
+            log_dir: None,
+            log_keep_ansi: false,
+            session_dir: None,
+            config_arg: None,
+            output_history: 10,
+            output_byte_limit: Some(100 * 1024 * 1024),
+            run_records: false,
+            run_record_limit: 100,
+            run_record_output: false,
+            enable_favourites: false,
+            value_history: 0,
+            occurrence_labels: HashMap::new(),
+            prefill_defaults: false,
+            dialog_bookmarks: vec![],
+            file_filters: HashMap::new(),
+            output_paths: vec![],
+            arg_texts: HashMap::new(),
+            hide_args: vec![],
+            hide_subcommands: vec![],
+            layout: vec![],
+            flag_names: FlagNames::Hidden,
+            #[cfg(feature = "terminal")]
+            enable_terminal: false,
+            enable_batch: false,
+            output_renderers: HashMap::new(),
+            enable_substitution: false,
+            enable_preferences: false,
+            enable_wizard: false,
+            capture_panics: false,
+            close_on_success: None,
+            keymap: Keymap::default(),
         }
     }
 }
@@ -0,0 +1,95 @@
+use crate::Localization;
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+use uuid::Uuid;
+
+/// Values of placeholders for one run, see [`crate::Settings::enable_substitution`].
+/// They're fixed when created, so every `{uuid}` of a run is the same.
+#[derive(Debug)]
+pub struct Variables {
+    pub now: DateTime<Local>,
+    pub uuid: Uuid,
+    pub home: String,
+    /// Number of the run, counted from 1
+    pub counter: usize,
+}
+
+impl Variables {
+    pub fn new(counter: usize) -> Self {
+        Self {
+            now: Local::now(),
+            uuid: Uuid::new_v4(),
+            home: dirs::home_dir()
+                .map(|home| home.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            counter,
+        }
+    }
+}
+
+/// Whether the value has anything to expand
+pub fn has_placeholders(value: &str) -> bool {
+    value.contains(['{', '}'])
+}
+
+/// Replaces placeholders like `{date:%Y-%m-%d}` with their values.
+/// `{{` and `}}` are literal braces.
+pub fn expand(
+    value: &str,
+    variables: &Variables,
+    localization: &Localization,
+) -> Result<String, String> {
+    let error = |placeholder: &str| {
+        localization
+            .error_unknown_placeholder
+            .format(&[("placeholder", placeholder)])
+    };
+
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(['{', '}']) {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            expanded.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        let end = match (rest.starts_with('{'), rest.find('}')) {
+            (true, Some(end)) => end,
+            _ => return Err(error(rest)),
+        };
+        let placeholder = &rest[1..end];
+        let (name, format) = match placeholder.split_once(':') {
+            Some((name, format)) => (name, Some(format)),
+            None => (placeholder, None),
+        };
+        match (name, format) {
+            ("date", format) => {
+                let items: Vec<Item> = StrftimeItems::new(format.unwrap_or("%Y-%m-%d")).collect();
+                if items.contains(&Item::Error) {
+                    return Err(error(placeholder));
+                }
+                expanded.push_str(
+                    &variables
+                        .now
+                        .format_with_items(items.into_iter())
+                        .to_string(),
+                );
+            }
+            ("uuid", None) => expanded.push_str(&variables.uuid.to_string()),
+            ("home", None) => expanded.push_str(&variables.home),
+            ("n", None) => expanded.push_str(&variables.counter.to_string()),
+            _ => return Err(error(placeholder)),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests;
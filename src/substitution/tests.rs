@@ -0,0 +1,46 @@
+use super::{expand, Variables};
+use crate::Localization;
+use chrono::{Local, TimeZone};
+use uuid::Uuid;
+
+fn variables() -> Variables {
+    Variables {
+        now: Local.with_ymd_and_hms(2022, 3, 4, 5, 6, 7).unwrap(),
+        uuid: Uuid::nil(),
+        home: "/home/user".into(),
+        counter: 3,
+    }
+}
+
+#[test]
+fn placeholders() {
+    let localization = Localization::default();
+    let expand = |value| expand(value, &variables(), &localization);
+
+    assert_eq!(
+        expand("report-{date}.txt").unwrap(),
+        "report-2022-03-04.txt"
+    );
+    assert_eq!(expand("{date:%d.%m %H:%M}").unwrap(), "04.03 05:06");
+    assert_eq!(
+        expand("{home}/{n}-{uuid}").unwrap(),
+        "/home/user/3-00000000-0000-0000-0000-000000000000"
+    );
+    assert_eq!(expand("{{n}} }}{n}").unwrap(), "{n} }3");
+    assert_eq!(expand("plain").unwrap(), "plain");
+}
+
+#[test]
+fn invalid_placeholders() {
+    let localization = Localization::default();
+    let expand = |value| expand(value, &variables(), &localization);
+
+    assert_eq!(expand("{name}").unwrap_err(), "Unknown placeholder 'name'");
+    assert_eq!(expand("{n:x}").unwrap_err(), "Unknown placeholder 'n:x'");
+    assert_eq!(
+        expand("{date:%Q}").unwrap_err(),
+        "Unknown placeholder 'date:%Q'"
+    );
+    assert_eq!(expand("a{n").unwrap_err(), "Unknown placeholder '{n'");
+    assert_eq!(expand("a}").unwrap_err(), "Unknown placeholder '}'");
+}
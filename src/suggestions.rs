@@ -0,0 +1,188 @@
+use eframe::egui::{
+    popup_below_widget,
+    text::{CCursor, CCursorRange},
+    Key, Label, Response, RichText, TextEdit, Ui,
+};
+use std::{fs, path::Path};
+
+/// Suggestions past this many aren't shown, typing more narrows them down
+const MAX_SHOWN: usize = 8;
+/// Details are cut to this many characters, so long values don't widen the popup
+const MAX_DETAIL_LEN: usize = 40;
+/// Entries of a directory read for completing paths, so huge directories don't stall the GUI
+const MAX_DIR_ENTRIES: usize = 1000;
+
+/// A value offered below a text field, see [`show`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub text: String,
+    /// Shown dimmed next to the text, like the current value of a variable
+    pub detail: Option<String>,
+}
+
+impl Suggestion {
+    pub fn new(text: impl Into<String>, detail: Option<String>) -> Self {
+        Self {
+            text: text.into(),
+            detail,
+        }
+    }
+}
+
+/// Candidates that start with `typed`, ignoring case, in their order and without duplicates.
+/// A candidate equal to what's typed isn't suggested.
+pub fn matching(typed: &str, candidates: impl IntoIterator<Item = Suggestion>) -> Vec<Suggestion> {
+    let typed_lower = typed.to_lowercase();
+    let mut matching: Vec<Suggestion> = vec![];
+    for candidate in candidates {
+        if candidate.text != typed
+            && candidate.text.to_lowercase().starts_with(&typed_lower)
+            && !matching.iter().any(|m| m.text == candidate.text)
+        {
+            matching.push(candidate);
+        }
+    }
+    matching
+}
+
+/// Files and directories that could complete the `typed` path, like a shell would.
+/// Directories end with a separator, so picking one lists its contents next.
+/// Relative paths are in `base`. Only files with one of the `extensions` are suggested,
+/// unless it's empty.
+pub fn paths(typed: &str, base: &Path, only_dirs: bool, extensions: &[String]) -> Vec<Suggestion> {
+    // Everything up to the last separator is kept as typed
+    let split = typed.rfind(is_separator).map_or(0, |i| i + 1);
+    let (dir, name) = typed.split_at(split);
+    // Joining an absolute path replaces the base
+    let read_dir = base.join(dir);
+    let entries = match fs::read_dir(&read_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let show_hidden = name.starts_with('.');
+    let mut paths: Vec<(bool, String)> = entries
+        .take(MAX_DIR_ENTRIES)
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let file_name = entry.file_name().into_string().ok()?;
+            // Follows symlinks, so links to directories complete like directories
+            let is_dir = read_dir.join(&file_name).is_dir();
+            let extension = Path::new(&file_name).extension().and_then(|e| e.to_str());
+            let shown_file = !only_dirs
+                && (extensions.is_empty()
+                    || extension
+                        .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e))));
+            (is_dir || shown_file)
+                .then_some((is_dir, file_name))
+                .filter(|(_, file_name)| show_hidden || !file_name.starts_with('.'))
+        })
+        .collect();
+    paths.sort_by(|(a_dir, a), (b_dir, b)| b_dir.cmp(a_dir).then_with(|| a.cmp(b)));
+
+    let separator = typed
+        .chars()
+        .rev()
+        .find(|&c| is_separator(c))
+        .unwrap_or(std::path::MAIN_SEPARATOR);
+    let candidates = paths.into_iter().map(|(is_dir, file_name)| {
+        let mut text = format!("{}{}", dir, file_name);
+        if is_dir {
+            text.push(separator);
+        }
+        Suggestion::new(text, None)
+    });
+    matching(typed, candidates)
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || std::path::is_separator(c)
+}
+
+/// Shows the suggestions below the field of `response` while it has focus, returns
+/// the one that was clicked, or chosen with the arrow keys and Enter
+pub fn show(ui: &Ui, response: &Response, suggestions: &[Suggestion]) -> Option<String> {
+    let popup_id = response.id.with("klask_suggestions");
+    let selected_id = popup_id.with("selected");
+    if suggestions.is_empty() || (response.lost_focus() && ui.input().key_pressed(Key::Tab)) {
+        if ui.memory().is_popup_open(popup_id) {
+            ui.memory().close_popup();
+        }
+        ui.data().remove::<usize>(selected_id);
+        return None;
+    }
+    if response.has_focus() && (response.changed() || response.gained_focus()) {
+        ui.memory().open_popup(popup_id);
+        ui.data().remove::<usize>(selected_id);
+    }
+    if !ui.memory().is_popup_open(popup_id) {
+        return None;
+    }
+
+    let shown = suggestions.len().min(MAX_SHOWN);
+    let mut selected: Option<usize> = ui.data().get_temp(selected_id);
+    let input = ui.input();
+    if response.has_focus() && input.key_pressed(Key::ArrowDown) {
+        selected = Some(selected.map_or(0, |s| (s + 1).min(shown - 1)));
+    }
+    if response.has_focus() && input.key_pressed(Key::ArrowUp) {
+        selected = selected.and_then(|s| s.checked_sub(1));
+    }
+    let entered = response.lost_focus() && input.key_pressed(Key::Enter);
+    drop(input);
+    if let Some(selected) = selected {
+        ui.data().insert_temp(selected_id, selected);
+    } else {
+        ui.data().remove::<usize>(selected_id);
+    }
+
+    if let (true, Some(selected)) = (entered, selected) {
+        return Some(choose(ui, response, &suggestions[selected.min(shown - 1)]));
+    }
+
+    popup_below_widget(ui, popup_id, response, |ui| {
+        let mut chosen = None;
+        for (index, suggestion) in suggestions.iter().take(MAX_SHOWN).enumerate() {
+            ui.horizontal(|ui| {
+                let label = ui.selectable_label(selected == Some(index), &suggestion.text);
+                if selected == Some(index) {
+                    label.scroll_to_me(None);
+                }
+                if label.clicked() {
+                    chosen = Some(suggestion);
+                }
+                if let Some(detail) = &suggestion.detail {
+                    let mut shortened: String = detail.chars().take(MAX_DETAIL_LEN).collect();
+                    if shortened.len() < detail.len() {
+                        shortened.push('…');
+                    }
+                    ui.add(Label::new(RichText::new(shortened).weak()).wrap(false));
+                }
+            });
+        }
+        if suggestions.len() > MAX_SHOWN {
+            ui.label(RichText::new("…").weak());
+        }
+        chosen.map(|suggestion| choose(ui, response, suggestion))
+    })
+    .flatten()
+}
+
+/// Closes the popup and focuses the field again with the cursor at the end, so typing
+/// can go on. Clicking a suggestion or pressing Enter takes the focus away from it.
+fn choose(ui: &Ui, response: &Response, suggestion: &Suggestion) -> String {
+    let popup_id = response.id.with("klask_suggestions");
+    ui.data().remove::<usize>(popup_id.with("selected"));
+    ui.memory().close_popup();
+
+    response.request_focus();
+    let mut state = TextEdit::load_state(ui.ctx(), response.id).unwrap_or_default();
+    let end = CCursor::new(suggestion.text.chars().count());
+    state.set_ccursor_range(Some(CCursorRange::one(end)));
+    TextEdit::store_state(ui.ctx(), response.id, state);
+
+    suggestion.text.clone()
+}
+
+#[cfg(test)]
+mod tests;
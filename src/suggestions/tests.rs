@@ -0,0 +1,57 @@
+use super::{matching, paths, Suggestion};
+use std::path::Path;
+
+#[test]
+fn matching_prefixes() {
+    let candidates = ["PAGER", "PATH", "HOME", "path", "PATH"]
+        .iter()
+        .map(|name| Suggestion::new(*name, None));
+    let texts: Vec<String> = matching("pa", candidates.clone())
+        .into_iter()
+        .map(|s| s.text)
+        .collect();
+    assert_eq!(texts, ["PAGER", "PATH", "path"]);
+
+    let texts: Vec<String> = matching("PATH", candidates)
+        .into_iter()
+        .map(|s| s.text)
+        .collect();
+    assert_eq!(texts, ["path"]);
+}
+
+#[test]
+fn completing_paths() {
+    let dir = std::env::temp_dir().join(format!("klask-paths-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join("data")).unwrap();
+    std::fs::write(dir.join("dataset.csv"), "").unwrap();
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+    std::fs::write(dir.join(".hidden"), "").unwrap();
+    let typed = |name: &str| format!("{}/{}", dir.display(), name);
+    let texts = |typed: &str, only_dirs| -> Vec<String> {
+        paths(typed, Path::new("."), only_dirs, &[])
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    };
+
+    assert_eq!(
+        texts(&typed("da"), false),
+        [typed("data/"), typed("dataset.csv")]
+    );
+    assert_eq!(texts(&typed("da"), true), [typed("data/")]);
+    assert_eq!(texts(&typed(""), false).len(), 3);
+    assert_eq!(texts(&typed("."), false), [typed(".hidden")]);
+    assert!(texts(&typed("data/"), false).is_empty());
+
+    let csv = paths(&typed(""), Path::new("."), false, &["CSV".into()]);
+    let csv: Vec<String> = csv.into_iter().map(|s| s.text).collect();
+    assert_eq!(csv, [typed("data/"), typed("dataset.csv")]);
+
+    let relative: Vec<String> = paths("da", &dir, false, &[])
+        .into_iter()
+        .map(|s| s.text)
+        .collect();
+    assert_eq!(relative, ["data/", "dataset.csv"]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,508 @@
+use crate::{
+    output::{AnsiPalette, Style},
+    ExecutionError, Localization, CHILD_APP_ENV_VAR,
+};
+use cansi::{Color, Intensity};
+use eframe::egui::{
+    text::LayoutJob, vec2, Button, Color32, Context, Event, FontId, Id, Key, Modifiers, Rect,
+    Sense, Stroke, TextFormat, TextStyle, Ui,
+};
+use nix::{
+    libc,
+    pty::{self, Winsize},
+    unistd,
+};
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    os::unix::{
+        io::{AsRawFd, FromRawFd},
+        process::CommandExt,
+    },
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+mod screen;
+#[cfg(test)]
+mod tests;
+
+use screen::{Cell, Screen};
+
+/// Rows of the terminal, the columns fill the width of the window
+const ROWS: usize = 24;
+/// Id of the terminal widget, which gets keyboard focus
+const ID: &str = "klask_terminal";
+
+const LETTERS: [Key; 26] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+];
+
+/// Tab with a terminal emulator the binary runs in, for interactive binaries
+/// like REPLs, TUIs and password prompts, see [`crate::Settings::enable_terminal`]
+#[derive(Debug)]
+pub struct Terminal {
+    screen: Screen,
+    session: Option<Session>,
+    /// Set while the arguments of a run started from the tab are validated
+    pending: bool,
+    /// Lines scrolled back with Shift+PageUp
+    scroll: usize,
+    /// Exit code or error of the last session
+    status: Option<String>,
+}
+
+#[derive(Debug)]
+struct Session {
+    child: Child,
+    /// Master side of the pseudoterminal, the binary has the other one
+    master: File,
+    output: Receiver<Vec<u8>>,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self {
+            screen: Screen::new(ROWS, 80),
+            session: None,
+            pending: false,
+            scroll: 0,
+            status: None,
+        }
+    }
+}
+
+impl Terminal {
+    pub fn is_running(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Whether keys go to the terminal, shortcuts aren't handled then
+    pub fn has_focus(ctx: &Context) -> bool {
+        ctx.memory().has_focus(Id::new(ID))
+    }
+
+    /// Returns true once after the tab started a run, which was then validated
+    pub fn take_pending(&mut self) -> bool {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Runs `program`, or this binary again if it's None, with a fresh screen.
+    /// Returns whether it started.
+    pub fn start(
+        &mut self,
+        program: Option<&Path>,
+        args: Vec<String>,
+        env: Option<Vec<(String, String)>>,
+        working_dir: Option<String>,
+        ctx: Context,
+    ) -> bool {
+        let (rows, cols) = self.screen.size();
+        self.screen = Screen::new(rows, cols);
+        self.scroll = 0;
+        match Session::spawn(program, args, env, working_dir, (rows, cols), ctx) {
+            Ok(session) => {
+                self.session = Some(session);
+                self.status = None;
+                true
+            }
+            Err(err) => {
+                self.status = Some(err.to_string());
+                false
+            }
+        }
+    }
+
+    pub fn kill(&mut self) {
+        if let Some(session) = &mut self.session {
+            drop(session.child.kill());
+        }
+    }
+
+    /// Reads the output of the binary, called every frame so it doesn't
+    /// block on a full terminal while another tab is shown
+    pub fn update(&mut self, localization: &Localization) {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return,
+        };
+
+        let mut disconnected = false;
+        loop {
+            match session.output.try_recv() {
+                Ok(bytes) => self.screen.feed(&bytes),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    disconnected = true;
+                    break;
+                }
+            }
+        }
+        let replies = self.screen.take_replies();
+        if !replies.is_empty() {
+            drop(session.master.write_all(&replies));
+        }
+
+        // The output ends once every process using the terminal exits
+        if disconnected {
+            self.status = match session.child.wait() {
+                Ok(status) => status
+                    .code()
+                    .map(|code| format!("{}: {}", localization.exit_code, code)),
+                Err(err) => Some(err.to_string()),
+            };
+            self.session = None;
+        }
+    }
+
+    /// Returns true when the user starts a run
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        busy: bool,
+        localization: &Localization,
+        palette: &AnsiPalette,
+    ) -> bool {
+        let mut run = false;
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !busy && !self.is_running(),
+                    Button::new(&localization.run_in_terminal),
+                )
+                .clicked()
+            {
+                self.pending = true;
+                run = true;
+            }
+            if self.is_running() && ui.button(&localization.kill).clicked() {
+                self.kill();
+            }
+            if let Some(status) = &self.status {
+                ui.label(status);
+            }
+        });
+
+        let font = TextStyle::Monospace.resolve(ui.style());
+        let char_size = vec2(
+            ui.fonts().glyph_width(&font, 'M'),
+            ui.fonts().row_height(&font),
+        );
+        let cols = (ui.available_width() / char_size.x) as usize;
+        if (ROWS, cols) != self.screen.size() {
+            self.screen.resize(ROWS, cols);
+            if let Some(session) = &self.session {
+                session.resize(self.screen.size());
+            }
+        }
+
+        let (rows, cols) = self.screen.size();
+        let size = vec2(cols as f32 * char_size.x, rows as f32 * char_size.y);
+        let rect = ui.allocate_space(size).1;
+        let response = ui.interact(rect, Id::new(ID), Sense::click());
+        if response.clicked() {
+            response.request_focus();
+        }
+        let focused = response.has_focus();
+        if focused {
+            ui.memory().lock_focus(response.id, true);
+            let events = ui.input().events.clone();
+            self.handle_input(&events);
+        }
+
+        self.paint(ui, rect, &font, char_size, focused, palette);
+        run
+    }
+
+    fn handle_input(&mut self, events: &[Event]) {
+        let mut input = vec![];
+        for event in events {
+            match event {
+                Event::Text(text) => input.extend_from_slice(text.as_bytes()),
+                Event::Paste(text) => {
+                    let text = text.replace('\n', "\r");
+                    if self.screen.bracketed_paste {
+                        input.extend_from_slice(b"\x1b[200~");
+                        input.extend_from_slice(text.as_bytes());
+                        input.extend_from_slice(b"\x1b[201~");
+                    } else {
+                        input.extend_from_slice(text.as_bytes());
+                    }
+                }
+                Event::Key {
+                    key: Key::PageUp,
+                    pressed: true,
+                    modifiers,
+                } if modifiers.shift => {
+                    self.scroll = (self.scroll + ROWS / 2).min(self.screen.scrollback_len());
+                }
+                Event::Key {
+                    key: Key::PageDown,
+                    pressed: true,
+                    modifiers,
+                } if modifiers.shift => {
+                    self.scroll = self.scroll.saturating_sub(ROWS / 2);
+                }
+                Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                } => {
+                    if let Some(bytes) = key_bytes(*key, *modifiers, self.screen.app_cursor_keys) {
+                        input.extend_from_slice(&bytes);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !input.is_empty() {
+            self.scroll = 0;
+            if let Some(session) = &mut self.session {
+                drop(session.master.write_all(&input));
+            }
+        }
+    }
+
+    fn paint(
+        &self,
+        ui: &mut Ui,
+        rect: Rect,
+        font: &FontId,
+        char_size: eframe::egui::Vec2,
+        focused: bool,
+        palette: &AnsiPalette,
+    ) {
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, Color32::BLACK);
+
+        for (row, line) in self.screen.view(self.scroll).enumerate() {
+            let mut job = LayoutJob::default();
+            for run in runs(line) {
+                let text: String = run.iter().map(|cell| cell.ch).collect();
+                job.append(&text, 0.0, text_format(font, &run[0].style, palette));
+            }
+            let galley = ui.fonts().layout_job(job);
+            let pos = rect.min + vec2(0.0, row as f32 * char_size.y);
+            painter.galley(pos, galley);
+        }
+
+        if self.screen.cursor_visible && self.scroll == 0 {
+            let (row, col) = self.screen.cursor();
+            let min = rect.min + vec2(col as f32 * char_size.x, row as f32 * char_size.y);
+            let cursor = Rect::from_min_size(min, char_size);
+            if focused {
+                painter.rect_filled(cursor, 0.0, Color32::from_white_alpha(120));
+            } else {
+                painter.rect_stroke(cursor, 0.0, Stroke::new(1.0, Color32::GRAY));
+            }
+        }
+    }
+}
+
+/// Runs of cells with the same style
+fn runs(line: &[Cell]) -> Vec<&[Cell]> {
+    let mut runs = vec![];
+    let mut start = 0;
+    for i in 1..=line.len() {
+        if i == line.len() || line[i].style != line[start].style {
+            runs.push(&line[start..i]);
+            start = i;
+        }
+    }
+    runs
+}
+
+fn text_format(font: &FontId, style: &Style, palette: &AnsiPalette) -> TextFormat {
+    let (fg, bg) = if style.inverse == Some(true) {
+        (
+            style.bg.unwrap_or(Color::Black),
+            style.fg.or(Some(Color::White)),
+        )
+    } else {
+        (style.fg.unwrap_or(Color::White), style.bg)
+    };
+    let mut color = palette.color(fg);
+    if style.intensity == Some(Intensity::Faint) {
+        color = color.linear_multiply(0.6);
+    }
+    let line = |on: Option<bool>| {
+        if on == Some(true) {
+            Stroke::new(1.0, color)
+        } else {
+            Stroke::none()
+        }
+    };
+
+    TextFormat {
+        font_id: font.clone(),
+        color,
+        background: bg.map_or(Color32::TRANSPARENT, |bg| palette.color(bg)),
+        italics: style.italic == Some(true),
+        underline: line(style.underline),
+        strikethrough: line(style.strikethrough),
+        ..TextFormat::default()
+    }
+}
+
+/// What a terminal sends for a key, text is sent by [`Event::Text`] instead
+fn key_bytes(key: Key, modifiers: Modifiers, app_cursor_keys: bool) -> Option<Vec<u8>> {
+    let arrow = |c: char| {
+        if app_cursor_keys {
+            format!("\x1bO{}", c).into_bytes()
+        } else {
+            format!("\x1b[{}", c).into_bytes()
+        }
+    };
+    let bytes = match key {
+        Key::Enter => b"\r".to_vec(),
+        Key::Backspace => b"\x7f".to_vec(),
+        Key::Tab => b"\t".to_vec(),
+        Key::Escape => b"\x1b".to_vec(),
+        Key::ArrowUp => arrow('A'),
+        Key::ArrowDown => arrow('B'),
+        Key::ArrowRight => arrow('C'),
+        Key::ArrowLeft => arrow('D'),
+        Key::Home => b"\x1b[H".to_vec(),
+        Key::End => b"\x1b[F".to_vec(),
+        Key::Insert => b"\x1b[2~".to_vec(),
+        Key::Delete => b"\x1b[3~".to_vec(),
+        Key::PageUp => b"\x1b[5~".to_vec(),
+        Key::PageDown => b"\x1b[6~".to_vec(),
+        // Ctrl+V pastes instead, see `Event::Paste`
+        Key::V => return None,
+        key if modifiers.ctrl => {
+            let letter = LETTERS.iter().position(|&k| k == key)?;
+            vec![letter as u8 + 1]
+        }
+        _ => return None,
+    };
+    Some(bytes)
+}
+
+fn winsize((rows, cols): (usize, usize)) -> Winsize {
+    Winsize {
+        ws_row: rows as u16,
+        ws_col: cols as u16,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+impl Session {
+    fn spawn(
+        program: Option<&Path>,
+        args: Vec<String>,
+        env: Option<Vec<(String, String)>>,
+        working_dir: Option<String>,
+        size: (usize, usize),
+        ctx: Context,
+    ) -> Result<Self, ExecutionError> {
+        let pty = pty::openpty(&winsize(size), None).map_err(io::Error::from)?;
+        // SAFETY: openpty returns new descriptors, which nothing else owns
+        let (master, slave) =
+            unsafe { (File::from_raw_fd(pty.master), File::from_raw_fd(pty.slave)) };
+
+        let mut command = match program {
+            Some(program) => Command::new(program),
+            None => {
+                let mut command = Command::new(std::env::current_exe()?);
+                command.env(CHILD_APP_ENV_VAR, "");
+                command
+            }
+        };
+        command
+            .args(args)
+            .env("TERM", "xterm-256color")
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        if let Some(env) = env {
+            command.envs(env);
+        }
+        if let Some(working_dir) = working_dir.filter(|dir| !dir.is_empty()) {
+            command.current_dir(working_dir);
+        }
+        // SAFETY: only async-signal-safe functions are called between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                // A new session with the terminal as its controlling terminal,
+                // so Ctrl+C interrupts the binary and not this process
+                unistd::setsid().map_err(io::Error::from)?;
+                if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        // Our copies of the slave have to be closed, or reading never ends
+        drop(command);
+
+        let mut reader = master.try_clone()?;
+        let (tx, output) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            loop {
+                // Fails on Linux instead of returning 0 once the terminal is closed
+                match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if tx.send(buf[..n].to_vec()).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                }
+            }
+            drop(tx);
+            ctx.request_repaint();
+        });
+
+        Ok(Self {
+            child,
+            master,
+            output,
+        })
+    }
+
+    fn resize(&self, size: (usize, usize)) {
+        // SAFETY: TIOCSWINSZ only reads the window size
+        unsafe {
+            libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &winsize(size));
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        drop(self.child.kill());
+    }
+}
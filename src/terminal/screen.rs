@@ -0,0 +1,468 @@
+use crate::output::{apply_sgr, Style};
+use std::collections::VecDeque;
+
+/// Lines scrolled off the top of the main screen that are kept
+const SCROLLBACK_LINES: usize = 1000;
+
+/// Control sequences longer than this are dropped
+const MAX_SEQUENCE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cell {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Ground,
+    Escape,
+    /// Parameters of a Control Sequence Introducer sequence
+    Csi(String),
+    /// Operating System Command, like setting the window title, which is skipped
+    Osc,
+    /// Escape inside an OSC, which ends it
+    OscEscape,
+    /// Character set selection, which is skipped
+    Charset,
+}
+
+/// Grid of characters written by a program through escape codes, a subset of
+/// what xterm supports. Input can be split anywhere, including inside escape
+/// codes and characters.
+#[derive(Debug)]
+pub(crate) struct Screen {
+    rows: usize,
+    cols: usize,
+    lines: Vec<Vec<Cell>>,
+    /// Lines of the main screen while the alternate screen is shown
+    main: Option<Vec<Vec<Cell>>>,
+    /// Oldest first
+    scrollback: VecDeque<Vec<Cell>>,
+    row: usize,
+    col: usize,
+    saved: (usize, usize),
+    /// Style of written characters
+    pen: Style,
+    /// First and last line of the scrolling region
+    top: usize,
+    bottom: usize,
+    /// The last column was written, so the next character goes on a new line
+    wrap_pending: bool,
+    pub cursor_visible: bool,
+    /// Arrow keys send `ESC O` instead of `ESC [`
+    pub app_cursor_keys: bool,
+    /// Pasted text is surrounded with escape codes
+    pub bracketed_paste: bool,
+    state: State,
+    /// Start of a character cut off at the end of the last input
+    utf8: Vec<u8>,
+    /// Answers to queries, to be written back to the program
+    replies: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        Self {
+            rows,
+            cols,
+            lines: vec![vec![Cell::default(); cols]; rows],
+            main: None,
+            scrollback: VecDeque::new(),
+            row: 0,
+            col: 0,
+            saved: (0, 0),
+            pen: Style::default(),
+            top: 0,
+            bottom: rows - 1,
+            wrap_pending: false,
+            cursor_visible: true,
+            app_cursor_keys: false,
+            bracketed_paste: false,
+            state: State::Ground,
+            utf8: vec![],
+            replies: vec![],
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Position of the cursor as a row and a column
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Number of lines that can be scrolled back to, none on the alternate screen
+    pub fn scrollback_len(&self) -> usize {
+        if self.main.is_some() {
+            0
+        } else {
+            self.scrollback.len()
+        }
+    }
+
+    /// The lines to show when scrolled back by `offset` lines
+    pub fn view(&self, offset: usize) -> impl Iterator<Item = &[Cell]> {
+        let offset = offset.min(self.scrollback_len());
+        self.scrollback
+            .iter()
+            .skip(self.scrollback.len() - offset)
+            .chain(&self.lines)
+            .take(self.rows)
+            .map(Vec::as_slice)
+    }
+
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.replies)
+    }
+
+    /// Shrinking drops lines from the top, so the cursor stays on the screen
+    pub fn resize(&mut self, rows: usize, cols: usize) {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        if (rows, cols) == self.size() {
+            return;
+        }
+
+        for line in self.lines.iter_mut().chain(self.main.iter_mut().flatten()) {
+            line.resize(cols, Cell::default());
+        }
+        while self.lines.len() > rows {
+            if self.row > 0 {
+                let line = self.lines.remove(0);
+                self.push_scrollback(line);
+                self.row -= 1;
+            } else {
+                self.lines.pop();
+            }
+        }
+        self.lines.resize(rows, vec![Cell::default(); cols]);
+        if let Some(main) = &mut self.main {
+            main.resize(rows, vec![Cell::default(); cols]);
+        }
+
+        self.rows = rows;
+        self.cols = cols;
+        self.top = 0;
+        self.bottom = rows - 1;
+        self.row = self.row.min(rows - 1);
+        self.col = self.col.min(cols - 1);
+        self.wrap_pending = false;
+    }
+
+    /// Handles output of the program
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.utf8.extend_from_slice(bytes);
+        let valid = match std::str::from_utf8(&self.utf8) {
+            Ok(_) => self.utf8.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => self.utf8.len(),
+        };
+        let rest = self.utf8.split_off(valid);
+        let bytes = std::mem::replace(&mut self.utf8, rest);
+        for c in String::from_utf8_lossy(&bytes).chars() {
+            self.process(c);
+        }
+    }
+
+    fn process(&mut self, c: char) {
+        match std::mem::replace(&mut self.state, State::Ground) {
+            State::Ground => self.ground(c),
+            State::Escape => self.escape(c),
+            State::Csi(mut params) => match c {
+                '\x40'..='\x7e' => self.csi(&params, c),
+                '\x1b' => self.state = State::Escape,
+                _ if params.len() < MAX_SEQUENCE_LEN => {
+                    params.push(c);
+                    self.state = State::Csi(params);
+                }
+                _ => {}
+            },
+            State::Osc => match c {
+                '\x07' => {}
+                '\x1b' => self.state = State::OscEscape,
+                _ => self.state = State::Osc,
+            },
+            State::OscEscape | State::Charset => {}
+        }
+    }
+
+    fn ground(&mut self, c: char) {
+        match c {
+            '\x1b' => self.state = State::Escape,
+            '\r' => {
+                self.col = 0;
+                self.wrap_pending = false;
+            }
+            '\n' | '\x0b' | '\x0c' => self.line_feed(),
+            '\x08' => {
+                self.col = self.col.saturating_sub(1);
+                self.wrap_pending = false;
+            }
+            '\t' => self.col = ((self.col / 8 + 1) * 8).min(self.cols - 1),
+            c if c.is_control() => {}
+            c => self.put(c),
+        }
+    }
+
+    fn escape(&mut self, c: char) {
+        match c {
+            '[' => self.state = State::Csi(String::new()),
+            ']' => self.state = State::Osc,
+            '(' | ')' | '*' | '+' => self.state = State::Charset,
+            '7' => self.saved = (self.row, self.col),
+            '8' => self.restore_cursor(),
+            'D' => self.line_feed(),
+            'E' => {
+                self.col = 0;
+                self.line_feed();
+            }
+            'M' => self.reverse_index(),
+            'c' => *self = Self::new(self.rows, self.cols),
+            _ => {}
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        let private = params.starts_with(['?', '>', '='].as_ref());
+        let nums: Vec<usize> = params
+            .trim_start_matches(['?', '>', '='].as_ref())
+            .split(';')
+            .map(|n| n.parse().unwrap_or(0))
+            .collect();
+        let num = |i: usize| nums.get(i).copied().unwrap_or(0);
+        // Counts and positions start at 1, and 0 means the same
+        let count = |i: usize| num(i).max(1);
+        let (last_row, last_col) = (self.rows - 1, self.cols - 1);
+
+        if action != 'm' {
+            self.wrap_pending = false;
+        }
+        match action {
+            'A' => self.row = self.row.saturating_sub(count(0)),
+            'B' | 'e' => self.row = (self.row + count(0)).min(last_row),
+            'C' | 'a' => self.col = (self.col + count(0)).min(last_col),
+            'D' => self.col = self.col.saturating_sub(count(0)),
+            'E' => {
+                self.row = (self.row + count(0)).min(last_row);
+                self.col = 0;
+            }
+            'F' => {
+                self.row = self.row.saturating_sub(count(0));
+                self.col = 0;
+            }
+            'G' | '`' => self.col = (count(0) - 1).min(last_col),
+            'd' => self.row = (count(0) - 1).min(last_row),
+            'H' | 'f' => {
+                self.row = (count(0) - 1).min(last_row);
+                self.col = (count(1) - 1).min(last_col);
+            }
+            'J' => {
+                let (row, col) = (self.row, self.col);
+                match num(0) {
+                    0 => {
+                        self.erase(row, col..self.cols);
+                        (row + 1..self.rows).for_each(|row| self.erase(row, 0..self.cols));
+                    }
+                    1 => {
+                        (0..row).for_each(|row| self.erase(row, 0..self.cols));
+                        self.erase(row, 0..col + 1);
+                    }
+                    n => {
+                        (0..self.rows).for_each(|row| self.erase(row, 0..self.cols));
+                        if n == 3 {
+                            self.scrollback.clear();
+                        }
+                    }
+                }
+            }
+            'K' => match num(0) {
+                0 => self.erase(self.row, self.col..self.cols),
+                1 => self.erase(self.row, 0..self.col + 1),
+                _ => self.erase(self.row, 0..self.cols),
+            },
+            'X' => self.erase(self.row, self.col..(self.col + count(0)).min(self.cols)),
+            'L' if (self.top..=self.bottom).contains(&self.row) => {
+                for _ in 0..count(0).min(self.bottom - self.row + 1) {
+                    self.lines.remove(self.bottom);
+                    self.lines.insert(self.row, self.blank_line());
+                }
+            }
+            'M' if (self.top..=self.bottom).contains(&self.row) => {
+                for _ in 0..count(0).min(self.bottom - self.row + 1) {
+                    self.lines.remove(self.row);
+                    self.lines.insert(self.bottom, self.blank_line());
+                }
+            }
+            'P' => {
+                let blank = self.blank();
+                let line = &mut self.lines[self.row];
+                let end = (self.col + count(0)).min(self.cols);
+                line.drain(self.col..end);
+                line.resize(self.cols, blank);
+            }
+            '@' => {
+                let blank = self.blank();
+                let line = &mut self.lines[self.row];
+                for _ in 0..count(0).min(self.cols - self.col) {
+                    line.insert(self.col, blank);
+                }
+                line.truncate(self.cols);
+            }
+            'S' => self.scroll_up(count(0)),
+            'T' => self.scroll_down(count(0)),
+            'm' if !private => apply_sgr(&mut self.pen, params),
+            'r' if !private => {
+                let top = count(0) - 1;
+                let bottom = match num(1) {
+                    0 => last_row,
+                    n => (n - 1).min(last_row),
+                };
+                if top < bottom {
+                    self.top = top;
+                    self.bottom = bottom;
+                }
+                self.row = 0;
+                self.col = 0;
+            }
+            's' if !private => self.saved = (self.row, self.col),
+            'u' if !private => self.restore_cursor(),
+            'n' if num(0) == 5 => self.replies.extend_from_slice(b"\x1b[0n"),
+            'n' if num(0) == 6 => {
+                let report = format!("\x1b[{};{}R", self.row + 1, self.col + 1);
+                self.replies.extend_from_slice(report.as_bytes());
+            }
+            // Reports a VT100 with advanced video
+            'c' if !private => self.replies.extend_from_slice(b"\x1b[?1;2c"),
+            'h' | 'l' if private => {
+                for &mode in &nums {
+                    self.set_mode(mode, action == 'h');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_mode(&mut self, mode: usize, on: bool) {
+        match mode {
+            1 => self.app_cursor_keys = on,
+            25 => self.cursor_visible = on,
+            47 | 1047 | 1049 => {
+                if on && self.main.is_none() {
+                    if mode == 1049 {
+                        self.saved = (self.row, self.col);
+                    }
+                    let blank = vec![vec![Cell::default(); self.cols]; self.rows];
+                    self.main = Some(std::mem::replace(&mut self.lines, blank));
+                } else if !on {
+                    if let Some(main) = self.main.take() {
+                        self.lines = main;
+                        if mode == 1049 {
+                            self.restore_cursor();
+                        }
+                    }
+                }
+            }
+            2004 => self.bracketed_paste = on,
+            _ => {}
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        if self.wrap_pending {
+            self.col = 0;
+            self.line_feed();
+        }
+        self.lines[self.row][self.col] = Cell {
+            ch: c,
+            style: self.pen,
+        };
+        if self.col + 1 < self.cols {
+            self.col += 1;
+        } else {
+            self.wrap_pending = true;
+        }
+    }
+
+    fn line_feed(&mut self) {
+        self.wrap_pending = false;
+        if self.row == self.bottom {
+            self.scroll_up(1);
+        } else if self.row + 1 < self.rows {
+            self.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.row == self.top {
+            self.scroll_down(1);
+        } else {
+            self.row = self.row.saturating_sub(1);
+        }
+    }
+
+    fn restore_cursor(&mut self) {
+        self.row = self.saved.0.min(self.rows - 1);
+        self.col = self.saved.1.min(self.cols - 1);
+        self.wrap_pending = false;
+    }
+
+    /// Scrolls the scrolling region up, lines leaving the top of the main screen are kept
+    fn scroll_up(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom - self.top + 1) {
+            let line = self.lines.remove(self.top);
+            if self.top == 0 && self.main.is_none() {
+                self.push_scrollback(line);
+            }
+            self.lines.insert(self.bottom, self.blank_line());
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        for _ in 0..count.min(self.bottom - self.top + 1) {
+            self.lines.remove(self.bottom);
+            self.lines.insert(self.top, self.blank_line());
+        }
+    }
+
+    fn push_scrollback(&mut self, line: Vec<Cell>) {
+        if self.scrollback.len() == SCROLLBACK_LINES {
+            self.scrollback.pop_front();
+        }
+        self.scrollback.push_back(line);
+    }
+
+    /// Erased cells keep the current background color
+    fn blank(&self) -> Cell {
+        Cell {
+            ch: ' ',
+            style: Style {
+                bg: self.pen.bg,
+                ..Style::default()
+            },
+        }
+    }
+
+    fn blank_line(&self) -> Vec<Cell> {
+        vec![self.blank(); self.cols]
+    }
+
+    fn erase(&mut self, row: usize, cols: std::ops::Range<usize>) {
+        let blank = self.blank();
+        let end = cols.end.min(self.cols);
+        self.lines[row][cols.start.min(end)..end].fill(blank);
+    }
+}
@@ -0,0 +1,113 @@
+use super::{key_bytes, screen::Screen};
+use eframe::egui::{Key, Modifiers};
+
+fn text(screen: &Screen) -> Vec<String> {
+    screen
+        .view(0)
+        .map(|line| {
+            let line: String = line.iter().map(|cell| cell.ch).collect();
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn writes_and_wraps() {
+    let mut screen = Screen::new(3, 5);
+    screen.feed(b"abcdefg\r\nxy");
+    assert_eq!(text(&screen), ["abcde", "fg", "xy"]);
+    assert_eq!(screen.cursor(), (2, 2));
+
+    // Scrolls the first line into the scrollback
+    screen.feed(b"\r\n1\r\n2");
+    assert_eq!(text(&screen), ["xy", "1", "2"]);
+    assert_eq!(screen.scrollback_len(), 2);
+    assert_eq!(screen.view(1).next().map(|line| line[0].ch), Some('f'),);
+}
+
+#[test]
+fn escape_codes_split_between_reads() {
+    let mut screen = Screen::new(3, 10);
+    screen.feed(b"hello\x1b[");
+    screen.feed(b"2;3Hx\x1b[1;31");
+    screen.feed(b"mz\xc5");
+    screen.feed(b"\x82");
+    assert_eq!(text(&screen), ["hello", "  xzł", ""]);
+    let line = screen.view(0).nth(1).unwrap();
+    assert_eq!(line[2].style.fg, None);
+    assert_eq!(line[3].style.fg, Some(cansi::Color::Red));
+}
+
+#[test]
+fn erases_and_edits_lines() {
+    let mut screen = Screen::new(3, 10);
+    screen.feed(b"one\r\ntwo\r\nthree");
+    screen.feed(b"\x1b[2;1H\x1b[L");
+    assert_eq!(text(&screen), ["one", "", "two"]);
+    screen.feed(b"\x1b[1;2H\x1b[K");
+    assert_eq!(text(&screen), ["o", "", "two"]);
+    screen.feed(b"\x1b[3;1H\x1b[P");
+    assert_eq!(text(&screen), ["o", "", "wo"]);
+    screen.feed(b"\x1b[2J");
+    assert_eq!(text(&screen), ["", "", ""]);
+}
+
+#[test]
+fn alternate_screen() {
+    let mut screen = Screen::new(2, 10);
+    screen.feed(b"shell");
+    screen.feed(b"\x1b[?1049h\x1b[Htui");
+    assert_eq!(text(&screen), ["tui", ""]);
+    screen.feed(b"\x1b[?1049l");
+    assert_eq!(text(&screen), ["shell", ""]);
+    assert_eq!(screen.cursor(), (0, 5));
+}
+
+#[test]
+fn replies_to_cursor_position_query() {
+    let mut screen = Screen::new(5, 10);
+    screen.feed(b"\x1b[3;4H\x1b[6n");
+    assert_eq!(screen.take_replies(), b"\x1b[3;4R");
+    assert!(screen.take_replies().is_empty());
+}
+
+#[test]
+fn keys() {
+    let ctrl = Modifiers {
+        ctrl: true,
+        ..Modifiers::default()
+    };
+    assert_eq!(key_bytes(Key::C, ctrl, false), Some(vec![3]));
+    assert_eq!(key_bytes(Key::C, Modifiers::default(), false), None);
+    assert_eq!(
+        key_bytes(Key::ArrowUp, Modifiers::default(), false),
+        Some(b"\x1b[A".to_vec())
+    );
+    assert_eq!(
+        key_bytes(Key::ArrowUp, Modifiers::default(), true),
+        Some(b"\x1bOA".to_vec())
+    );
+}
+
+#[test]
+fn runs_in_pseudoterminal() {
+    let mut terminal = super::Terminal::default();
+    terminal.start(
+        Some(std::path::Path::new("sh")),
+        vec!["-c".into(), "test -t 0 && printf tty; exit 3".into()],
+        None,
+        None,
+        eframe::egui::Context::default(),
+    );
+    let localization = crate::Localization::default();
+    for _ in 0..500 {
+        terminal.update(&localization);
+        if !terminal.is_running() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(!terminal.is_running());
+    assert_eq!(text(&terminal.screen)[0], "tty");
+    assert_eq!(terminal.status.as_deref(), Some("Exit code: 3"));
+}
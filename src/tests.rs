@@ -0,0 +1,46 @@
+use super::{Klask, RunSnapshot, Settings};
+use clap::Command;
+use eframe::egui::Context;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+#[test]
+fn recorded_run_is_saved() {
+    let dir = std::env::temp_dir().join(format!("klask-sessions-{}", uuid::Uuid::new_v4()));
+    let settings = Settings {
+        session_dir: Some(dir.clone()),
+        ..Default::default()
+    };
+    let mut klask = Klask::new(Command::new("echo"), settings);
+    klask.program = Some("echo".into());
+    klask.sessions.as_mut().unwrap().record = true;
+
+    let ctx = Context::default();
+    let snapshot = RunSnapshot {
+        args: vec!["hello".into()],
+        subcommands: vec![],
+        env: None,
+        stdin: None,
+        working_dir: None,
+    };
+    klask.launch(snapshot, ctx.clone());
+
+    let started = Instant::now();
+    let saved = loop {
+        klask.update_child(&ctx);
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            if let Some(entry) = entries.flatten().next() {
+                break entry.path();
+            }
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "session wasn't saved"
+        );
+        std::thread::sleep(Duration::from_millis(5));
+    };
+
+    let session = std::fs::read_to_string(saved).unwrap();
+    assert!(session.contains("hello"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
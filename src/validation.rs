@@ -0,0 +1,73 @@
+use crate::{error::ExecutionError, Localization, RunSnapshot};
+use clap::Command;
+use eframe::egui::Context;
+use std::{
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+};
+
+/// Validates arguments with clap on a worker thread, so huge argument sets
+/// or slow value parsers don't freeze the GUI when Run is pressed
+#[derive(Debug)]
+pub struct Validation {
+    requests: Sender<(RunSnapshot, Context)>,
+    results: Receiver<(RunSnapshot, Result<(), ExecutionError>)>,
+    pending: bool,
+}
+
+impl Validation {
+    pub fn new(mut app: Command<'static>) -> Self {
+        let (requests, rx) = mpsc::channel::<(RunSnapshot, Context)>();
+        let (tx, results) = mpsc::channel();
+        thread::spawn(move || {
+            for (snapshot, ctx) in rx {
+                let result = app
+                    .try_get_matches_from_mut(snapshot.args.iter())
+                    .map(drop)
+                    .map_err(ExecutionError::from);
+                if tx.send((snapshot, result)).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+
+        Self {
+            requests,
+            results,
+            pending: false,
+        }
+    }
+
+    pub fn start(&mut self, snapshot: RunSnapshot, ctx: Context) {
+        // If the worker panicked, poll reports it
+        drop(self.requests.send((snapshot, ctx)));
+        self.pending = true;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Returns the snapshot once it's validated, or the validation error
+    pub fn poll(
+        &mut self,
+        localization: &Localization,
+    ) -> Option<Result<RunSnapshot, ExecutionError>> {
+        if !self.pending {
+            return None;
+        }
+
+        match self.results.try_recv() {
+            Ok((snapshot, result)) => {
+                self.pending = false;
+                Some(result.map(|()| snapshot))
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = false;
+                Some(Err(localization.error_validation_stopped.as_str().into()))
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+
+/// Previous values by argument id, most recent first
+pub type Values = Arc<HashMap<String, Vec<String>>>;
+
+/// Values entered in text arguments of earlier runs, see [`crate::Settings::value_history`].
+/// They are saved in the platform data directory.
+#[derive(Debug)]
+pub struct ValueHistory {
+    /// None if the platform has no data directory, or for tests
+    path: Option<PathBuf>,
+    /// Number of values kept for each argument
+    limit: usize,
+    values: Values,
+}
+
+impl ValueHistory {
+    /// Kept in memory only
+    pub fn new(limit: usize) -> Self {
+        Self {
+            path: None,
+            limit,
+            values: Values::default(),
+        }
+    }
+
+    pub fn load(app_name: &str, limit: usize) -> Self {
+        let mut history = Self::new(limit);
+        history.path = dirs::data_dir().map(|dir| dir.join(app_name).join("value_history.json"));
+        if let Some(values) = history
+            .path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|json| serde_json::from_slice(&json).ok())
+        {
+            history.values = Arc::new(values);
+        }
+        history
+    }
+
+    pub fn values(&self) -> Values {
+        self.values.clone()
+    }
+
+    /// Moves the `entered` values, pairs of argument id and value, to the front
+    /// of the history of their argument, forgetting the oldest ones over the limit
+    pub fn remember(&mut self, entered: Vec<(String, String)>) {
+        if entered.is_empty() {
+            return;
+        }
+
+        let values = Arc::make_mut(&mut self.values);
+        // Reversed, so the first value of an argument with multiple ends up at the front
+        for (id, value) in entered.into_iter().rev() {
+            let previous = values.entry(id).or_default();
+            previous.retain(|previous| *previous != value);
+            previous.insert(0, value);
+            previous.truncate(self.limit);
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                drop(fs::create_dir_all(dir));
+            }
+            if let Ok(json) = serde_json::to_vec_pretty(&*self.values) {
+                drop(fs::write(path, json));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,21 @@
+use super::ValueHistory;
+
+fn entered(values: &[(&str, &str)]) -> Vec<(String, String)> {
+    values
+        .iter()
+        .map(|(id, value)| (id.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn most_recent_first() {
+    let mut history = ValueHistory::new(3);
+    history.remember(entered(&[("dataset", "a"), ("out", "x")]));
+    history.remember(entered(&[("dataset", "b")]));
+    history.remember(entered(&[("dataset", "c"), ("dataset", "a")]));
+    history.remember(entered(&[("dataset", "d")]));
+
+    let values = history.values();
+    assert_eq!(values["dataset"], ["d", "c", "a"]);
+    assert_eq!(values["out"], ["x"]);
+}